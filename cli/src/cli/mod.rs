@@ -1,6 +1,6 @@
 use bip39::Mnemonic;
 use clap::{Parser, Subcommand};
-use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48};
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::Input;
 use std::io::{Error, ErrorKind};
@@ -24,6 +24,12 @@ pub struct Cli {
     #[arg(long, value_name = "Wallet Port")]
     wallet_port: Option<u16>,
 
+    #[arg(
+        long,
+        value_name = "Network used for consensus constants and addresses"
+    )]
+    pub network: Option<String>,
+
     #[command(subcommand)]
     pub action: RootCommands,
 }
@@ -305,6 +311,83 @@ pub enum RootCommands {
         #[arg(short, long)]
         auth_key: Bytes32,
     },
+    #[command(about = "Creates an unsigned transaction file from a master public key", long_about = None)]
+    ExportUnsignedTransaction {
+        #[arg(long)]
+        master_public_key: Bytes48,
+        #[arg(long)]
+        address: String,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long)]
+        fee: Option<u64>,
+        #[arg(long)]
+        output: String,
+        #[arg(long)]
+        search_limit: Option<u32>,
+    },
+    #[command(about = "Signs an unsigned transaction file without network access", long_about = None)]
+    SignUnsignedTransaction {
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        output: String,
+        #[arg(long)]
+        search_limit: Option<u32>,
+    },
+    #[command(about = "Combines signature files and submits the transaction", long_about = None)]
+    BroadcastSignedTransaction {
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        signatures: Vec<String>,
+    },
+    #[command(about = "Prints the address of an m-of-n multisig key set", long_about = None)]
+    MultisigAddress {
+        #[arg(long)]
+        threshold: u64,
+        #[arg(long)]
+        public_keys: Vec<Bytes48>,
+    },
+    #[command(about = "Prints the keys for a derivation path such as m/12381n/8444n/2n/0n", long_about = None)]
+    DeriveKey {
+        #[arg(long)]
+        path: String,
+        #[arg(long)]
+        master_public_key: Option<Bytes48>,
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+    #[command(about = "Searches wallet, owner and auth indexes for a public key, puzzle hash or address", long_about = None)]
+    SearchKeys {
+        #[arg(long)]
+        target: String,
+        #[arg(long)]
+        master_public_key: Option<Bytes48>,
+        #[arg(long)]
+        kinds: Vec<String>,
+        #[arg(
+            long,
+            help = "First index to search, for auth keys this is the pool wallet index and only auth index 0 is checked"
+        )]
+        start: Option<u32>,
+        #[arg(long)]
+        limit: Option<u32>,
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+    #[command(about = "Lists the certificates in an ssl folder and warns about expiring ones", long_about = None)]
+    CheckSsl {
+        #[arg(long)]
+        ssl_dir: String,
+        #[arg(long)]
+        warn_days: Option<u64>,
+    },
+    #[command(about = "Regenerates node certificates from the existing CA, keeping backups of the old ones", long_about = None)]
+    RotateSsl {
+        #[arg(long)]
+        ssl_dir: String,
+    },
     #[command(about = "Create a cold wallet or a PlotNFT wallet", long_about = None)]
     CreateWallet {
         #[command(subcommand)]
@@ -1,31 +1,43 @@
 pub mod cli;
 
-use blst::min_pk::SecretKey;
+use blst::min_pk::{PublicKey, SecretKey};
 use clap::Parser;
 use cli::*;
 use dg_xch_cli::wallet_commands::{
     create_cold_wallet, get_plotnft_ready_state, migrate_plot_nft, migrate_plot_nft_with_owner_key,
 };
+use dg_xch_cli::wallets::offline::{
+    broadcast_transaction, create_unsigned_transaction, sign_unsigned_transaction, SignatureSet,
+    UnsignedTransaction,
+};
 use dg_xch_cli::wallets::plotnft_utils::{get_plotnft_by_launcher_id, scrounge_for_plotnfts};
 use dg_xch_clients::api::full_node::{FullnodeAPI, FullnodeExtAPI};
 use dg_xch_clients::api::pool::create_pool_login_url;
 use dg_xch_clients::rpc::full_node::FullnodeClient;
 use dg_xch_clients::ClientSSLConfig;
-use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48};
+use dg_xch_core::blockchain::sized_bytes::{prep_hex_str, Bytes32, Bytes48};
 use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::config::root::ChiaRoot;
+use dg_xch_core::consensus::registry::CONSENSUS_CONSTANTS_REGISTRY;
+use dg_xch_core::ssl::{check_ssl_expiry, inspect_all_ssl, rotate_node_certs};
+use dg_xch_keys::explorer::{search_keys, DerivationPath, KeyKind, KeySource, SearchTarget};
 use dg_xch_keys::{
-    encode_puzzle_hash, key_from_mnemonic, master_sk_to_farmer_sk, master_sk_to_pool_sk,
-    master_sk_to_wallet_sk, master_sk_to_wallet_sk_unhardened,
+    decode_puzzle_hash, encode_puzzle_hash, fingerprint, key_from_mnemonic, master_sk_to_farmer_sk,
+    master_sk_to_pool_sk, master_sk_to_wallet_sk, master_sk_to_wallet_sk_unhardened,
 };
 use dg_xch_puzzles::clvm_puzzles::launcher_id_to_p2_puzzle_hash;
 use dg_xch_puzzles::p2_delegated_puzzle_or_hidden_puzzle::puzzle_hash_for_pk;
+use dg_xch_puzzles::p2_m_of_n_delegate_direct::puzzle_hash_for_m_of_n_public_keys;
 use dg_xch_serialize::{ChiaProtocolVersion, ChiaSerialize};
 use hex::decode;
-use log::{error, info, LevelFilter};
+use log::{debug, error, info, LevelFilter};
 use simple_logger::SimpleLogger;
 use std::env;
 use std::io::{Cursor, Error, ErrorKind};
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -50,6 +62,12 @@ async fn main() -> Result<(), Error> {
         ssl_key_path: format!("{}/{}", v, "full_node/private_full_node.crt"),
         ssl_ca_crt_path: format!("{}/{}", v, "full_node/private_full_node.crt"),
     });
+    //Registers the custom networks from the CHIA_ROOT config when there is one
+    if let Err(e) = ChiaRoot::from_env().and_then(|root| root.config()) {
+        debug!("Using the built in networks only, failed to load the chia config: {e}");
+    }
+    let constants =
+        CONSENSUS_CONSTANTS_REGISTRY.get_or_err(cli.network.as_deref().unwrap_or("mainnet"))?;
     match cli.action {
         RootCommands::PrintPlottingInfo { launcher_id } => {
             let client = Arc::new(FullnodeClient::new(&host, port, timeout, ssl, &None));
@@ -704,6 +722,210 @@ async fn main() -> Result<(), Error> {
                 create_pool_login_url(&target_pool, &[(auth_key.into(), launcher_id)]).await?;
             println!("{}", url);
         }
+        RootCommands::ExportUnsignedTransaction {
+            master_public_key,
+            address,
+            amount,
+            fee,
+            output,
+            search_limit,
+        } => {
+            let client = FullnodeClient::new(&host, port, timeout, ssl, &None);
+            let transaction = create_unsigned_transaction(
+                &client,
+                &master_public_key.into(),
+                search_limit.unwrap_or(500),
+                &decode_puzzle_hash(&address)?,
+                amount,
+                fee.unwrap_or_default(),
+                &constants,
+            )
+            .await?;
+            transaction.save(Path::new(&output))?;
+            info!(
+                "Saved unsigned transaction {} with {} signing targets to {output}",
+                transaction.id(),
+                transaction.signing_targets.len()
+            );
+        }
+        RootCommands::SignUnsignedTransaction {
+            input,
+            output,
+            search_limit,
+        } => {
+            let transaction = UnsignedTransaction::load(Path::new(&input), &constants)?;
+            for addition in transaction.additions()? {
+                info!(
+                    "Creates {} mojos at {}",
+                    addition.amount,
+                    encode_puzzle_hash(&addition.puzzle_hash, &constants.bech32_prefix)?
+                );
+            }
+            info!("Fee: {} mojos", transaction.fee()?);
+            let master_key = key_from_mnemonic(&prompt_for_mnemonic()?)?;
+            let signatures = sign_unsigned_transaction(
+                &transaction,
+                &master_key,
+                search_limit.unwrap_or(500),
+                &constants,
+            )?;
+            signatures.save(Path::new(&output))?;
+            info!(
+                "Saved {} of {} signatures to {output}",
+                signatures.signatures.len(),
+                transaction.signing_targets.len()
+            );
+        }
+        RootCommands::BroadcastSignedTransaction { input, signatures } => {
+            let client = FullnodeClient::new(&host, port, timeout, ssl, &None);
+            let transaction = UnsignedTransaction::load(Path::new(&input), &constants)?;
+            let signature_sets = signatures
+                .iter()
+                .map(|s| SignatureSet::load(Path::new(s)))
+                .collect::<Result<Vec<SignatureSet>, Error>>()?;
+            let (name, status) =
+                broadcast_transaction(&client, &transaction, &signature_sets, &constants).await?;
+            info!("SpendBundle {name} submitted with status {status:?}");
+        }
+        RootCommands::MultisigAddress {
+            threshold,
+            public_keys,
+        } => {
+            let puzzle_hash = puzzle_hash_for_m_of_n_public_keys(threshold, &public_keys)?;
+            info!("{threshold} of {} multisig", public_keys.len());
+            info!("Puzzle Hash: {puzzle_hash}");
+            info!(
+                "Address: {}",
+                encode_puzzle_hash(&puzzle_hash, &constants.bech32_prefix)?
+            );
+        }
+        RootCommands::DeriveKey {
+            path,
+            master_public_key,
+            prefix,
+        } => {
+            let path = DerivationPath::from_str(&path)?;
+            let master_key;
+            let master_public_key = master_public_key.map(PublicKey::from);
+            let source = match &master_public_key {
+                Some(public_key) => KeySource::Observer(public_key),
+                None => {
+                    master_key = key_from_mnemonic(&prompt_for_mnemonic()?)?;
+                    KeySource::Secret(&master_key)
+                }
+            };
+            let key = source.derive(&path)?;
+            info!(
+                "Master Fingerprint: {}",
+                fingerprint(&source.master_public_key())
+            );
+            info!("Path: {}", key.path);
+            info!("Fingerprint: {}", key.fingerprint);
+            info!("Public Key: {}", key.public_key);
+            info!("Synthetic Public Key: {}", key.synthetic_public_key);
+            info!("Puzzle Hash: {}", key.puzzle_hash);
+            info!(
+                "Address: {}",
+                key.address(prefix.as_deref().unwrap_or(&constants.bech32_prefix))?
+            );
+        }
+        RootCommands::SearchKeys {
+            target,
+            master_public_key,
+            kinds,
+            start,
+            limit,
+            prefix,
+        } => {
+            let target = if target.starts_with("xch") || target.starts_with("txch") {
+                SearchTarget::PuzzleHash(decode_puzzle_hash(&target)?)
+            } else if prep_hex_str(&target).len() == 96 {
+                SearchTarget::PublicKey(Bytes48::from(target))
+            } else if prep_hex_str(&target).len() == 64 {
+                SearchTarget::PuzzleHash(Bytes32::from(target))
+            } else {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Target must be a public key, puzzle hash or address",
+                ));
+            };
+            let kinds = if kinds.is_empty() {
+                KeyKind::ALL.to_vec()
+            } else {
+                kinds
+                    .iter()
+                    .map(|k| KeyKind::from_str(k))
+                    .collect::<Result<Vec<KeyKind>, Error>>()?
+            };
+            let master_key;
+            let master_public_key = master_public_key.map(PublicKey::from);
+            let source = match &master_public_key {
+                Some(public_key) => KeySource::Observer(public_key),
+                None => {
+                    master_key = key_from_mnemonic(&prompt_for_mnemonic()?)?;
+                    KeySource::Secret(&master_key)
+                }
+            };
+            let start = start.unwrap_or_default();
+            let end = start.saturating_add(limit.unwrap_or(500));
+            info!(
+                "Master Fingerprint: {}",
+                fingerprint(&source.master_public_key())
+            );
+            let matches = search_keys(&source, &target, &kinds, start, end)?;
+            if matches.is_empty() {
+                info!("No matching keys found in indexes {start} to {end}");
+            }
+            for m in matches {
+                info!(
+                    "{:?} index {} ({}): {}",
+                    m.kind,
+                    m.index,
+                    if m.hardened { "hardened" } else { "unhardened" },
+                    m.key.path
+                );
+                info!("\tFingerprint: {}", m.key.fingerprint);
+                info!("\tPublic Key: {}", m.key.public_key);
+                info!(
+                    "\tAddress: {}",
+                    m.key
+                        .address(prefix.as_deref().unwrap_or(&constants.bech32_prefix))?
+                );
+            }
+        }
+        RootCommands::CheckSsl { ssl_dir, warn_days } => {
+            let ssl_dir = Path::new(&ssl_dir);
+            for cert in inspect_all_ssl(ssl_dir)? {
+                info!("{:?}", cert.path);
+                info!("\tSubject: {}", cert.subject);
+                info!("\tIssuer: {}", cert.issuer);
+                info!("\tNode Id: {}", cert.node_id);
+                info!(
+                    "\tExpires: {}",
+                    match cert.expires_in() {
+                        Some(remaining) => format!("in {} days", remaining.as_secs() / 86400),
+                        None => "expired".to_string(),
+                    }
+                );
+                match cert.key_matches {
+                    Some(true) => info!("\tKey: matches"),
+                    Some(false) => error!("\tKey: does not match {:?}", cert.key_path),
+                    None => info!("\tKey: not found"),
+                }
+            }
+            let warn_within = Duration::from_secs(warn_days.unwrap_or(30) * 86400);
+            let expiring = check_ssl_expiry(ssl_dir, warn_within)?;
+            if expiring.is_empty() {
+                info!(
+                    "No certificates expire within {} days",
+                    warn_within.as_secs() / 86400
+                );
+            }
+        }
+        RootCommands::RotateSsl { ssl_dir } => {
+            let backup_dir = rotate_node_certs(Path::new(&ssl_dir))?;
+            info!("Rotated node certificates, the old ones were backed up to {backup_dir:?}");
+        }
         RootCommands::CreateWallet { action } => match action {
             WalletAction::WithNFT { .. } => {}
             WalletAction::Cold => create_cold_wallet()?,
@@ -0,0 +1,546 @@
+use dg_xch_core::blockchain::coin::Coin;
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, SizedBytes};
+use log::debug;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io::{Error, ErrorKind};
+
+pub struct CoinSelectionConstraints {
+    pub exclude_coins: Vec<Coin>,
+    pub exclude_coin_amounts: Vec<u64>,
+    pub min_coin_amount: u64,
+    pub max_coin_amount: u64,
+    pub max_coin_count: usize,
+}
+impl Default for CoinSelectionConstraints {
+    fn default() -> Self {
+        Self {
+            exclude_coins: vec![],
+            exclude_coin_amounts: vec![],
+            min_coin_amount: 0,
+            max_coin_amount: u64::MAX,
+            max_coin_count: 500,
+        }
+    }
+}
+impl CoinSelectionConstraints {
+    //Returns the coins allowed by these constraints sorted from smallest to largest
+    pub fn filter(&self, coins: &[Coin]) -> Vec<Coin> {
+        let mut valid_coins: Vec<Coin> = coins
+            .iter()
+            .filter(|c| {
+                !self.exclude_coins.contains(c)
+                    && c.amount >= self.min_coin_amount
+                    && c.amount <= self.max_coin_amount
+                    && !self.exclude_coin_amounts.contains(&c.amount)
+            })
+            .cloned()
+            .collect();
+        valid_coins.sort_by_key(|c| c.amount);
+        valid_coins
+    }
+
+    fn checked_candidates(&self, coins: &[Coin], target: u64) -> Result<Vec<Coin>, Error> {
+        let valid_coins = self.filter(coins);
+        let sum_spendable_coins: u128 = valid_coins.iter().map(|c| c.amount as u128).sum();
+        if sum_spendable_coins < target as u128 {
+            Err(Error::new(ErrorKind::InvalidInput, format!("Transaction for {target} is greater than spendable balance of {sum_spendable_coins}. There may be other transactions pending or our minimum coin amount is too high.")))
+        } else if target == 0 && sum_spendable_coins == 0 {
+            Err(Error::new(ErrorKind::InvalidInput, "No coins available to spend, you can not create a coin with an amount of 0, without already having coins."))
+        } else {
+            Ok(valid_coins)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CoinSelection {
+    pub strategy: String,
+    pub target: u64,
+    pub coins: HashSet<Coin>,
+    pub total: u64,
+    pub change: u64,
+    pub reasons: Vec<String>,
+}
+impl CoinSelection {
+    pub(crate) fn new(
+        strategy: &str,
+        target: u64,
+        coins: HashSet<Coin>,
+        reasons: Vec<String>,
+    ) -> Result<Self, Error> {
+        let total = coins
+            .iter()
+            .try_fold(0u64, |total, c| total.checked_add(c.amount))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    "Selected coin amounts overflow a u64",
+                )
+            })?;
+        Ok(Self {
+            strategy: strategy.to_string(),
+            target,
+            coins,
+            total,
+            change: total.saturating_sub(target),
+            reasons,
+        })
+    }
+}
+
+pub trait CoinSelector {
+    fn name(&self) -> &str;
+    fn select(
+        &self,
+        coins: &[Coin],
+        target: u64,
+        constraints: &CoinSelectionConstraints,
+    ) -> Result<CoinSelection, Error>;
+}
+
+//Exact match, then all smaller coins, then knapsack, then the smallest coin over the target
+#[derive(Default)]
+pub struct DefaultCoinSelector {
+    pub seed: Option<Bytes32>,
+}
+impl CoinSelector for DefaultCoinSelector {
+    fn name(&self) -> &str {
+        "default"
+    }
+
+    fn select(
+        &self,
+        coins: &[Coin],
+        target: u64,
+        constraints: &CoinSelectionConstraints,
+    ) -> Result<CoinSelection, Error> {
+        debug!("About to select coins for amount {target}");
+        let valid_spendable_coins = constraints.checked_candidates(coins, target)?;
+        let max_num_coins = constraints.max_coin_count;
+        if let Some(c) = check_for_exact_match(&valid_spendable_coins, target) {
+            return CoinSelection::new(
+                self.name(),
+                target,
+                HashSet::from([c]),
+                vec!["Selected coin with an exact match".to_string()],
+            );
+        }
+        let mut smaller_coin_sum = 0u128;
+        let mut smaller_coins = vec![];
+        for coin in &valid_spendable_coins {
+            if coin.amount < target {
+                smaller_coin_sum += coin.amount as u128;
+                smaller_coins.push(coin.clone());
+            }
+        }
+        if smaller_coin_sum == target as u128 && smaller_coins.len() < max_num_coins && target != 0
+        {
+            CoinSelection::new(
+                self.name(),
+                target,
+                HashSet::from_iter(smaller_coins),
+                vec!["Selected all smaller coins because they equal the target".to_string()],
+            )
+        } else if smaller_coin_sum > target as u128 {
+            if let Some(coin_set) = knapsack_coin_algorithm(
+                &smaller_coins,
+                target,
+                constraints.max_coin_amount,
+                max_num_coins,
+                self.seed.as_ref().map(|s| s.as_slice()),
+            ) {
+                return CoinSelection::new(
+                    self.name(),
+                    target,
+                    coin_set,
+                    vec!["Selected coins from knapsack algorithm".to_string()],
+                );
+            }
+            match sum_largest_coins(target as u128, &smaller_coins) {
+                Some(coin_set) if coin_set.len() <= max_num_coins => CoinSelection::new(
+                    self.name(),
+                    target,
+                    coin_set,
+                    vec!["Knapsack failed, summed the largest smaller coins".to_string()],
+                ),
+                _ => match select_smallest_coin_over_target(target, &valid_spendable_coins) {
+                    Some(coin) => CoinSelection::new(
+                        self.name(),
+                        target,
+                        HashSet::from([coin]),
+                        vec![format!(
+                            "Smaller coins would need more than {max_num_coins} coins, selected closest greater coin"
+                        )],
+                    ),
+                    None => Err(Error::new(ErrorKind::InvalidInput, format!("Transaction of {target} mojo would use more than {max_num_coins} coins. Try sending a smaller amount"))),
+                },
+            }
+        } else {
+            match select_smallest_coin_over_target(target, &valid_spendable_coins) {
+                Some(coin) => CoinSelection::new(
+                    self.name(),
+                    target,
+                    HashSet::from([coin]),
+                    vec!["Selected closest greater coin".to_string()],
+                ),
+                None => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Too many coins are required to make this transaction",
+                )),
+            }
+        }
+    }
+}
+
+//Depth first search for the set of coins with the least change, bounded by max_tries
+pub struct BranchAndBoundCoinSelector {
+    pub max_tries: usize,
+}
+impl Default for BranchAndBoundCoinSelector {
+    fn default() -> Self {
+        Self { max_tries: 100_000 }
+    }
+}
+impl CoinSelector for BranchAndBoundCoinSelector {
+    fn name(&self) -> &str {
+        "branch_and_bound"
+    }
+
+    fn select(
+        &self,
+        coins: &[Coin],
+        target: u64,
+        constraints: &CoinSelectionConstraints,
+    ) -> Result<CoinSelection, Error> {
+        let mut candidates = constraints.checked_candidates(coins, target)?;
+        candidates.sort_by_key(|c| Reverse(c.amount));
+        let mut remaining = vec![0u128; candidates.len() + 1];
+        for i in (0..candidates.len()).rev() {
+            remaining[i] = remaining[i + 1] + candidates[i].amount as u128;
+        }
+        let mut search = BranchAndBoundSearch {
+            candidates: &candidates,
+            remaining: &remaining,
+            target: target as u128,
+            max_coin_count: constraints.max_coin_count,
+            tries_left: self.max_tries,
+            selected: vec![],
+            best: None,
+        };
+        search.search(0, 0);
+        let exhausted = search.tries_left == 0;
+        match search.best {
+            Some((change, indexes)) => {
+                let mut reasons = vec![format!(
+                    "Found {} coins leaving {change} mojos of change",
+                    indexes.len()
+                )];
+                if exhausted {
+                    reasons.push(format!(
+                        "Search stopped after {} tries, result may not be minimal",
+                        self.max_tries
+                    ));
+                }
+                CoinSelection::new(
+                    self.name(),
+                    target,
+                    indexes.into_iter().map(|i| candidates[i].clone()).collect(),
+                    reasons,
+                )
+            }
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "No set of at most {} coins reaches {target} mojos",
+                    constraints.max_coin_count
+                ),
+            )),
+        }
+    }
+}
+
+struct BranchAndBoundSearch<'a> {
+    candidates: &'a [Coin],
+    remaining: &'a [u128],
+    target: u128,
+    max_coin_count: usize,
+    tries_left: usize,
+    selected: Vec<usize>,
+    best: Option<(u128, Vec<usize>)>,
+}
+impl BranchAndBoundSearch<'_> {
+    fn search(&mut self, index: usize, sum: u128) {
+        if self.tries_left == 0 || matches!(self.best, Some((0, _))) {
+            return;
+        }
+        self.tries_left -= 1;
+        if sum >= self.target {
+            let change = sum - self.target;
+            if self.best.as_ref().map(|(c, _)| change < *c).unwrap_or(true) {
+                self.best = Some((change, self.selected.clone()));
+            }
+            return;
+        }
+        if index >= self.candidates.len()
+            || self.selected.len() >= self.max_coin_count
+            || sum + self.remaining[index] < self.target
+        {
+            return;
+        }
+        if let Some((best_change, _)) = &self.best {
+            if sum + self.candidates[index].amount as u128 >= self.target + best_change {
+                //Including this coin cannot beat the best result, only try without it
+                self.search(index + 1, sum);
+                return;
+            }
+        }
+        self.selected.push(index);
+        self.search(index + 1, sum + self.candidates[index].amount as u128);
+        self.selected.pop();
+        self.search(index + 1, sum);
+    }
+}
+
+//Spends every coin below the dust threshold along with the payment, topping up with the largest coins
+pub struct DustConsolidationCoinSelector {
+    pub dust_threshold: u64,
+}
+impl CoinSelector for DustConsolidationCoinSelector {
+    fn name(&self) -> &str {
+        "dust_consolidation"
+    }
+
+    fn select(
+        &self,
+        coins: &[Coin],
+        target: u64,
+        constraints: &CoinSelectionConstraints,
+    ) -> Result<CoinSelection, Error> {
+        let candidates = constraints.checked_candidates(coins, target)?;
+        let (dust, mut larger): (Vec<Coin>, Vec<Coin>) = candidates
+            .into_iter()
+            .partition(|c| c.amount < self.dust_threshold);
+        larger.sort_by_key(|c| Reverse(c.amount));
+        let mut selected = HashSet::new();
+        let mut total = 0u128;
+        let mut reasons = vec![];
+        //Leave room for at least one larger coin in case the dust does not cover the target
+        let dust_limit = constraints.max_coin_count.saturating_sub(1).max(1);
+        for coin in dust.into_iter().take(dust_limit) {
+            total += coin.amount as u128;
+            selected.insert(coin);
+        }
+        if !selected.is_empty() {
+            reasons.push(format!(
+                "Consolidated {} coins below {} mojos",
+                selected.len(),
+                self.dust_threshold
+            ));
+        }
+        let mut added = 0;
+        for coin in larger {
+            if total >= target as u128 && !selected.is_empty() {
+                break;
+            }
+            if selected.len() >= constraints.max_coin_count {
+                break;
+            }
+            total += coin.amount as u128;
+            selected.insert(coin);
+            added += 1;
+        }
+        if total < target as u128 || selected.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Could not reach {target} mojos with at most {} coins",
+                    constraints.max_coin_count
+                ),
+            ));
+        }
+        if added > 0 {
+            reasons.push(format!("Added {added} larger coins to reach the target"));
+        }
+        CoinSelection::new(self.name(), target, selected, reasons)
+    }
+}
+
+//Picks coins in a random order so selection does not reveal wallet structure
+#[derive(Default)]
+pub struct RandomCoinSelector {
+    pub seed: Option<Bytes32>,
+}
+impl CoinSelector for RandomCoinSelector {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn select(
+        &self,
+        coins: &[Coin],
+        target: u64,
+        constraints: &CoinSelectionConstraints,
+    ) -> Result<CoinSelection, Error> {
+        let mut candidates = constraints.checked_candidates(coins, target)?;
+        let mut rand = match &self.seed {
+            Some(seed) => StdRng::from_seed(*seed.to_sized_bytes()),
+            None => StdRng::from_entropy(),
+        };
+        candidates.shuffle(&mut rand);
+        let mut selected = HashSet::new();
+        let mut total = 0u128;
+        for coin in candidates {
+            if total >= target as u128 && !selected.is_empty() {
+                break;
+            }
+            if selected.len() >= constraints.max_coin_count {
+                break;
+            }
+            total += coin.amount as u128;
+            selected.insert(coin);
+        }
+        if total < target as u128 || selected.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Random selection could not reach {target} mojos with at most {} coins",
+                    constraints.max_coin_count
+                ),
+            ));
+        }
+        let reasons = vec![format!("Selected {} coins in random order", selected.len())];
+        CoinSelection::new(self.name(), target, selected, reasons)
+    }
+}
+
+fn check_for_exact_match(coin_list: &[Coin], target: u64) -> Option<Coin> {
+    for coin in coin_list {
+        if coin.amount == target {
+            return Some(coin.clone());
+        }
+    }
+    None
+}
+
+fn select_smallest_coin_over_target(target: u64, sorted_coin_list: &[Coin]) -> Option<Coin> {
+    for coin in sorted_coin_list.iter() {
+        if coin.amount >= target {
+            return Some(coin.clone());
+        }
+    }
+    None
+}
+
+fn sum_largest_coins(target: u128, sorted_coins: &[Coin]) -> Option<HashSet<Coin>> {
+    let mut total_value = 0u128;
+    let mut selected_coins = HashSet::default();
+    for coin in sorted_coins {
+        total_value += coin.amount as u128;
+        selected_coins.insert(coin.clone());
+        if total_value >= target {
+            return Some(selected_coins);
+        }
+    }
+    None
+}
+
+fn knapsack_coin_algorithm(
+    smaller_coins: &[Coin],
+    target: u64,
+    max_coin_amount: u64,
+    max_num_coins: usize,
+    seed: Option<&[u8]>,
+) -> Option<HashSet<Coin>> {
+    let mut best_set_sum = max_coin_amount;
+    let mut best_set_of_coins: Option<HashSet<Coin>> = None;
+    let seed = Bytes32::new(seed.unwrap_or(b"knapsack seed"));
+    let mut rand = StdRng::from_seed(*seed.to_sized_bytes());
+    for _ in 0..1000 {
+        let mut selected_coins = HashSet::default();
+        let mut selected_coins_sum = 0;
+        let mut n_pass = 0;
+        let mut target_reached = false;
+        while n_pass < 2 && !target_reached {
+            for coin in smaller_coins {
+                if (n_pass == 0 && rand.gen::<bool>())
+                    || (n_pass == 1 && !selected_coins.contains(coin))
+                {
+                    if selected_coins.len() > max_num_coins {
+                        break;
+                    }
+                    selected_coins_sum += coin.amount;
+                    selected_coins.insert(coin.clone());
+                    match selected_coins_sum.cmp(&target) {
+                        std::cmp::Ordering::Greater => {
+                            target_reached = true;
+                            if selected_coins_sum < best_set_sum {
+                                best_set_of_coins = Some(selected_coins.clone());
+                                best_set_sum = selected_coins_sum;
+                                selected_coins_sum -= coin.amount;
+                                selected_coins.remove(coin);
+                            }
+                        }
+                        std::cmp::Ordering::Less => {}
+                        std::cmp::Ordering::Equal => return Some(selected_coins),
+                    }
+                }
+            }
+            n_pass += 1;
+        }
+    }
+    best_set_of_coins
+}
+
+#[test]
+fn test_coin_selectors() {
+    let coins: Vec<Coin> = [1u64, 2, 5, 40, 60, 100, 250]
+        .iter()
+        .map(|amount| Coin {
+            parent_coin_info: Bytes32::new(&[*amount as u8; 32]),
+            puzzle_hash: Default::default(),
+            amount: *amount,
+        })
+        .collect();
+    let constraints = CoinSelectionConstraints::default();
+    let selection = DefaultCoinSelector::default()
+        .select(&coins, 100, &constraints)
+        .unwrap();
+    assert_eq!(selection.total, 100);
+    let selection = BranchAndBoundCoinSelector::default()
+        .select(&coins, 105, &constraints)
+        .unwrap();
+    assert_eq!(selection.change, 0);
+    let selection = DustConsolidationCoinSelector { dust_threshold: 10 }
+        .select(&coins, 50, &constraints)
+        .unwrap();
+    assert_eq!(selection.coins.len(), 4);
+    assert!(selection.total >= 50);
+    let selection = RandomCoinSelector {
+        seed: Some(Bytes32::new(&[9u8; 32])),
+    }
+    .select(&coins, 300, &constraints)
+    .unwrap();
+    assert!(selection.total >= 300);
+    let limited = CoinSelectionConstraints {
+        exclude_coin_amounts: vec![250],
+        max_coin_count: 2,
+        ..Default::default()
+    };
+    assert!(BranchAndBoundCoinSelector::default()
+        .select(&coins, 300, &limited)
+        .is_err());
+    let huge: Vec<Coin> = [u64::MAX, u64::MAX - 1]
+        .iter()
+        .map(|amount| Coin {
+            parent_coin_info: Bytes32::default(),
+            puzzle_hash: Bytes32::default(),
+            amount: *amount,
+        })
+        .collect();
+    let overflow = CoinSelection::new("test", 1, huge.into_iter().collect(), vec![]);
+    assert_eq!(overflow.unwrap_err().kind(), ErrorKind::InvalidData);
+}
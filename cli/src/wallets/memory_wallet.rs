@@ -1,3 +1,6 @@
+use crate::wallets::coin_selection::{
+    CoinSelection, CoinSelectionConstraints, CoinSelector, DefaultCoinSelector,
+};
 use crate::wallets::common::DerivationRecord;
 use crate::wallets::{SecretKeyStore, Wallet, WalletInfo, WalletStore};
 use async_trait::async_trait;
@@ -8,15 +11,13 @@ use dg_xch_clients::ClientSSLConfig;
 use dg_xch_core::blockchain::coin::Coin;
 use dg_xch_core::blockchain::coin_record::CoinRecord;
 use dg_xch_core::blockchain::coin_spend::CoinSpend;
-use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48, SizedBytes};
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48};
 use dg_xch_core::blockchain::wallet_type::WalletType;
 use dg_xch_keys::{master_sk_to_wallet_sk, master_sk_to_wallet_sk_unhardened};
 use dg_xch_puzzles::p2_delegated_puzzle_or_hidden_puzzle::{
     calculate_synthetic_secret_key, puzzle_hash_for_pk, DEFAULT_HIDDEN_PUZZLE_HASH,
 };
-use log::{debug, error, info};
-use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng};
+use log::{error, info};
 use std::collections::{HashMap, HashSet};
 use std::io::{Error, ErrorKind};
 use std::sync::atomic::{AtomicU32, Ordering};
@@ -168,116 +169,42 @@ impl WalletStore for MemoryWalletStore {
         max_coin_amount: u64,
         exclude_coin_amounts: Option<&[u64]>,
     ) -> Result<HashSet<Coin>, Error> {
+        let constraints = CoinSelectionConstraints {
+            exclude_coins: exclude.unwrap_or_default().to_vec(),
+            exclude_coin_amounts: exclude_coin_amounts.unwrap_or_default().to_vec(),
+            min_coin_amount: min_coin_amount.unwrap_or(0),
+            max_coin_amount,
+            ..Default::default()
+        };
+        let selection = self
+            .select_coins_with(amount, &DefaultCoinSelector::default(), &constraints)
+            .await?;
+        Ok(selection.coins)
+    }
+
+    async fn select_coins_with(
+        &self,
+        amount: u64,
+        selector: &(dyn CoinSelector + Send + Sync),
+        constraints: &CoinSelectionConstraints,
+    ) -> Result<CoinSelection, Error> {
         let spendable_amount = self.get_spendable_balance().await;
-        let exclude = exclude.unwrap_or_default();
-        let min_coin_amount = min_coin_amount.unwrap_or(0);
-        let exclude_coin_amounts = exclude_coin_amounts.unwrap_or_default();
         if amount as u128 > spendable_amount {
             Err(Error::new(ErrorKind::InvalidInput, format!("Can't select amount higher than our spendable balance.  Amount: {amount}, spendable: {spendable_amount}")))
         } else {
-            debug!("About to select coins for amount {amount}");
-            let max_num_coins = 500;
-            let mut sum_spendable_coins = 0;
-            let mut valid_spendable_coins: Vec<Coin> = vec![];
-            for coin_record in self.unspent_coins.values() {
-                if exclude.contains(&coin_record.coin) {
-                    continue;
-                }
-                if coin_record.coin.amount < min_coin_amount
-                    || coin_record.coin.amount > max_coin_amount
-                {
-                    continue;
-                }
-                if exclude_coin_amounts.contains(&coin_record.coin.amount) {
-                    continue;
-                }
-                sum_spendable_coins += coin_record.coin.amount;
-                valid_spendable_coins.push(coin_record.coin.clone());
-            }
-            if sum_spendable_coins < amount {
-                return Err(Error::new(ErrorKind::InvalidInput, format!("Transaction for {amount} is greater than spendable balance of {sum_spendable_coins}. There may be other transactions pending or our minimum coin amount is too high.")));
-            }
-            if amount == 0 && sum_spendable_coins == 0 {
-                return Err(Error::new(ErrorKind::InvalidInput, "No coins available to spend, you can not create a coin with an amount of 0, without already having coins."));
-            }
-            valid_spendable_coins.sort_by(|f, s| f.amount.cmp(&s.amount));
-            match check_for_exact_match(&valid_spendable_coins, amount) {
-                Some(c) => {
-                    info!("Selected coin with an exact match: {:?}", c);
-                    Ok(HashSet::from([c]))
-                }
-                None => {
-                    let mut smaller_coin_sum = 0; //coins smaller than target.
-                    let mut all_sum = 0; //coins smaller than target.
-                    let mut smaller_coins = vec![];
-                    for coin in &valid_spendable_coins {
-                        if coin.amount < amount {
-                            smaller_coin_sum += coin.amount;
-                            smaller_coins.push(coin.clone());
-                        }
-                        all_sum += coin.amount;
-                    }
-                    if smaller_coin_sum == amount
-                        && smaller_coins.len() < max_num_coins
-                        && amount != 0
-                    {
-                        debug!("Selected all smaller coins because they equate to an exact match of the target: {:?}", smaller_coins);
-                        Ok(HashSet::from_iter(smaller_coins.iter().cloned()))
-                    } else if smaller_coin_sum < amount {
-                        let smallest_coin =
-                            select_smallest_coin_over_target(amount, &valid_spendable_coins);
-                        if let Some(smallest_coin) = smallest_coin {
-                            debug!("Selected closest greater coin: {}", smallest_coin.name());
-                            Ok(HashSet::from([smallest_coin]))
-                        } else {
-                            return Err(Error::new(ErrorKind::InvalidInput, format!("Transaction of {amount} mojo is greater than available sum {all_sum} mojos.")));
-                        }
-                    } else if smaller_coin_sum > amount {
-                        let mut coin_set = knapsack_coin_algorithm(
-                            &smaller_coins,
-                            amount,
-                            max_coin_amount,
-                            max_num_coins,
-                            None,
-                        );
-                        debug!("Selected coins from knapsack algorithm: {:?}", coin_set);
-                        if coin_set.is_none() {
-                            coin_set = sum_largest_coins(amount as u128, &smaller_coins);
-                            if coin_set.is_none()
-                                || coin_set.as_ref().map(|v| v.len()).unwrap_or_default()
-                                    > max_num_coins
-                            {
-                                let greater_coin = select_smallest_coin_over_target(
-                                    amount,
-                                    &valid_spendable_coins,
-                                );
-                                if let Some(greater_coin) = greater_coin {
-                                    coin_set = Some(HashSet::from([greater_coin]));
-                                } else {
-                                    return Err(Error::new(ErrorKind::InvalidInput, format!("Transaction of {amount} mojo would use more than {max_num_coins} coins. Try sending a smaller amount")));
-                                }
-                            }
-                        }
-                        coin_set.ok_or_else(|| {
-                            Error::new(
-                                ErrorKind::InvalidInput,
-                                "Failed to select coins for transaction",
-                            )
-                        })
-                    } else {
-                        match select_smallest_coin_over_target(amount, &valid_spendable_coins) {
-                            Some(coin) => {
-                                debug!("Resorted to selecting smallest coin over target due to dust.: {:?}", coin);
-                                Ok(HashSet::from([coin]))
-                            }
-                            None => Err(Error::new(
-                                ErrorKind::InvalidInput,
-                                "Too many coins are required to make this transaction",
-                            )),
-                        }
-                    }
-                }
-            }
+            let coins: Vec<Coin> = self
+                .unspent_coins
+                .values()
+                .map(|c| c.coin.clone())
+                .collect();
+            let selection = selector.select(&coins, amount, constraints)?;
+            info!(
+                "Selected {} coins for {amount} mojos using {}: {:?}",
+                selection.coins.len(),
+                selection.strategy,
+                selection.reasons
+            );
+            Ok(selection)
         }
     }
 
@@ -342,84 +269,6 @@ impl WalletStore for MemoryWalletStore {
     }
 }
 
-fn check_for_exact_match(coin_list: &[Coin], target: u64) -> Option<Coin> {
-    for coin in coin_list {
-        if coin.amount == target {
-            return Some(coin.clone());
-        }
-    }
-    None
-}
-
-fn select_smallest_coin_over_target(target: u64, sorted_coin_list: &[Coin]) -> Option<Coin> {
-    for coin in sorted_coin_list.iter() {
-        if coin.amount >= target {
-            return Some(coin.clone());
-        }
-    }
-    None
-}
-
-fn sum_largest_coins(target: u128, sorted_coins: &[Coin]) -> Option<HashSet<Coin>> {
-    let mut total_value = 0u128;
-    let mut selected_coins = HashSet::default();
-    for coin in sorted_coins {
-        total_value += coin.amount as u128;
-        selected_coins.insert(coin.clone());
-        if total_value >= target {
-            return Some(selected_coins);
-        }
-    }
-    None
-}
-
-fn knapsack_coin_algorithm(
-    smaller_coins: &[Coin],
-    target: u64,
-    max_coin_amount: u64,
-    max_num_coins: usize,
-    seed: Option<&[u8]>,
-) -> Option<HashSet<Coin>> {
-    let mut best_set_sum = max_coin_amount;
-    let mut best_set_of_coins: Option<HashSet<Coin>> = None;
-    let seed = Bytes32::new(seed.unwrap_or(b"knapsack seed"));
-    let mut rand = StdRng::from_seed(*seed.to_sized_bytes());
-    for _ in 0..1000 {
-        let mut selected_coins = HashSet::default();
-        let mut selected_coins_sum = 0;
-        let mut n_pass = 0;
-        let mut target_reached = false;
-        while n_pass < 2 && !target_reached {
-            for coin in smaller_coins {
-                if (n_pass == 0 && rand.gen::<bool>())
-                    || (n_pass == 1 && !selected_coins.contains(coin))
-                {
-                    if selected_coins.len() > max_num_coins {
-                        break;
-                    }
-                    selected_coins_sum += coin.amount;
-                    selected_coins.insert(coin.clone());
-                    match selected_coins_sum.cmp(&target) {
-                        std::cmp::Ordering::Greater => {
-                            target_reached = true;
-                            if selected_coins_sum < best_set_sum {
-                                best_set_of_coins = Some(selected_coins.clone());
-                                best_set_sum = selected_coins_sum;
-                                selected_coins_sum -= coin.amount;
-                                selected_coins.remove(coin);
-                            }
-                        }
-                        std::cmp::Ordering::Less => {}
-                        std::cmp::Ordering::Equal => return Some(selected_coins),
-                    }
-                }
-            }
-            n_pass += 1;
-        }
-    }
-    best_set_of_coins
-}
-
 pub struct MemoryWallet {
     //A wallet that is lost on restarts
     info: WalletInfo<MemoryWalletStore>,
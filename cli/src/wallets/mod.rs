@@ -1,3 +1,4 @@
+use crate::wallets::coin_selection::{CoinSelection, CoinSelectionConstraints, CoinSelector};
 use crate::wallets::common::{sign_coin_spends, DerivationRecord};
 use async_trait::async_trait;
 use blst::min_pk::SecretKey;
@@ -33,9 +34,12 @@ use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
 
+pub mod coin_selection;
 pub mod common;
 pub mod memory_wallet;
+pub mod offline;
 pub mod plotnft_utils;
+pub mod transaction_manager;
 
 #[derive(Default)]
 pub struct SecretKeyStore {
@@ -93,6 +97,33 @@ pub trait WalletStore {
         max_coin_amount: u64,
         exclude_coin_amounts: Option<&[u64]>,
     ) -> Result<HashSet<Coin>, Error>;
+    //Stores that can list their spendable coins should override this to run the selector, the
+    //default falls back to select_coins and reports the selection under that name
+    async fn select_coins_with(
+        &self,
+        amount: u64,
+        selector: &(dyn CoinSelector + Send + Sync),
+        constraints: &CoinSelectionConstraints,
+    ) -> Result<CoinSelection, Error> {
+        let coins = self
+            .select_coins(
+                amount,
+                Some(&constraints.exclude_coins),
+                Some(constraints.min_coin_amount),
+                constraints.max_coin_amount,
+                Some(&constraints.exclude_coin_amounts),
+            )
+            .await?;
+        CoinSelection::new(
+            "select_coins",
+            amount,
+            coins,
+            vec![format!(
+                "Store does not expose its coins, {} selector was not used",
+                selector.name()
+            )],
+        )
+    }
     async fn populate_secret_key_for_puzzle_hash(
         &self,
         puz_hash: &Bytes32,
@@ -271,6 +302,7 @@ pub trait Wallet<T: WalletStore + Send + Sync, C> {
         exclude_coin_amounts: Option<&[u64]>,
         exclude_coins: Option<&[Coin]>,
         reuse_puzhash: Option<bool>,
+        coin_selector: Option<&(dyn CoinSelector + Send + Sync)>,
     ) -> Result<TransactionRecord, Error> {
         let non_change_amount = if let Some(primaries) = primaries {
             amount + primaries.iter().map(|a| a.amount).sum::<u64>()
@@ -299,6 +331,7 @@ pub trait Wallet<T: WalletStore + Send + Sync, C> {
                 exclude_coin_amounts,
                 exclude_coins,
                 reuse_puzhash,
+                coin_selector,
             )
             .await?;
         assert!(!transaction.is_empty());
@@ -382,6 +415,7 @@ pub trait Wallet<T: WalletStore + Send + Sync, C> {
         exclude_coin_amounts: Option<&[u64]>,
         exclude_coins: Option<&[Coin]>,
         reuse_puzhash: Option<bool>,
+        coin_selector: Option<&(dyn CoinSelector + Send + Sync)>,
     ) -> Result<Vec<CoinSpend>, Error> {
         let mut primaries_amount = 0u64;
         let total_amount: u128;
@@ -412,24 +446,52 @@ pub trait Wallet<T: WalletStore + Send + Sync, C> {
             if total_amount > total_balance {
                 return Err(Error::new(ErrorKind::InvalidInput, format!("Can't spend more than wallet balance: {total_balance} mojos, tried to spend: {total_amount} mojos")));
             }
-            coins_set = self
-                .wallet_store()
-                .lock()
-                .await
-                .select_coins(
-                    total_amount as u64,
-                    exclude_coins,
-                    min_coin_amount,
-                    max_coin_amount.unwrap_or(
-                        self.wallet_info()
-                            .constants
-                            .max_coin_amount
-                            .to_u64()
+            let max_coin_amount = max_coin_amount.unwrap_or(
+                self.wallet_info()
+                    .constants
+                    .max_coin_amount
+                    .to_u64()
+                    .unwrap_or_default(),
+            );
+            coins_set = match coin_selector {
+                Some(selector) => {
+                    let constraints = CoinSelectionConstraints {
+                        exclude_coins: exclude_coins.map(|c| c.to_vec()).unwrap_or_default(),
+                        exclude_coin_amounts: exclude_coin_amounts
+                            .map(|a| a.to_vec())
                             .unwrap_or_default(),
-                    ),
-                    exclude_coin_amounts,
-                )
-                .await?;
+                        min_coin_amount: min_coin_amount.unwrap_or_default(),
+                        max_coin_amount,
+                        ..Default::default()
+                    };
+                    let selection = self
+                        .wallet_store()
+                        .lock()
+                        .await
+                        .select_coins_with(total_amount as u64, selector, &constraints)
+                        .await?;
+                    debug!(
+                        "Selected {} coins with {}: {:?}",
+                        selection.coins.len(),
+                        selection.strategy,
+                        selection.reasons
+                    );
+                    selection.coins
+                }
+                None => {
+                    self.wallet_store()
+                        .lock()
+                        .await
+                        .select_coins(
+                            total_amount as u64,
+                            exclude_coins,
+                            min_coin_amount,
+                            max_coin_amount,
+                            exclude_coin_amounts,
+                        )
+                        .await?
+                }
+            };
         } else if exclude_coins.is_some() {
             return Err(Error::new(
                 ErrorKind::InvalidInput,
@@ -0,0 +1,584 @@
+use blst::min_pk::{AggregateSignature, PublicKey, SecretKey, Signature};
+use dg_xch_clients::api::full_node::FullnodeAPI;
+use dg_xch_core::blockchain::announcement::Announcement;
+use dg_xch_core::blockchain::coin::Coin;
+use dg_xch_core::blockchain::coin_spend::CoinSpend;
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48, Bytes96, SizedBytes};
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::blockchain::tx_status::TXStatus;
+use dg_xch_core::blockchain::unsized_bytes::UnsizedBytes;
+use dg_xch_core::blockchain::utils::pkm_pairs_for_conditions_dict;
+use dg_xch_core::clvm::bls_bindings;
+use dg_xch_core::clvm::bls_bindings::{aggregate_verify_signature, verify_signature};
+use dg_xch_core::clvm::condition_utils::conditions_dict_for_solution;
+use dg_xch_core::clvm::program::SerializedProgram;
+use dg_xch_core::consensus::constants::ConsensusConstants;
+use dg_xch_keys::{
+    master_pk_to_wallet_pk_unhardened, master_sk_to_wallet_sk, master_sk_to_wallet_sk_unhardened,
+};
+use dg_xch_puzzles::p2_delegated_puzzle_or_hidden_puzzle::{
+    calculate_synthetic_public_key, calculate_synthetic_secret_key, puzzle_for_pk,
+    puzzle_hash_for_pk, solution_for_conditions, DEFAULT_HIDDEN_PUZZLE_HASH,
+};
+use dg_xch_puzzles::utils::{
+    make_assert_coin_announcement, make_create_coin_announcement, make_create_coin_condition,
+    make_reserve_fee_condition,
+};
+use dg_xch_serialize::{hash_256, ChiaProtocolVersion, ChiaSerialize};
+use log::{info, warn};
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+pub const UNSIGNED_TRANSACTION_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningTarget {
+    pub coin_name: Bytes32,
+    pub public_key: Bytes48,
+    pub message: UnsizedBytes,
+}
+
+//Tells the signer where in the wallet tree the key for public_key lives,
+//public_key is the synthetic key that appears in the AGG_SIG condition
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivationHint {
+    pub public_key: Bytes48,
+    pub index: u32,
+    pub hardened: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub version: u32,
+    pub coin_spends: Vec<CoinSpend>,
+    pub signing_targets: Vec<SigningTarget>,
+    pub derivation_hints: Vec<DerivationHint>,
+    pub additional_data: UnsizedBytes,
+    pub max_cost: u64,
+}
+impl UnsignedTransaction {
+    pub fn new(
+        coin_spends: Vec<CoinSpend>,
+        derivation_hints: Vec<DerivationHint>,
+        constants: &ConsensusConstants,
+    ) -> Result<Self, Error> {
+        let max_cost = constants
+            .max_block_cost_clvm
+            .to_u64()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid max_block_cost_clvm"))?;
+        let signing_targets = signing_targets_for_coin_spends(
+            &coin_spends,
+            &constants.agg_sig_me_additional_data,
+            max_cost,
+        )?;
+        Ok(Self {
+            version: UNSIGNED_TRANSACTION_VERSION,
+            coin_spends,
+            signing_targets,
+            derivation_hints,
+            additional_data: UnsizedBytes::new(&constants.agg_sig_me_additional_data),
+            max_cost,
+        })
+    }
+    pub fn id(&self) -> Bytes32 {
+        let mut buf = vec![];
+        for coin_spend in &self.coin_spends {
+            buf.extend(coin_spend.to_bytes(ChiaProtocolVersion::default()));
+        }
+        Bytes32::new(&hash_256(buf))
+    }
+    pub fn fee(&self) -> Result<u64, Error> {
+        let spent = checked_sum(self.coin_spends.iter().map(|c| c.coin.amount))?;
+        let created = checked_sum(self.additions()?.iter().map(|c| c.amount))?;
+        spent.checked_sub(created).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Transaction creates more than it spends: {created} > {spent}"),
+            )
+        })
+    }
+    pub fn additions(&self) -> Result<Vec<Coin>, Error> {
+        self.coin_spends.iter().try_fold(vec![], |mut prev, cur| {
+            prev.extend(cur.additions()?);
+            Ok(prev)
+        })
+    }
+    //The signer never trusts the targets or network in the file, they are recomputed from the
+    //spends using the signer's own constants
+    pub fn validate(&self, constants: &ConsensusConstants) -> Result<(), Error> {
+        if self.version != UNSIGNED_TRANSACTION_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unsupported unsigned transaction version: {}", self.version),
+            ));
+        }
+        if self.additional_data.bytes != constants.agg_sig_me_additional_data {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Transaction was created for a different network",
+            ));
+        }
+        let max_cost = constants
+            .max_block_cost_clvm
+            .to_u64()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid max_block_cost_clvm"))?;
+        let targets = signing_targets_for_coin_spends(
+            &self.coin_spends,
+            &constants.agg_sig_me_additional_data,
+            max_cost,
+        )?;
+        if targets != self.signing_targets {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Signing targets do not match the coin spends in the transaction",
+            ));
+        }
+        Ok(())
+    }
+    pub fn load(path: &Path, constants: &ConsensusConstants) -> Result<Self, Error> {
+        let tx: Self = load_json(path)?;
+        tx.validate(constants)?;
+        Ok(tx)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        save_json(path, self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetSignature {
+    pub public_key: Bytes48,
+    pub message: UnsizedBytes,
+    pub signature: Bytes96,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureSet {
+    pub transaction_id: Bytes32,
+    pub signatures: Vec<TargetSignature>,
+}
+impl SignatureSet {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        load_json(path)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        save_json(path, self)
+    }
+}
+
+fn checked_sum<I: Iterator<Item = u64>>(mut amounts: I) -> Result<u64, Error> {
+    amounts.try_fold(0u64, |total, amount| {
+        total
+            .checked_add(amount)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Coin amounts overflow u64"))
+    })
+}
+
+fn load_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, Error> {
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse {}: {:?}", path.display(), e),
+        )
+    })
+}
+
+fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to convert value to JSON: {:?}", e),
+        )
+    })?;
+    fs::write(path, json)
+}
+
+pub fn signing_targets_for_coin_spends(
+    coin_spends: &[CoinSpend],
+    additional_data: &[u8],
+    max_cost: u64,
+) -> Result<Vec<SigningTarget>, Error> {
+    let mut targets = vec![];
+    for coin_spend in coin_spends {
+        let conditions_dict = conditions_dict_for_solution(
+            &coin_spend.puzzle_reveal,
+            &coin_spend.solution,
+            max_cost,
+        )?
+        .0;
+        for (public_key, message) in
+            pkm_pairs_for_conditions_dict(conditions_dict, coin_spend.coin.name(), additional_data)?
+        {
+            targets.push(SigningTarget {
+                coin_name: coin_spend.coin.name(),
+                public_key,
+                message: UnsizedBytes::new(&message),
+            });
+        }
+    }
+    Ok(targets)
+}
+
+fn synthetic_secret_key_at_index(
+    master_sk: &SecretKey,
+    index: u32,
+    hardened: bool,
+) -> Result<SecretKey, Error> {
+    let wallet_sk = if hardened {
+        master_sk_to_wallet_sk(master_sk, index)?
+    } else {
+        master_sk_to_wallet_sk_unhardened(master_sk, index)?
+    };
+    calculate_synthetic_secret_key(&wallet_sk, &DEFAULT_HIDDEN_PUZZLE_HASH)
+}
+
+fn find_secret_key(
+    master_sk: &SecretKey,
+    public_key: &Bytes48,
+    hints: &[DerivationHint],
+    search_limit: u32,
+) -> Result<Option<SecretKey>, Error> {
+    for hint in hints.iter().filter(|h| &h.public_key == public_key) {
+        let secret_key = synthetic_secret_key_at_index(master_sk, hint.index, hint.hardened)?;
+        if &Bytes48::from(secret_key.sk_to_pk()) == public_key {
+            return Ok(Some(secret_key));
+        }
+        warn!(
+            "Derivation hint for {public_key} (index: {}, hardened: {}) did not match, searching",
+            hint.index, hint.hardened
+        );
+    }
+    for index in 0..search_limit {
+        for hardened in [false, true] {
+            let secret_key = synthetic_secret_key_at_index(master_sk, index, hardened)?;
+            if &Bytes48::from(secret_key.sk_to_pk()) == public_key {
+                return Ok(Some(secret_key));
+            }
+        }
+    }
+    Ok(None)
+}
+
+//Signs every target this key can sign, targets owned by other keys are left for other signers
+pub fn sign_unsigned_transaction(
+    transaction: &UnsignedTransaction,
+    master_sk: &SecretKey,
+    search_limit: u32,
+    constants: &ConsensusConstants,
+) -> Result<SignatureSet, Error> {
+    transaction.validate(constants)?;
+    let mut key_cache: HashMap<Bytes48, Option<SecretKey>> = HashMap::new();
+    let mut signatures = vec![];
+    for target in &transaction.signing_targets {
+        let secret_key = match key_cache.get(&target.public_key) {
+            Some(secret_key) => secret_key.clone(),
+            None => {
+                let secret_key = find_secret_key(
+                    master_sk,
+                    &target.public_key,
+                    &transaction.derivation_hints,
+                    search_limit,
+                )?;
+                key_cache.insert(target.public_key, secret_key.clone());
+                secret_key
+            }
+        };
+        match secret_key {
+            Some(secret_key) => {
+                let signature = bls_bindings::sign(&secret_key, &target.message.bytes);
+                if !verify_signature(&secret_key.sk_to_pk(), &target.message.bytes, &signature) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Failed to verify signature for {}", target.public_key),
+                    ));
+                }
+                signatures.push(TargetSignature {
+                    public_key: target.public_key,
+                    message: target.message.clone(),
+                    signature: Bytes96::from(signature),
+                });
+            }
+            None => {
+                warn!(
+                    "No key found for {} on coin {}",
+                    target.public_key, target.coin_name
+                );
+            }
+        }
+    }
+    if signatures.is_empty() && !transaction.signing_targets.is_empty() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "Keyring does not hold any of the keys required by this transaction",
+        ));
+    }
+    Ok(SignatureSet {
+        transaction_id: transaction.id(),
+        signatures,
+    })
+}
+
+pub fn combine_signatures(
+    transaction: &UnsignedTransaction,
+    signature_sets: &[SignatureSet],
+    constants: &ConsensusConstants,
+) -> Result<SpendBundle, Error> {
+    transaction.validate(constants)?;
+    let transaction_id = transaction.id();
+    let mut available: HashMap<(Bytes48, &[u8]), Signature> = HashMap::new();
+    for set in signature_sets {
+        if set.transaction_id != transaction_id {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Signature set is for transaction {}, expected {transaction_id}",
+                    set.transaction_id
+                ),
+            ));
+        }
+        for sig in &set.signatures {
+            let public_key: PublicKey = (&sig.public_key).into();
+            let signature: Signature = (&sig.signature).try_into()?;
+            if !verify_signature(&public_key, &sig.message.bytes, &signature) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid signature from {}", sig.public_key),
+                ));
+            }
+            available.insert((sig.public_key, sig.message.bytes.as_slice()), signature);
+        }
+    }
+    let mut signatures = vec![];
+    for target in &transaction.signing_targets {
+        match available.get(&(target.public_key, target.message.bytes.as_slice())) {
+            Some(signature) => signatures.push(signature),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!(
+                        "Missing signature for {} on coin {}",
+                        target.public_key, target.coin_name
+                    ),
+                ));
+            }
+        }
+    }
+    let aggregated_signature = if signatures.is_empty() {
+        Bytes96::default()
+    } else {
+        let aggsig = AggregateSignature::aggregate(&signatures, true)
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Failed to aggregate signatures: {:?}", e),
+                )
+            })?
+            .to_signature();
+        let pk_list: Vec<&Bytes48> = transaction
+            .signing_targets
+            .iter()
+            .map(|t| &t.public_key)
+            .collect();
+        let msg_list: Vec<&[u8]> = transaction
+            .signing_targets
+            .iter()
+            .map(|t| t.message.bytes.as_slice())
+            .collect();
+        if !aggregate_verify_signature(&pk_list, &msg_list, &aggsig) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Aggregated signature failed to verify",
+            ));
+        }
+        Bytes96::from(aggsig)
+    };
+    Ok(SpendBundle {
+        coin_spends: transaction.coin_spends.clone(),
+        aggregated_signature,
+    })
+}
+
+pub async fn broadcast_transaction<T: FullnodeAPI>(
+    client: &T,
+    transaction: &UnsignedTransaction,
+    signature_sets: &[SignatureSet],
+    constants: &ConsensusConstants,
+) -> Result<(Bytes32, TXStatus), Error> {
+    let spend_bundle = combine_signatures(transaction, signature_sets, constants)?;
+    let name = spend_bundle.name();
+    info!("Submitting SpendBundle {name}");
+    Ok((name, client.push_tx(&spend_bundle).await?))
+}
+
+//Builds an unsigned standard transaction using only the master public key,
+//so the machine with network access never holds secret keys
+pub async fn create_unsigned_transaction<T: FullnodeAPI>(
+    client: &T,
+    master_pk: &PublicKey,
+    search_limit: u32,
+    puzzle_hash: &Bytes32,
+    amount: u64,
+    fee: u64,
+    constants: &ConsensusConstants,
+) -> Result<UnsignedTransaction, Error> {
+    let mut keys_for_ph: HashMap<Bytes32, (Bytes48, u32)> = HashMap::new();
+    for index in 0..search_limit {
+        let wallet_pk = Bytes48::from(master_pk_to_wallet_pk_unhardened(master_pk, index)?);
+        keys_for_ph.insert(puzzle_hash_for_pk(&wallet_pk)?, (wallet_pk, index));
+    }
+    let puzzle_hashes: Vec<Bytes32> = keys_for_ph.keys().copied().collect();
+    let mut coins: Vec<Coin> = client
+        .get_coin_records_by_puzzle_hashes(&puzzle_hashes, Some(false), None, None)
+        .await?
+        .into_iter()
+        .filter(|c| !c.spent)
+        .map(|c| c.coin)
+        .collect();
+    coins.sort_by_key(|c| Reverse(c.amount));
+    let total_amount = amount as u128 + fee as u128;
+    let mut selected = vec![];
+    let mut selected_amount = 0u128;
+    for coin in coins {
+        if selected_amount >= total_amount && !selected.is_empty() {
+            break;
+        }
+        selected_amount += coin.amount as u128;
+        selected.push(coin);
+    }
+    if selected.is_empty() || selected_amount < total_amount {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Insufficient funds, found {selected_amount} mojos but need {total_amount}"),
+        ));
+    }
+    let change = (selected_amount - total_amount) as u64;
+    let origin = &selected[0];
+    let mut primaries = vec![];
+    if amount > 0 {
+        primaries.push((*puzzle_hash, amount));
+    }
+    if change > 0 {
+        primaries.push((origin.puzzle_hash, change));
+    }
+    let mut message_list: Vec<Bytes32> = selected.iter().map(|c| c.name()).collect();
+    for (ph, amt) in &primaries {
+        message_list.push(
+            Coin {
+                parent_coin_info: origin.name(),
+                puzzle_hash: *ph,
+                amount: *amt,
+            }
+            .name(),
+        );
+    }
+    let message = hash_256(message_list.iter().fold(vec![], |mut v, e| {
+        v.extend(e.to_bytes(ChiaProtocolVersion::default()));
+        v
+    }));
+    let announcement_hash = Announcement {
+        origin_info: origin.name(),
+        message: message.clone(),
+        morph_bytes: None,
+    }
+    .name();
+    let mut coin_spends = vec![];
+    let mut derivation_hints = vec![];
+    for (i, coin) in selected.iter().enumerate() {
+        let (wallet_pk, index) = keys_for_ph.get(&coin.puzzle_hash).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("No key found for puzzle hash {}", coin.puzzle_hash),
+            )
+        })?;
+        let conditions = if i == 0 {
+            let mut conditions = vec![];
+            for (ph, amt) in &primaries {
+                conditions.push(make_create_coin_condition(*ph, *amt, &[]));
+            }
+            if fee > 0 {
+                conditions.push(make_reserve_fee_condition(fee));
+            }
+            conditions.push(make_create_coin_announcement(&message));
+            conditions
+        } else {
+            vec![make_assert_coin_announcement(&announcement_hash)]
+        };
+        let puzzle = puzzle_for_pk(wallet_pk)?;
+        let solution = solution_for_conditions(conditions)?;
+        coin_spends.push(CoinSpend {
+            coin: coin.clone(),
+            puzzle_reveal: SerializedProgram::from_bytes(&puzzle.serialized),
+            solution: SerializedProgram::from_bytes(&solution.serialized),
+        });
+        let hint = DerivationHint {
+            public_key: calculate_synthetic_public_key(wallet_pk, &DEFAULT_HIDDEN_PUZZLE_HASH)?,
+            index: *index,
+            hardened: false,
+        };
+        if !derivation_hints.contains(&hint) {
+            derivation_hints.push(hint);
+        }
+    }
+    UnsignedTransaction::new(coin_spends, derivation_hints, constants)
+}
+
+#[test]
+fn test_offline_sign_and_combine() {
+    use dg_xch_keys::key_from_mnemonic_str;
+    let constants = ConsensusConstants::default();
+    let master_sk = key_from_mnemonic_str("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+    let wallet_pk =
+        Bytes48::from(master_pk_to_wallet_pk_unhardened(&master_sk.sk_to_pk(), 3).unwrap());
+    let coin = Coin {
+        parent_coin_info: Bytes32::new(&[1u8; 32]),
+        puzzle_hash: puzzle_hash_for_pk(&wallet_pk).unwrap(),
+        amount: 1000,
+    };
+    let puzzle = puzzle_for_pk(&wallet_pk).unwrap();
+    let solution = solution_for_conditions(vec![
+        make_create_coin_condition(Bytes32::new(&[2u8; 32]), 900, &[]),
+        make_reserve_fee_condition(100),
+    ])
+    .unwrap();
+    let transaction = UnsignedTransaction::new(
+        vec![CoinSpend {
+            coin,
+            puzzle_reveal: SerializedProgram::from_bytes(&puzzle.serialized),
+            solution: SerializedProgram::from_bytes(&solution.serialized),
+        }],
+        vec![],
+        &constants,
+    )
+    .unwrap();
+    assert_eq!(transaction.signing_targets.len(), 1);
+    assert_eq!(transaction.fee().unwrap(), 100);
+    let json = serde_json::to_string(&transaction).unwrap();
+    let transaction: UnsignedTransaction = serde_json::from_str(&json).unwrap();
+    transaction.validate(&constants).unwrap();
+    let testnet = ConsensusConstants {
+        agg_sig_me_additional_data: vec![7u8; 32],
+        ..Default::default()
+    };
+    assert!(transaction.validate(&testnet).is_err());
+    assert!(sign_unsigned_transaction(&transaction, &master_sk, 10, &testnet).is_err());
+    let signatures = sign_unsigned_transaction(&transaction, &master_sk, 10, &constants).unwrap();
+    assert_eq!(signatures.signatures.len(), 1);
+    let bundle = combine_signatures(&transaction, &[signatures], &constants).unwrap();
+    assert_eq!(bundle.coin_spends, transaction.coin_spends);
+    let other_sk = key_from_mnemonic_str(
+        "legal winner thank year wave sausage worth useful legal winner thank yellow",
+    )
+    .unwrap();
+    assert!(sign_unsigned_transaction(&transaction, &other_sk, 10, &constants).is_err());
+    assert!(combine_signatures(&transaction, &[], &constants).is_err());
+    let mut overflow = transaction.clone();
+    overflow.coin_spends.push(overflow.coin_spends[0].clone());
+    overflow.coin_spends[1].coin.amount = u64::MAX;
+    assert!(overflow.fee().is_err());
+}
@@ -1,5 +1,6 @@
 use crate::wallets::common::sign_coin_spend;
 use crate::wallets::memory_wallet::{MemoryWalletConfig, MemoryWalletStore};
+use crate::wallets::transaction_manager::estimate_fee;
 use crate::wallets::{Wallet, WalletInfo};
 use async_trait::async_trait;
 use blst::min_pk::SecretKey;
@@ -162,10 +163,22 @@ impl PlotNFTWallet {
             None,
             None,
             None,
+            None,
         )
         .await
     }
 
+    pub async fn generate_estimated_fee_transaction(
+        &self,
+        spend_bundle: &SpendBundle,
+        target_time: u64,
+        coin_announcements: Option<&[Announcement]>,
+    ) -> Result<TransactionRecord, Error> {
+        let fee = estimate_fee(self.fullnode_client.as_ref(), spend_bundle, target_time).await?;
+        info!("Estimated fee of {fee} mojos to confirm within {target_time} seconds");
+        self.generate_fee_transaction(fee, coin_announcements).await
+    }
+
     pub async fn generate_travel_transaction(
         &self,
         plot_nft: &PlotNft,
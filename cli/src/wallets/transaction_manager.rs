@@ -0,0 +1,575 @@
+use dg_xch_clients::api::full_node::FullnodeAPI;
+use dg_xch_core::blockchain::coin::Coin;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::blockchain::tx_status::TXStatus;
+use log::{debug, info, warn};
+use std::cmp::max;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+//Smallest fee increase the mempool accepts for a replacement bundle
+pub const MIN_FEE_INCREASE: u64 = 10_000_000;
+
+pub fn spend_bundle_fee(spend_bundle: &SpendBundle) -> Result<u64, Error> {
+    let removed: u128 = spend_bundle
+        .removals()
+        .iter()
+        .map(|c| c.amount as u128)
+        .sum();
+    let added: u128 = spend_bundle
+        .additions()?
+        .iter()
+        .map(|c| c.amount as u128)
+        .sum();
+    removed
+        .checked_sub(added)
+        .and_then(|fee| u64::try_from(fee).ok())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "Spend bundle creates more value than it spends",
+            )
+        })
+}
+
+pub async fn estimate_fee<T: FullnodeAPI>(
+    client: &T,
+    spend_bundle: &SpendBundle,
+    target_time: u64,
+) -> Result<u64, Error> {
+    let estimate = client
+        .get_fee_estimate(None, Some(spend_bundle.clone()), None, &[target_time])
+        .await?;
+    debug!(
+        "Fee estimate for {target_time}s: {:?}, current fee rate {}",
+        estimate.estimates, estimate.current_fee_rate
+    );
+    estimate.estimates.first().copied().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("No fee estimate returned for target time {target_time}"),
+        )
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedTransaction {
+    pub id: Bytes32,
+    pub current_name: Bytes32,
+    pub spend_bundle: SpendBundle,
+    pub coins: Vec<Coin>,
+    pub fee: u64,
+    pub status: TXStatus,
+    pub submitted_at: u64,
+    pub bumps: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct PollReport {
+    pub updates: Vec<(Bytes32, TXStatus)>,
+    pub errors: Vec<(Bytes32, Error)>,
+}
+
+pub struct TransactionManagerConfig {
+    pub target_time: u64,
+    pub stuck_after: u64,
+    pub max_fee: u64,
+    pub max_bumps: u32,
+}
+impl Default for TransactionManagerConfig {
+    fn default() -> Self {
+        Self {
+            target_time: 120,
+            stuck_after: 600,
+            max_fee: 1_000_000_000,
+            max_bumps: 5,
+        }
+    }
+}
+
+pub struct TransactionManager<T: FullnodeAPI> {
+    pub client: Arc<T>,
+    pub config: TransactionManagerConfig,
+    transactions: Mutex<HashMap<Bytes32, TrackedTransaction>>,
+}
+impl<T: FullnodeAPI> TransactionManager<T> {
+    pub fn new(client: Arc<T>, config: TransactionManagerConfig) -> Self {
+        Self {
+            client,
+            config,
+            transactions: Default::default(),
+        }
+    }
+
+    pub async fn estimate_fee(&self, spend_bundle: &SpendBundle) -> Result<u64, Error> {
+        estimate_fee(self.client.as_ref(), spend_bundle, self.config.target_time).await
+    }
+
+    pub async fn submit(&self, spend_bundle: SpendBundle) -> Result<Bytes32, Error> {
+        let id = spend_bundle.name();
+        let fee = spend_bundle_fee(&spend_bundle)?;
+        let status = self.client.push_tx(&spend_bundle).await?;
+        if status == TXStatus::FAILED {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Transaction {id} was rejected by the node"),
+            ));
+        }
+        info!("Submitted transaction {id} with fee {fee}, status: {status:?}");
+        self.transactions.lock().await.insert(
+            id,
+            TrackedTransaction {
+                id,
+                current_name: id,
+                coins: spend_bundle.removals(),
+                spend_bundle,
+                fee,
+                status,
+                submitted_at: now(),
+                bumps: 0,
+            },
+        );
+        Ok(id)
+    }
+
+    pub async fn status(&self, id: &Bytes32) -> Option<TXStatus> {
+        self.transactions.lock().await.get(id).map(|t| t.status)
+    }
+
+    pub async fn tracked(&self) -> Vec<TrackedTransaction> {
+        self.transactions.lock().await.values().cloned().collect()
+    }
+
+    pub async fn forget(&self, id: &Bytes32) -> Option<TrackedTransaction> {
+        self.transactions.lock().await.remove(id)
+    }
+
+    //Checks every pending transaction, bumping any that have been stuck past the deadline.
+    //fee_fn must return a signed bundle that adds the requested fee without touching other coins.
+    //Lookup failures leave the transaction pending and are reported next to the status updates
+    pub async fn poll<F, Fut>(&self, fee_fn: F) -> PollReport
+    where
+        F: Fn(u64) -> Fut,
+        Fut: Future<Output = Result<SpendBundle, Error>>,
+    {
+        let pending: Vec<TrackedTransaction> = self
+            .transactions
+            .lock()
+            .await
+            .values()
+            .filter(|t| t.status == TXStatus::PENDING)
+            .cloned()
+            .collect();
+        let mut report = PollReport::default();
+        for mut tx in pending {
+            match self
+                .client
+                .get_mempool_item_by_tx_id(&tx.current_name.to_string())
+                .await
+            {
+                Ok(_) => {
+                    if now().saturating_sub(tx.submitted_at) > self.config.stuck_after {
+                        if tx.bumps < self.config.max_bumps {
+                            if let Err(e) = self.bump(&mut tx, &fee_fn).await {
+                                warn!("Failed to bump fee for {}: {:?}", tx.id, e);
+                                report.errors.push((tx.id, e));
+                            }
+                        } else {
+                            debug!("Transaction {} reached the bump limit", tx.id);
+                        }
+                    }
+                }
+                Err(e) if is_not_in_mempool(&e) => match self.is_confirmed(&tx).await {
+                    Ok(true) => {
+                        info!("Transaction {} confirmed", tx.id);
+                        tx.status = TXStatus::SUCCESS;
+                    }
+                    Ok(false) => {
+                        warn!("Transaction {} dropped from the mempool", tx.id);
+                        tx.status = TXStatus::FAILED;
+                    }
+                    Err(e) => {
+                        warn!("Failed to check coins for {}: {:?}", tx.id, e);
+                        report.errors.push((tx.id, e));
+                    }
+                },
+                Err(e) => {
+                    warn!("Failed to look up {} in the mempool: {:?}", tx.id, e);
+                    report.errors.push((tx.id, e));
+                }
+            }
+            report.updates.push((tx.id, tx.status));
+            self.transactions.lock().await.insert(tx.id, tx);
+        }
+        report
+    }
+
+    async fn is_confirmed(&self, tx: &TrackedTransaction) -> Result<bool, Error> {
+        for coin in &tx.coins {
+            match self.client.get_coin_record_by_name(&coin.name()).await? {
+                Some(record) if record.spent => {}
+                _ => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    async fn bump<F, Fut>(&self, tx: &mut TrackedTransaction, fee_fn: &F) -> Result<(), Error>
+    where
+        F: Fn(u64) -> Fut,
+        Fut: Future<Output = Result<SpendBundle, Error>>,
+    {
+        let estimate = self.estimate_fee(&tx.spend_bundle).await?;
+        let min_fee = tx.fee.checked_add(MIN_FEE_INCREASE).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Fee {} can not be raised any further", tx.fee),
+            )
+        })?;
+        let new_fee = max(estimate, min_fee);
+        if new_fee > self.config.max_fee {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Replacement fee {new_fee} is above the limit of {}",
+                    self.config.max_fee
+                ),
+            ));
+        }
+        let fee_bundle = fee_fn(new_fee - tx.fee).await?;
+        //Keep every spend of the stuck bundle so the replacement is a superset of it
+        let replacement = SpendBundle::aggregate(vec![tx.spend_bundle.clone(), fee_bundle])?;
+        let status = self.client.push_tx(&replacement).await?;
+        if status == TXStatus::FAILED {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Replacement for {} was rejected by the node", tx.id),
+            ));
+        }
+        info!(
+            "Replaced {} with {} raising the fee from {} to {new_fee}",
+            tx.current_name,
+            replacement.name(),
+            tx.fee
+        );
+        tx.current_name = replacement.name();
+        tx.fee = spend_bundle_fee(&replacement)?;
+        tx.spend_bundle = replacement;
+        tx.submitted_at = now();
+        tx.bumps += 1;
+        Ok(())
+    }
+}
+
+//The node answers an unknown tx id with {success: false, error: "Tx id .. not in the mempool"},
+//anything else (timeouts, a restarting node) says nothing about whether the transaction is gone
+fn is_not_in_mempool(e: &Error) -> bool {
+    e.kind() == ErrorKind::NotFound || e.to_string().contains("not in the mempool")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_transaction_manager_poll() {
+    use async_trait::async_trait;
+    use dg_xch_core::blockchain::block_record::BlockRecord;
+    use dg_xch_core::blockchain::blockchain_state::BlockchainState;
+    use dg_xch_core::blockchain::coin_record::CoinRecord;
+    use dg_xch_core::blockchain::coin_spend::CoinSpend;
+    use dg_xch_core::blockchain::full_block::FullBlock;
+    use dg_xch_core::blockchain::mempool_item::MempoolItem;
+    use dg_xch_core::blockchain::network_info::NetworkInfo;
+    use dg_xch_core::blockchain::npc_result::NPCResult;
+    use dg_xch_core::blockchain::signage_point_or_eos::SignagePointOrEOS;
+    use dg_xch_core::blockchain::sized_bytes::{Bytes96, SizedBytes};
+    use dg_xch_core::blockchain::unfinished_header_block::UnfinishedHeaderBlock;
+    use dg_xch_core::clvm::program::SerializedProgram;
+    use dg_xch_core::protocols::full_node::{BlockCountMetrics, FeeEstimate};
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct MockNode {
+        mempool: std::sync::Mutex<HashMap<Bytes32, SpendBundle>>,
+        spent: std::sync::Mutex<HashSet<Bytes32>>,
+        offline: AtomicBool,
+    }
+    fn unsupported<T>() -> Result<T, Error> {
+        Err(Error::new(ErrorKind::Unsupported, "Not used by the mock"))
+    }
+    #[async_trait]
+    impl FullnodeAPI for MockNode {
+        async fn get_blockchain_state(&self) -> Result<BlockchainState, Error> {
+            unsupported()
+        }
+        async fn get_block(&self, _: &Bytes32) -> Result<FullBlock, Error> {
+            unsupported()
+        }
+        async fn get_blocks(
+            &self,
+            _: u32,
+            _: u32,
+            _: bool,
+            _: bool,
+        ) -> Result<Vec<FullBlock>, Error> {
+            unsupported()
+        }
+        async fn get_all_blocks(&self, _: u32, _: u32) -> Result<Vec<FullBlock>, Error> {
+            unsupported()
+        }
+        async fn get_block_count_metrics(&self) -> Result<BlockCountMetrics, Error> {
+            unsupported()
+        }
+        async fn get_block_record_by_height(&self, _: u32) -> Result<BlockRecord, Error> {
+            unsupported()
+        }
+        async fn get_block_record(&self, _: &Bytes32) -> Result<BlockRecord, Error> {
+            unsupported()
+        }
+        async fn get_block_records(&self, _: u32, _: u32) -> Result<Vec<BlockRecord>, Error> {
+            unsupported()
+        }
+        async fn get_unfinished_block_headers(&self) -> Result<Vec<UnfinishedHeaderBlock>, Error> {
+            unsupported()
+        }
+        async fn get_network_space(&self, _: &Bytes32, _: &Bytes32) -> Result<u64, Error> {
+            unsupported()
+        }
+        async fn get_network_space_by_height(&self, _: u32, _: u32) -> Result<u64, Error> {
+            unsupported()
+        }
+        async fn get_additions_and_removals(
+            &self,
+            _: &Bytes32,
+        ) -> Result<(Vec<CoinRecord>, Vec<CoinRecord>), Error> {
+            unsupported()
+        }
+        async fn get_initial_freeze_period(&self) -> Result<u64, Error> {
+            unsupported()
+        }
+        async fn get_network_info(&self) -> Result<NetworkInfo, Error> {
+            unsupported()
+        }
+        async fn get_recent_signage_point_or_eos(
+            &self,
+            _: Option<&Bytes32>,
+            _: Option<&Bytes32>,
+        ) -> Result<SignagePointOrEOS, Error> {
+            unsupported()
+        }
+        async fn get_coin_records_by_puzzle_hash(
+            &self,
+            _: &Bytes32,
+            _: Option<bool>,
+            _: Option<u32>,
+            _: Option<u32>,
+        ) -> Result<Vec<CoinRecord>, Error> {
+            unsupported()
+        }
+        async fn get_coin_records_by_puzzle_hashes(
+            &self,
+            _: &[Bytes32],
+            _: Option<bool>,
+            _: Option<u32>,
+            _: Option<u32>,
+        ) -> Result<Vec<CoinRecord>, Error> {
+            unsupported()
+        }
+        async fn get_coin_record_by_name(
+            &self,
+            name: &Bytes32,
+        ) -> Result<Option<CoinRecord>, Error> {
+            let spent = self.spent.lock().unwrap().contains(name);
+            Ok(Some(CoinRecord {
+                coin: Coin {
+                    parent_coin_info: *name,
+                    puzzle_hash: Default::default(),
+                    amount: 0,
+                },
+                confirmed_block_index: 1,
+                spent_block_index: if spent { 2 } else { 0 },
+                coinbase: false,
+                timestamp: 0,
+                spent,
+            }))
+        }
+        async fn get_coin_records_by_names(
+            &self,
+            _: &[Bytes32],
+            _: bool,
+            _: u32,
+            _: u32,
+        ) -> Result<Vec<CoinRecord>, Error> {
+            unsupported()
+        }
+        async fn get_coin_records_by_parent_ids(
+            &self,
+            _: &[Bytes32],
+            _: bool,
+            _: u32,
+            _: u32,
+        ) -> Result<Vec<CoinRecord>, Error> {
+            unsupported()
+        }
+        async fn get_coin_records_by_hint(
+            &self,
+            _: &Bytes32,
+            _: bool,
+            _: u32,
+            _: u32,
+        ) -> Result<Vec<CoinRecord>, Error> {
+            unsupported()
+        }
+        async fn push_tx(&self, spend_bundle: &SpendBundle) -> Result<TXStatus, Error> {
+            self.mempool
+                .lock()
+                .unwrap()
+                .insert(spend_bundle.name(), spend_bundle.clone());
+            Ok(TXStatus::PENDING)
+        }
+        async fn get_puzzle_and_solution(&self, _: &Bytes32, _: u32) -> Result<CoinSpend, Error> {
+            unsupported()
+        }
+        async fn get_coin_spend(&self, _: &CoinRecord) -> Result<CoinSpend, Error> {
+            unsupported()
+        }
+        async fn get_all_mempool_tx_ids(&self) -> Result<Vec<Bytes32>, Error> {
+            unsupported()
+        }
+        async fn get_all_mempool_items(&self) -> Result<HashMap<Bytes32, MempoolItem>, Error> {
+            unsupported()
+        }
+        async fn get_mempool_item_by_tx_id(&self, tx_id: &str) -> Result<MempoolItem, Error> {
+            if self.offline.load(Ordering::Relaxed) {
+                return Err(Error::new(ErrorKind::ConnectionRefused, "Node is offline"));
+            }
+            let name = Bytes32::from(tx_id);
+            match self.mempool.lock().unwrap().get(&name) {
+                Some(spend_bundle) => Ok(MempoolItem {
+                    spend_bundle: spend_bundle.clone(),
+                    fee: 0,
+                    cost: 0,
+                    npc_result: NPCResult {
+                        error: None,
+                        conds: None,
+                    },
+                    spend_bundle_name: name,
+                    height_added_to_mempool: 0,
+                    assert_height: None,
+                    assert_before_height: None,
+                    assert_before_seconds: None,
+                    bundle_coin_spends: Default::default(),
+                    additions: vec![],
+                    removals: vec![],
+                }),
+                None => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Request failed: Tx id {tx_id} not in the mempool"),
+                )),
+            }
+        }
+        async fn get_mempool_items_by_coin_name(
+            &self,
+            _: &Bytes32,
+        ) -> Result<Vec<MempoolItem>, Error> {
+            unsupported()
+        }
+        async fn get_fee_estimate(
+            &self,
+            _: Option<u64>,
+            _: Option<SpendBundle>,
+            _: Option<String>,
+            target_times: &[u64],
+        ) -> Result<FeeEstimate, Error> {
+            Ok(FeeEstimate {
+                estimates: vec![0],
+                target_times: target_times.to_vec(),
+                current_fee_rate: 0.0,
+                mempool_size: 0,
+                mempool_fees: 0,
+                num_spends: 0,
+                mempool_max_size: 0,
+                full_node_synced: true,
+                peak_height: 0,
+                last_peak_timestamp: 0,
+                node_time_utc: 0,
+                last_block_cost: 0,
+                fees_last_block: None,
+                fee_rate_last_block: 0.0,
+                last_tx_block_height: 0,
+            })
+        }
+    }
+    //Puzzle 1 returns its empty solution, so the whole coin amount goes to fees
+    fn bundle(seed: u8, amount: u64) -> SpendBundle {
+        let mut signature = [0u8; 96];
+        signature[0] = 0xc0;
+        SpendBundle {
+            coin_spends: vec![CoinSpend {
+                coin: Coin {
+                    parent_coin_info: Bytes32::new(&[seed; 32]),
+                    puzzle_hash: Default::default(),
+                    amount,
+                },
+                puzzle_reveal: SerializedProgram::from_bytes(&[0x01]),
+                solution: SerializedProgram::from_bytes(&[0x80]),
+            }],
+            aggregated_signature: Bytes96::new(&signature),
+        }
+    }
+    let node = Arc::new(MockNode::default());
+    let manager = TransactionManager::new(node.clone(), TransactionManagerConfig::default());
+    let fee_fn = |fee: u64| async move { Ok(bundle(9, fee)) };
+    let stuck = manager.submit(bundle(1, 100)).await.unwrap();
+    //Lookup errors other than not found keep the transaction pending
+    node.offline.store(true, Ordering::Relaxed);
+    let report = manager.poll(fee_fn).await;
+    assert_eq!(report.updates, vec![(stuck, TXStatus::PENDING)]);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].1.kind(), ErrorKind::ConnectionRefused);
+    node.offline.store(false, Ordering::Relaxed);
+    //Stuck transactions are replaced with a bundle paying at least MIN_FEE_INCREASE more
+    manager
+        .transactions
+        .lock()
+        .await
+        .get_mut(&stuck)
+        .unwrap()
+        .submitted_at = 0;
+    let report = manager.poll(fee_fn).await;
+    assert!(report.errors.is_empty());
+    let tracked = manager.tracked().await.pop().unwrap();
+    assert_eq!(tracked.status, TXStatus::PENDING);
+    assert_eq!(tracked.bumps, 1);
+    assert_eq!(tracked.fee, 100 + MIN_FEE_INCREASE);
+    assert_ne!(tracked.current_name, stuck);
+    assert_eq!(tracked.spend_bundle.coin_spends.len(), 2);
+    //Leaving the mempool with every coin spent confirms it
+    node.mempool.lock().unwrap().clear();
+    for coin in &tracked.coins {
+        node.spent.lock().unwrap().insert(coin.name());
+    }
+    let report = manager.poll(fee_fn).await;
+    assert_eq!(report.updates, vec![(stuck, TXStatus::SUCCESS)]);
+    //Leaving the mempool with unspent coins means it was dropped
+    let dropped = manager.submit(bundle(2, 50)).await.unwrap();
+    node.mempool.lock().unwrap().clear();
+    let report = manager.poll(fee_fn).await;
+    assert_eq!(report.updates, vec![(dropped, TXStatus::FAILED)]);
+    assert!(report.errors.is_empty());
+    //Fees that can not be raised are reported instead of overflowing
+    let mut tx = manager.forget(&dropped).await.unwrap();
+    tx.fee = u64::MAX;
+    let e = manager.bump(&mut tx, &fee_fn).await.unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+}
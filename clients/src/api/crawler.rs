@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use dg_xch_core::blockchain::peer_counts::PeerCounts;
+use std::io::Error;
+
+#[async_trait]
+pub trait CrawlerAPI {
+    async fn get_peer_counts(&self) -> Result<PeerCounts, Error>;
+    //Returns one page of the ips seen after the unix timestamp and the total number of such ips
+    async fn get_ips_after_timestamp(
+        &self,
+        after: u64,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<(Vec<String>, u64), Error>;
+}
@@ -0,0 +1,53 @@
+use crate::api::responses::{
+    DataLayerChange, DataLayerDiff, DataLayerKeyValue, DataLayerMirror, DataLayerRoot,
+};
+use async_trait::async_trait;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use std::io::Error;
+
+#[async_trait]
+pub trait DataLayerAPI {
+    async fn create_data_store(&self, fee: Option<u64>) -> Result<Bytes32, Error>;
+    async fn batch_update(
+        &self,
+        id: &Bytes32,
+        changelist: Vec<DataLayerChange>,
+        fee: Option<u64>,
+        submit_on_chain: Option<bool>,
+    ) -> Result<Option<Bytes32>, Error>;
+    async fn get_value(
+        &self,
+        id: &Bytes32,
+        key: &[u8],
+        root_hash: Option<Bytes32>,
+    ) -> Result<Option<Vec<u8>>, Error>;
+    async fn get_keys_values(
+        &self,
+        id: &Bytes32,
+        root_hash: Option<Bytes32>,
+        page: Option<u32>,
+        max_page_size: Option<u32>,
+    ) -> Result<Vec<DataLayerKeyValue>, Error>;
+    async fn get_root(&self, id: &Bytes32) -> Result<DataLayerRoot, Error>;
+    async fn get_root_history(&self, id: &Bytes32) -> Result<Vec<DataLayerRoot>, Error>;
+    async fn get_kv_diff(
+        &self,
+        id: &Bytes32,
+        hash_1: &Bytes32,
+        hash_2: &Bytes32,
+        page: Option<u32>,
+        max_page_size: Option<u32>,
+    ) -> Result<Vec<DataLayerDiff>, Error>;
+    async fn subscribe(&self, id: &Bytes32, urls: Vec<String>) -> Result<(), Error>;
+    async fn unsubscribe(&self, id: &Bytes32, retain: bool) -> Result<(), Error>;
+    async fn subscriptions(&self) -> Result<Vec<Bytes32>, Error>;
+    async fn add_mirror(
+        &self,
+        id: &Bytes32,
+        urls: Vec<String>,
+        amount: u64,
+        fee: Option<u64>,
+    ) -> Result<(), Error>;
+    async fn delete_mirror(&self, coin_id: &Bytes32, fee: Option<u64>) -> Result<(), Error>;
+    async fn get_mirrors(&self, id: &Bytes32) -> Result<Vec<DataLayerMirror>, Error>;
+}
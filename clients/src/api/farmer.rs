@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use dg_xch_core::blockchain::harvester_info::{HarvesterInfo, HarvesterSummary};
+use dg_xch_core::blockchain::paginated_plots::{PaginatedPlots, PlotFilter};
+use dg_xch_core::blockchain::pool_state_info::PoolStateInfo;
+use dg_xch_core::blockchain::reward_targets::RewardTargets;
+use dg_xch_core::blockchain::signage_point_proofs::SignagePointProofs;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::protocols::harvester::Plot;
+use serde::{Deserialize, Serialize};
+use std::io::Error;
+
+//Selects one page of the plots a farmer knows for the harvester with node_id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlotPageRequest {
+    pub node_id: Bytes32,
+    pub page: u32,
+    pub page_size: u32,
+    pub reverse: bool,
+}
+
+#[async_trait]
+pub trait FarmerAPI {
+    async fn get_signage_point(&self, sp_hash: &Bytes32) -> Result<SignagePointProofs, Error>;
+    async fn get_signage_points(&self) -> Result<Vec<SignagePointProofs>, Error>;
+    async fn get_reward_targets(
+        &self,
+        search_for_private_key: bool,
+        max_ph_to_search: Option<u32>,
+    ) -> Result<RewardTargets, Error>;
+    async fn set_reward_targets(
+        &self,
+        farmer_target: Option<String>,
+        pool_target: Option<String>,
+    ) -> Result<(), Error>;
+    async fn get_pool_state(&self) -> Result<Vec<PoolStateInfo>, Error>;
+    async fn set_payout_instructions(
+        &self,
+        launcher_id: &Bytes32,
+        payout_instructions: String,
+    ) -> Result<(), Error>;
+    async fn get_harvesters(&self) -> Result<Vec<HarvesterInfo>, Error>;
+    async fn get_harvesters_summary(&self) -> Result<Vec<HarvesterSummary>, Error>;
+    async fn get_harvester_plots_valid(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[PlotFilter],
+        sort_key: Option<String>,
+    ) -> Result<PaginatedPlots<Plot>, Error>;
+    async fn get_harvester_plots_invalid(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[String],
+    ) -> Result<PaginatedPlots<String>, Error>;
+    async fn get_harvester_plots_keys_missing(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[String],
+    ) -> Result<PaginatedPlots<String>, Error>;
+    async fn get_harvester_plots_duplicates(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[String],
+    ) -> Result<PaginatedPlots<String>, Error>;
+}
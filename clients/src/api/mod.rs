@@ -1,6 +1,11 @@
+pub mod crawler;
+pub mod data_layer;
+pub mod farmer;
 pub mod full_node;
+pub mod harvester;
 pub mod pool;
 pub mod responses;
+pub mod timelord;
 pub mod wallet;
 
 use serde::Serialize;
@@ -1,19 +1,34 @@
 use dg_xch_core::blockchain::block_record::BlockRecord;
 use dg_xch_core::blockchain::blockchain_state::BlockchainState;
+use dg_xch_core::blockchain::coin::Coin;
 use dg_xch_core::blockchain::coin_record::{CoinRecord, HintedCoinRecord, PaginatedCoinRecord};
 use dg_xch_core::blockchain::coin_spend::CoinSpend;
+use dg_xch_core::blockchain::connection_info::ConnectionInfo;
 use dg_xch_core::blockchain::full_block::FullBlock;
+use dg_xch_core::blockchain::harvester_info::{HarvesterInfo, HarvesterSummary};
 use dg_xch_core::blockchain::mempool_item::MempoolItem;
+use dg_xch_core::blockchain::nft_info::NftInfo;
+use dg_xch_core::blockchain::notification::Notification;
+use dg_xch_core::blockchain::peer_counts::PeerCounts;
+use dg_xch_core::blockchain::pool_state_info::PoolStateInfo;
+use dg_xch_core::blockchain::pool_wallet_info::{PoolWalletInfo, PoolWalletTransaction};
+use dg_xch_core::blockchain::proof_of_space::ProofOfSpace;
 use dg_xch_core::blockchain::signage_point::SignagePoint;
+use dg_xch_core::blockchain::signage_point_proofs::SignagePointProofs;
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
 use dg_xch_core::blockchain::subslot_bundle::SubSlotBundle;
+use dg_xch_core::blockchain::trade_record::{OfferSummary, TradeRecord};
 use dg_xch_core::blockchain::transaction_record::TransactionRecord;
 use dg_xch_core::blockchain::tx_status::TXStatus;
 use dg_xch_core::blockchain::unfinished_header_block::UnfinishedHeaderBlock;
 use dg_xch_core::blockchain::wallet_balance::WalletBalance;
 use dg_xch_core::blockchain::wallet_info::WalletInfo;
+use dg_xch_core::protocols::farmer::NewSignagePoint;
 use dg_xch_core::protocols::full_node::BlockCountMetrics;
+use dg_xch_core::protocols::harvester::Plot;
 
-use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48, Bytes96};
+use dg_xch_core::blockchain::unsized_bytes::UnsizedBytes;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -97,6 +112,12 @@ pub struct CoinSpendMapResp {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConnectionsResp {
+    pub connections: Vec<ConnectionInfo>,
+    pub success: bool,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FullBlockResp {
     pub block: FullBlock,
@@ -114,6 +135,25 @@ pub struct FullBlockAryResp {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HarvestersResp {
+    pub harvesters: Vec<HarvesterInfo>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HarvestersSummaryResp {
+    pub harvesters: Vec<HarvesterSummary>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IpsAfterTimestampResp {
+    pub ips: Vec<String>,
+    pub total: u64,
+    pub success: bool,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InitialFreezePeriodResp {
     pub initial_freeze_end_timestamp: u64,
@@ -163,6 +203,57 @@ pub struct NetSpaceResp {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PaginatedPlotsResp<T> {
+    pub node_id: Bytes32,
+    pub page: u32,
+    pub page_count: u32,
+    pub total_count: u32,
+    pub plots: Vec<T>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PeerCountsResp {
+    pub peer_counts: PeerCounts,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlotDirectoriesResp {
+    pub directories: Vec<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlotsResp {
+    pub plots: Vec<Plot>,
+    pub failed_to_open_filenames: Vec<String>,
+    pub not_found_filenames: Vec<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PoolStateResp {
+    pub pool_state: Vec<PoolStateInfo>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RewardTargetsResp {
+    pub farmer_target: String,
+    pub pool_target: String,
+    pub have_farmer_sk: Option<bool>,
+    pub have_pool_sk: Option<bool>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RoutesResp {
+    pub routes: Vec<String>,
+    pub success: bool,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SignagePointOrEOSResp {
     pub signage_point: Option<SignagePoint>,
@@ -172,12 +263,31 @@ pub struct SignagePointOrEOSResp {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignagePointProofsResp {
+    pub signage_point: NewSignagePoint,
+    pub proofs: Vec<(String, ProofOfSpace)>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignagePointProofsAryResp {
+    pub signage_points: Vec<SignagePointProofs>,
+    pub success: bool,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SignedTransactionRecordResp {
     pub signed_tx: TransactionRecord,
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SuccessResp {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TXResp {
     pub status: TXStatus,
@@ -196,6 +306,12 @@ pub struct UnfinishedBlockAryResp {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VersionResp {
+    pub version: String,
+    pub success: bool,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct WalletBalanceResp {
     pub wallets: Vec<WalletBalance>,
@@ -215,3 +331,402 @@ pub struct WalletSyncResp {
     pub syncing: bool,
     pub success: bool,
 }
+
+//DataLayer keys and values are arbitrary bytes sent as hex
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataLayerAction {
+    Insert,
+    Delete,
+    Upsert,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DataLayerChange {
+    pub action: DataLayerAction,
+    pub key: UnsizedBytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<UnsizedBytes>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DataLayerKeyValue {
+    pub hash: Bytes32,
+    pub key: UnsizedBytes,
+    pub value: UnsizedBytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DataLayerRoot {
+    #[serde(alias = "root_hash")]
+    pub hash: Bytes32,
+    pub confirmed: bool,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DataLayerDiffType {
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DataLayerDiff {
+    #[serde(rename = "type")]
+    pub diff_type: DataLayerDiffType,
+    pub key: UnsizedBytes,
+    pub value: UnsizedBytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DataLayerMirror {
+    pub coin_id: Bytes32,
+    pub launcher_id: Bytes32,
+    pub amount: u64,
+    pub urls: Vec<String>,
+    pub ours: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateDataStoreReq {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+    pub verbose: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BatchUpdateReq {
+    pub id: Bytes32,
+    pub changelist: Vec<DataLayerChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_on_chain: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetValueReq {
+    pub id: Bytes32,
+    pub key: UnsizedBytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_hash: Option<Bytes32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetKeysValuesReq {
+    pub id: Bytes32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_hash: Option<Bytes32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_page_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StoreIdReq {
+    pub id: Bytes32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetKvDiffReq {
+    pub id: Bytes32,
+    pub hash_1: Bytes32,
+    pub hash_2: Bytes32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_page_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SubscribeReq {
+    pub id: Bytes32,
+    pub urls: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UnsubscribeReq {
+    pub id: Bytes32,
+    pub retain: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AddMirrorReq {
+    pub id: Bytes32,
+    pub urls: Vec<String>,
+    pub amount: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DeleteMirrorReq {
+    pub coin_id: Bytes32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateDataStoreResp {
+    pub id: Bytes32,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BatchUpdateResp {
+    pub tx_id: Option<Bytes32>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetValueResp {
+    pub value: Option<UnsizedBytes>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetKeysValuesResp {
+    pub keys_values: Vec<DataLayerKeyValue>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetRootResp {
+    pub hash: Bytes32,
+    pub confirmed: bool,
+    pub timestamp: u64,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetRootHistoryResp {
+    pub root_history: Vec<DataLayerRoot>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetKvDiffResp {
+    pub diff: Vec<DataLayerDiff>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SubscriptionsResp {
+    pub store_ids: Vec<Bytes32>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetMirrorsResp {
+    pub mirrors: Vec<DataLayerMirror>,
+    pub success: bool,
+}
+
+//Wallet responses beyond the basic send and balance calls
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TransactionRecordAryResp {
+    pub transactions: Vec<TransactionRecord>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CountResp {
+    pub count: u64,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NextAddressResp {
+    pub address: String,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CoinAryResp {
+    pub coins: Vec<Coin>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SpendableCoins {
+    pub confirmed_records: Vec<CoinRecord>,
+    pub unconfirmed_removals: Vec<CoinRecord>,
+    pub unconfirmed_additions: Vec<Coin>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpendableCoinsResp {
+    #[serde(flatten)]
+    pub coins: SpendableCoins,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OfferResp {
+    pub offer: String,
+    pub trade_record: TradeRecord,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TradeRecordResp {
+    pub trade_record: TradeRecord,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TradeRecordAryResp {
+    pub trade_records: Vec<TradeRecord>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OfferSummaryResp {
+    pub summary: OfferSummary,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OfferValidityResp {
+    pub valid: bool,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WalletNameResp {
+    pub wallet_id: Option<u32>,
+    pub name: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CatAssetIdResp {
+    pub asset_id: Bytes32,
+    pub wallet_id: u32,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpendBundleResp {
+    pub spend_bundle: SpendBundle,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NftMintReq {
+    pub wallet_id: u32,
+    pub uris: Vec<String>,
+    pub hash: Bytes32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub royalty_address: Option<String>,
+    pub royalty_percentage: u16,
+    pub meta_uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta_hash: Option<Bytes32>,
+    pub license_uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_hash: Option<Bytes32>,
+    pub edition_number: u64,
+    pub edition_total: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_id: Option<String>,
+    pub fee: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NftMintResp {
+    pub wallet_id: u32,
+    pub spend_bundle: SpendBundle,
+    pub nft_id: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NftListResp {
+    pub wallet_id: u32,
+    pub nft_list: Vec<NftInfo>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NftInfoResp {
+    pub nft_info: NftInfo,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateWalletResp {
+    pub wallet_id: u32,
+    pub asset_id: Option<Bytes32>,
+    pub my_did: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DidResp {
+    pub wallet_id: u32,
+    pub my_did: String,
+    pub coin_id: Option<Bytes32>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DidMetadataResp {
+    pub metadata: HashMap<String, String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PoolWalletTransactionResp {
+    #[serde(flatten)]
+    pub transaction: PoolWalletTransaction,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PoolWalletStatusResp {
+    pub state: PoolWalletInfo,
+    pub unconfirmed_transactions: Vec<TransactionRecord>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SignedMessage {
+    pub pubkey: Bytes48,
+    pub signature: Bytes96,
+    pub signing_mode: String,
+    pub latest_coin_id: Option<Bytes32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignMessageResp {
+    #[serde(flatten)]
+    pub message: SignedMessage,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VerifySignatureResp {
+    #[serde(rename = "isValid")]
+    pub is_valid: bool,
+    pub error: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationsResp {
+    pub notifications: Vec<Notification>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationTXResp {
+    pub tx: TransactionRecord,
+    pub success: bool,
+}
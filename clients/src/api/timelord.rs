@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+use dg_xch_core::blockchain::connection_info::ConnectionInfo;
+use std::io::Error;
+
+//The timelord only serves the endpoints shared by every chia service
+#[async_trait]
+pub trait TimelordAPI {
+    async fn healthz(&self) -> Result<bool, Error>;
+    async fn get_connections(&self) -> Result<Vec<ConnectionInfo>, Error>;
+    async fn get_routes(&self) -> Result<Vec<String>, Error>;
+    async fn get_version(&self) -> Result<String, Error>;
+}
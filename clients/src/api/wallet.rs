@@ -1,11 +1,19 @@
+use crate::api::responses::{NftMintReq, SignedMessage, SpendableCoins};
 use async_trait::async_trait;
 use dg_xch_core::blockchain::announcement::Announcement;
 use dg_xch_core::blockchain::coin::Coin;
+use dg_xch_core::blockchain::nft_info::NftInfo;
+use dg_xch_core::blockchain::notification::Notification;
 use dg_xch_core::blockchain::pending_payment::PendingPayment;
+use dg_xch_core::blockchain::pool_wallet_info::{PoolWalletInfo, PoolWalletTransaction};
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48, Bytes96};
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::blockchain::trade_record::{OfferSummary, TradeRecord};
 use dg_xch_core::blockchain::transaction_record::TransactionRecord;
 use dg_xch_core::blockchain::wallet_balance::WalletBalance;
 use dg_xch_core::blockchain::wallet_info::WalletInfo;
 use dg_xch_core::blockchain::wallet_sync::WalletSync;
+use std::collections::HashMap;
 use std::io::Error;
 
 #[async_trait]
@@ -42,4 +50,181 @@ pub trait WalletAPI {
         puzzle_announcements: Vec<Announcement>,
         fee: u64,
     ) -> Result<TransactionRecord, Error>;
+    async fn get_transactions(
+        &self,
+        wallet_id: u32,
+        start: Option<u32>,
+        end: Option<u32>,
+        reverse: Option<bool>,
+    ) -> Result<Vec<TransactionRecord>, Error>;
+    async fn get_transaction_count(&self, wallet_id: u32) -> Result<u64, Error>;
+    async fn get_next_address(&self, wallet_id: u32, new_address: bool) -> Result<String, Error>;
+    async fn select_coins(
+        &self,
+        wallet_id: u32,
+        amount: u64,
+        excluded_coin_ids: Vec<Bytes32>,
+    ) -> Result<Vec<Coin>, Error>;
+    async fn get_spendable_coins(
+        &self,
+        wallet_id: u32,
+        min_coin_amount: Option<u64>,
+        max_coin_amount: Option<u64>,
+    ) -> Result<SpendableCoins, Error>;
+    async fn combine_coins(
+        &self,
+        wallet_id: u32,
+        number_of_coins: u32,
+        target_coin_ids: Vec<Bytes32>,
+        fee: u64,
+    ) -> Result<Vec<TransactionRecord>, Error>;
+    async fn split_coins(
+        &self,
+        wallet_id: u32,
+        number_of_coins: u32,
+        amount_per_coin: u64,
+        target_coin_id: &Bytes32,
+        fee: u64,
+    ) -> Result<Vec<TransactionRecord>, Error>;
+    async fn create_offer_for_ids(
+        &self,
+        offer: HashMap<String, i64>,
+        fee: u64,
+        validate_only: bool,
+    ) -> Result<(String, TradeRecord), Error>;
+    async fn take_offer(&self, offer: &str, fee: u64) -> Result<TradeRecord, Error>;
+    async fn cancel_offer(&self, trade_id: &Bytes32, secure: bool, fee: u64) -> Result<(), Error>;
+    async fn get_all_offers(
+        &self,
+        start: Option<u32>,
+        end: Option<u32>,
+        exclude_my_offers: bool,
+        exclude_taken_offers: bool,
+        include_completed: bool,
+    ) -> Result<Vec<TradeRecord>, Error>;
+    async fn get_offer_summary(&self, offer: &str) -> Result<OfferSummary, Error>;
+    async fn check_offer_validity(&self, offer: &str) -> Result<bool, Error>;
+    async fn create_new_cat_wallet(
+        &self,
+        amount: u64,
+        fee: u64,
+        name: Option<String>,
+    ) -> Result<(u32, Bytes32), Error>;
+    async fn create_cat_wallet_for_asset(
+        &self,
+        asset_id: &Bytes32,
+        name: Option<String>,
+    ) -> Result<u32, Error>;
+    async fn cat_get_name(&self, wallet_id: u32) -> Result<String, Error>;
+    async fn cat_set_name(&self, wallet_id: u32, name: &str) -> Result<(), Error>;
+    async fn cat_get_asset_id(&self, wallet_id: u32) -> Result<Bytes32, Error>;
+    async fn cat_asset_id_to_name(
+        &self,
+        asset_id: &Bytes32,
+    ) -> Result<(Option<u32>, Option<String>), Error>;
+    async fn cat_spend(
+        &self,
+        wallet_id: u32,
+        inner_address: &str,
+        amount: u64,
+        fee: u64,
+        memos: Vec<String>,
+    ) -> Result<TransactionRecord, Error>;
+    async fn nft_mint_nft(
+        &self,
+        request: &NftMintReq,
+    ) -> Result<(Option<String>, SpendBundle), Error>;
+    async fn nft_get_nfts(
+        &self,
+        wallet_id: u32,
+        start_index: Option<u32>,
+        num: Option<u32>,
+    ) -> Result<Vec<NftInfo>, Error>;
+    async fn nft_get_info(&self, coin_id: &str) -> Result<NftInfo, Error>;
+    async fn nft_count_nfts(&self, wallet_id: u32) -> Result<u64, Error>;
+    async fn nft_transfer_nft(
+        &self,
+        wallet_id: u32,
+        target_address: &str,
+        nft_coin_id: &str,
+        fee: u64,
+    ) -> Result<SpendBundle, Error>;
+    async fn nft_set_nft_did(
+        &self,
+        wallet_id: u32,
+        did_id: &str,
+        nft_coin_id: &str,
+        fee: u64,
+    ) -> Result<SpendBundle, Error>;
+    async fn create_new_did_wallet(
+        &self,
+        amount: u64,
+        fee: u64,
+        backup_dids: Vec<String>,
+        num_of_backup_ids_needed: u64,
+    ) -> Result<(u32, String), Error>;
+    async fn did_get_did(&self, wallet_id: u32) -> Result<(String, Option<Bytes32>), Error>;
+    async fn did_get_wallet_name(&self, wallet_id: u32) -> Result<String, Error>;
+    async fn did_set_wallet_name(&self, wallet_id: u32, name: &str) -> Result<(), Error>;
+    async fn did_get_metadata(&self, wallet_id: u32) -> Result<HashMap<String, String>, Error>;
+    async fn did_update_metadata(
+        &self,
+        wallet_id: u32,
+        metadata: HashMap<String, String>,
+        fee: u64,
+    ) -> Result<SpendBundle, Error>;
+    async fn did_transfer_did(
+        &self,
+        wallet_id: u32,
+        inner_address: &str,
+        fee: u64,
+        with_recovery_info: bool,
+    ) -> Result<TransactionRecord, Error>;
+    async fn pw_join_pool(
+        &self,
+        wallet_id: u32,
+        target_puzzlehash: &Bytes32,
+        pool_url: &str,
+        relative_lock_height: u32,
+        fee: u64,
+    ) -> Result<PoolWalletTransaction, Error>;
+    async fn pw_self_pool(&self, wallet_id: u32, fee: u64) -> Result<PoolWalletTransaction, Error>;
+    async fn pw_absorb_rewards(
+        &self,
+        wallet_id: u32,
+        fee: u64,
+        max_spends_in_tx: Option<u32>,
+    ) -> Result<PoolWalletTransaction, Error>;
+    async fn pw_status(
+        &self,
+        wallet_id: u32,
+    ) -> Result<(PoolWalletInfo, Vec<TransactionRecord>), Error>;
+    async fn sign_message_by_address(
+        &self,
+        address: &str,
+        message: &str,
+    ) -> Result<SignedMessage, Error>;
+    async fn sign_message_by_id(&self, id: &str, message: &str) -> Result<SignedMessage, Error>;
+    async fn verify_signature(
+        &self,
+        pubkey: &Bytes48,
+        message: &str,
+        signature: &Bytes96,
+        address: Option<String>,
+        signing_mode: Option<String>,
+    ) -> Result<bool, Error>;
+    async fn get_notifications(
+        &self,
+        ids: Option<Vec<Bytes32>>,
+        start: Option<u32>,
+        end: Option<u32>,
+    ) -> Result<Vec<Notification>, Error>;
+    async fn delete_notifications(&self, ids: Option<Vec<Bytes32>>) -> Result<(), Error>;
+    async fn send_notification(
+        &self,
+        target: &Bytes32,
+        message: &[u8],
+        amount: u64,
+        fee: u64,
+    ) -> Result<TransactionRecord, Error>;
 }
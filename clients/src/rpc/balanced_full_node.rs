@@ -0,0 +1,565 @@
+use crate::api::full_node::{FullnodeAPI, FullnodeExtAPI};
+use crate::rpc::full_node::FullnodeClient;
+use crate::rpc::RpcErrorClass;
+use crate::websocket::supervisor::ReconnectPolicy;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::block_record::BlockRecord;
+use dg_xch_core::blockchain::blockchain_state::BlockchainState;
+use dg_xch_core::blockchain::coin_record::{CoinRecord, HintedCoinRecord, PaginatedCoinRecord};
+use dg_xch_core::blockchain::coin_spend::CoinSpend;
+use dg_xch_core::blockchain::full_block::FullBlock;
+use dg_xch_core::blockchain::mempool_item::MempoolItem;
+use dg_xch_core::blockchain::network_info::NetworkInfo;
+use dg_xch_core::blockchain::signage_point_or_eos::SignagePointOrEOS;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::blockchain::tx_status::TXStatus;
+use dg_xch_core::blockchain::unfinished_header_block::UnfinishedHeaderBlock;
+use dg_xch_core::protocols::full_node::{BlockCountMetrics, FeeEstimate};
+use futures_util::future::{join_all, BoxFuture};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct NodeHealth {
+    pub reachable: bool,
+    pub synced: bool,
+    pub peak_height: u32,
+    pub consecutive_failures: u32,
+    pub last_checked: Option<Instant>,
+    pub last_error: Option<String>,
+}
+impl NodeHealth {
+    //Lower is better, nodes that were never checked rank between synced and syncing nodes
+    fn tier(&self) -> u8 {
+        match (self.last_checked.is_some(), self.reachable, self.synced) {
+            (_, true, true) => 0,
+            (false, _, _) => 1,
+            (true, true, false) => 2,
+            (true, false, _) => 3,
+        }
+    }
+}
+
+struct BalancedNode {
+    client: FullnodeClient,
+    health: RwLock<NodeHealth>,
+}
+
+//Routes FullnodeAPI calls to the most up to date of several nodes, failing over and retrying
+//with backoff when a node cannot be reached
+pub struct BalancedFullnodeClient {
+    nodes: Vec<BalancedNode>,
+    pub retry_policy: ReconnectPolicy,
+}
+impl BalancedFullnodeClient {
+    pub fn new(nodes: Vec<FullnodeClient>, retry_policy: ReconnectPolicy) -> Result<Self, Error> {
+        if nodes.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "BalancedFullnodeClient needs at least one node",
+            ));
+        }
+        Ok(Self {
+            nodes: nodes
+                .into_iter()
+                .map(|client| BalancedNode {
+                    client,
+                    health: RwLock::new(NodeHealth::default()),
+                })
+                .collect(),
+            retry_policy,
+        })
+    }
+
+    pub async fn health(&self) -> Vec<(String, NodeHealth)> {
+        let mut health = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            health.push((
+                format!("{}:{}", node.client.host, node.client.port),
+                node.health.read().await.clone(),
+            ));
+        }
+        health
+    }
+
+    //Polls get_blockchain_state on every node and records its sync status and peak
+    pub async fn check_health(&self) {
+        join_all(self.nodes.iter().map(|node| async move {
+            let result = node.client.get_blockchain_state().await;
+            let mut health = node.health.write().await;
+            health.last_checked = Some(Instant::now());
+            match result {
+                Ok(state) => {
+                    health.reachable = true;
+                    health.synced = state.sync.synced;
+                    health.peak_height = state.peak.map(|p| p.height).unwrap_or_default();
+                    health.consecutive_failures = 0;
+                    health.last_error = None;
+                }
+                Err(e) => {
+                    debug!(
+                        "Health check failed for {}:{}: {:?}",
+                        node.client.host, node.client.port, e
+                    );
+                    health.reachable = false;
+                    health.synced = false;
+                    health.consecutive_failures += 1;
+                    health.last_error = Some(e.to_string());
+                }
+            }
+        }))
+        .await;
+    }
+
+    pub fn spawn_health_checks(
+        self: &Arc<Self>,
+        interval: Duration,
+        run: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        let client = self.clone();
+        tokio::spawn(async move {
+            while run.load(Ordering::Relaxed) {
+                client.check_health().await;
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    //Node indexes ordered best first, synced nodes by highest peak then fewest recent failures
+    async fn ranked(&self) -> Vec<usize> {
+        let mut ranks = Vec::with_capacity(self.nodes.len());
+        for (index, node) in self.nodes.iter().enumerate() {
+            let health = node.health.read().await;
+            ranks.push((
+                health.tier(),
+                u32::MAX - health.peak_height,
+                health.consecutive_failures,
+                index,
+            ));
+        }
+        ranks.sort();
+        ranks.into_iter().map(|(_, _, _, index)| index).collect()
+    }
+
+    async fn record_success(&self, index: usize) {
+        let node = &self.nodes[index];
+        if node.health.read().await.consecutive_failures > 0 {
+            let mut health = node.health.write().await;
+            health.reachable = true;
+            health.consecutive_failures = 0;
+            health.last_error = None;
+        }
+    }
+
+    async fn record_failure(&self, index: usize, error: &Error) {
+        let mut health = self.nodes[index].health.write().await;
+        health.reachable = false;
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.to_string());
+    }
+
+    //Non idempotent calls are only retried when the request never reached the node
+    async fn call<'a, T, F>(&'a self, idempotent: bool, request: F) -> Result<T, Error>
+    where
+        F: Fn(&'a FullnodeClient) -> BoxFuture<'a, Result<T, Error>> + Send + Sync,
+        T: Send,
+    {
+        let ranked = self.ranked().await;
+        let max_attempts = self
+            .retry_policy
+            .max_attempts
+            .unwrap_or(2 * ranked.len() as u32)
+            .max(1);
+        let mut attempt = 0;
+        loop {
+            let position = attempt as usize % ranked.len();
+            let index = ranked[position];
+            let node = &self.nodes[index];
+            attempt += 1;
+            match request(&node.client).await {
+                Ok(value) => {
+                    self.record_success(index).await;
+                    return Ok(value);
+                }
+                Err(e) => {
+                    let class = RpcErrorClass::of(&e);
+                    if !class.is_retryable() {
+                        return Err(e);
+                    }
+                    self.record_failure(index, &e).await;
+                    let safe_to_retry = idempotent || class == RpcErrorClass::Refused;
+                    if !safe_to_retry || attempt >= max_attempts {
+                        return Err(e);
+                    }
+                    warn!(
+                        "{:?} error from {}:{}, retrying: {}",
+                        class, node.client.host, node.client.port, e
+                    );
+                    //Try every node once before backing off
+                    if position + 1 == ranked.len() {
+                        tokio::time::sleep(self.retry_policy.delay(attempt / ranked.len() as u32))
+                            .await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl FullnodeAPI for BalancedFullnodeClient {
+    async fn get_blockchain_state(&self) -> Result<BlockchainState, Error> {
+        self.call(true, |c| c.get_blockchain_state()).await
+    }
+    async fn get_block(&self, header_hash: &Bytes32) -> Result<FullBlock, Error> {
+        self.call(true, |c| c.get_block(header_hash)).await
+    }
+    async fn get_blocks(
+        &self,
+        start: u32,
+        end: u32,
+        exclude_header_hash: bool,
+        exclude_reorged: bool,
+    ) -> Result<Vec<FullBlock>, Error> {
+        self.call(true, |c| {
+            c.get_blocks(start, end, exclude_header_hash, exclude_reorged)
+        })
+        .await
+    }
+    async fn get_all_blocks(&self, start: u32, end: u32) -> Result<Vec<FullBlock>, Error> {
+        self.call(true, |c| c.get_all_blocks(start, end)).await
+    }
+    async fn get_block_count_metrics(&self) -> Result<BlockCountMetrics, Error> {
+        self.call(true, |c| c.get_block_count_metrics()).await
+    }
+    async fn get_block_record_by_height(&self, height: u32) -> Result<BlockRecord, Error> {
+        self.call(true, |c| c.get_block_record_by_height(height))
+            .await
+    }
+    async fn get_block_record(&self, header_hash: &Bytes32) -> Result<BlockRecord, Error> {
+        self.call(true, |c| c.get_block_record(header_hash)).await
+    }
+    async fn get_block_records(&self, start: u32, end: u32) -> Result<Vec<BlockRecord>, Error> {
+        self.call(true, |c| c.get_block_records(start, end)).await
+    }
+    async fn get_unfinished_block_headers(&self) -> Result<Vec<UnfinishedHeaderBlock>, Error> {
+        self.call(true, |c| c.get_unfinished_block_headers()).await
+    }
+    async fn get_network_space(
+        &self,
+        older_block_header_hash: &Bytes32,
+        newer_block_header_hash: &Bytes32,
+    ) -> Result<u64, Error> {
+        self.call(true, |c| {
+            c.get_network_space(older_block_header_hash, newer_block_header_hash)
+        })
+        .await
+    }
+    async fn get_network_space_by_height(
+        &self,
+        older_block_height: u32,
+        newer_block_height: u32,
+    ) -> Result<u64, Error> {
+        self.call(true, |c| {
+            c.get_network_space_by_height(older_block_height, newer_block_height)
+        })
+        .await
+    }
+    async fn get_additions_and_removals(
+        &self,
+        header_hash: &Bytes32,
+    ) -> Result<(Vec<CoinRecord>, Vec<CoinRecord>), Error> {
+        self.call(true, |c| c.get_additions_and_removals(header_hash))
+            .await
+    }
+    async fn get_initial_freeze_period(&self) -> Result<u64, Error> {
+        self.call(true, |c| c.get_initial_freeze_period()).await
+    }
+    async fn get_network_info(&self) -> Result<NetworkInfo, Error> {
+        self.call(true, |c| c.get_network_info()).await
+    }
+    async fn get_recent_signage_point_or_eos(
+        &self,
+        sp_hash: Option<&Bytes32>,
+        challenge_hash: Option<&Bytes32>,
+    ) -> Result<SignagePointOrEOS, Error> {
+        self.call(true, |c| {
+            c.get_recent_signage_point_or_eos(sp_hash, challenge_hash)
+        })
+        .await
+    }
+    async fn get_coin_records_by_puzzle_hash(
+        &self,
+        puzzle_hash: &Bytes32,
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_puzzle_hash(
+                puzzle_hash,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+        })
+        .await
+    }
+    async fn get_coin_records_by_puzzle_hashes(
+        &self,
+        puzzle_hashes: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_puzzle_hashes(
+                puzzle_hashes,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+        })
+        .await
+    }
+    async fn get_coin_record_by_name(&self, name: &Bytes32) -> Result<Option<CoinRecord>, Error> {
+        self.call(true, |c| c.get_coin_record_by_name(name)).await
+    }
+    async fn get_coin_records_by_names(
+        &self,
+        names: &[Bytes32],
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_names(names, include_spent_coins, start_height, end_height)
+        })
+        .await
+    }
+    async fn get_coin_records_by_parent_ids(
+        &self,
+        parent_ids: &[Bytes32],
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_parent_ids(
+                parent_ids,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+        })
+        .await
+    }
+    async fn get_coin_records_by_hint(
+        &self,
+        hint: &Bytes32,
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_hint(hint, include_spent_coins, start_height, end_height)
+        })
+        .await
+    }
+    async fn push_tx(&self, spend_bundle: &SpendBundle) -> Result<TXStatus, Error> {
+        self.call(false, |c| c.push_tx(spend_bundle)).await
+    }
+    async fn get_puzzle_and_solution(
+        &self,
+        coin_id: &Bytes32,
+        height: u32,
+    ) -> Result<CoinSpend, Error> {
+        self.call(true, |c| c.get_puzzle_and_solution(coin_id, height))
+            .await
+    }
+    async fn get_coin_spend(&self, coin_record: &CoinRecord) -> Result<CoinSpend, Error> {
+        self.call(true, |c| c.get_coin_spend(coin_record)).await
+    }
+    async fn get_all_mempool_tx_ids(&self) -> Result<Vec<Bytes32>, Error> {
+        self.call(true, |c| c.get_all_mempool_tx_ids()).await
+    }
+    async fn get_all_mempool_items(&self) -> Result<HashMap<Bytes32, MempoolItem>, Error> {
+        self.call(true, |c| c.get_all_mempool_items()).await
+    }
+    async fn get_mempool_item_by_tx_id(&self, tx_id: &str) -> Result<MempoolItem, Error> {
+        self.call(true, |c| c.get_mempool_item_by_tx_id(tx_id))
+            .await
+    }
+    async fn get_mempool_items_by_coin_name(
+        &self,
+        coin_name: &Bytes32,
+    ) -> Result<Vec<MempoolItem>, Error> {
+        self.call(true, |c| c.get_mempool_items_by_coin_name(coin_name))
+            .await
+    }
+    async fn get_fee_estimate(
+        &self,
+        cost: Option<u64>,
+        spend_bundle: Option<SpendBundle>,
+        spend_type: Option<String>,
+        target_times: &[u64],
+    ) -> Result<FeeEstimate, Error> {
+        self.call(true, |c| {
+            c.get_fee_estimate(cost, spend_bundle.clone(), spend_type.clone(), target_times)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl FullnodeExtAPI for BalancedFullnodeClient {
+    async fn get_additions_and_removals_with_hints(
+        &self,
+        header_hash: &Bytes32,
+    ) -> Result<(Vec<HintedCoinRecord>, Vec<HintedCoinRecord>), Error> {
+        self.call(true, |c| {
+            c.get_additions_and_removals_with_hints(header_hash)
+        })
+        .await
+    }
+    async fn get_singleton_by_launcher_id(
+        &self,
+        launcher_id: &Bytes32,
+    ) -> Result<(CoinRecord, CoinSpend), Error> {
+        self.call(true, |c| c.get_singleton_by_launcher_id(launcher_id))
+            .await
+    }
+    async fn get_coin_records_by_hints(
+        &self,
+        hints: &[Bytes32],
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_hints(hints, include_spent_coins, start_height, end_height)
+        })
+        .await
+    }
+    async fn get_coin_records_by_hints_paginated(
+        &self,
+        hints: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        page_size: u32,
+        last_id: Option<Bytes32>,
+    ) -> Result<(Vec<PaginatedCoinRecord>, Option<Bytes32>, Option<i32>), Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_hints_paginated(
+                hints,
+                include_spent_coins,
+                start_height,
+                end_height,
+                page_size,
+                last_id,
+            )
+        })
+        .await
+    }
+    async fn get_coin_records_by_puzzle_hashes_paginated(
+        &self,
+        puzzle_hashes: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        page_size: u32,
+        last_id: Option<Bytes32>,
+    ) -> Result<(Vec<PaginatedCoinRecord>, Option<Bytes32>, Option<i32>), Error> {
+        self.call(true, |c| {
+            c.get_coin_records_by_puzzle_hashes_paginated(
+                puzzle_hashes,
+                include_spent_coins,
+                start_height,
+                end_height,
+                page_size,
+                last_id,
+            )
+        })
+        .await
+    }
+    async fn get_hints_by_coin_ids(
+        &self,
+        coin_ids: &[Bytes32],
+    ) -> Result<HashMap<Bytes32, Bytes32>, Error> {
+        self.call(true, |c| c.get_hints_by_coin_ids(coin_ids)).await
+    }
+    async fn get_puzzles_and_solutions_by_names(
+        &self,
+        names: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+    ) -> Result<HashMap<Bytes32, Option<CoinSpend>>, Error> {
+        self.call(true, |c| {
+            c.get_puzzles_and_solutions_by_names(
+                names,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+        })
+        .await
+    }
+}
+
+#[tokio::test]
+async fn test_balanced_full_node_client() {
+    //Nothing listens on these ports so every request is refused without touching the network
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+        max_attempts: Some(4),
+        ..Default::default()
+    };
+    let nodes = vec![
+        FullnodeClient::new("127.0.0.1", 1, 2, None, &None),
+        FullnodeClient::new("127.0.0.1", 2, 2, None, &None),
+    ];
+    let client = BalancedFullnodeClient::new(nodes, policy).unwrap();
+    let err = client.get_blockchain_state().await.unwrap_err();
+    assert_eq!(RpcErrorClass::of(&err), RpcErrorClass::Refused);
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let health = client.health().await;
+    assert_eq!(health.len(), 2);
+    assert!(health.iter().all(|(_, h)| h.consecutive_failures == 2));
+    client.check_health().await;
+    assert!(client
+        .health()
+        .await
+        .iter()
+        .all(|(_, h)| !h.reachable && h.last_checked.is_some()));
+    {
+        let mut health = client.nodes[1].health.write().await;
+        health.reachable = true;
+        health.synced = true;
+        health.peak_height = 100;
+    }
+    assert_eq!(client.ranked().await, vec![1, 0]);
+    assert!(BalancedFullnodeClient::new(vec![], ReconnectPolicy::default()).is_err());
+    assert!(!RpcErrorClass::of(&Error::new(ErrorKind::InvalidInput, "")).is_retryable());
+    assert!(RpcErrorClass::of(&Error::new(ErrorKind::TimedOut, "")).is_retryable());
+    let rejected = crate::rpc::check_success(
+        crate::api::responses::SuccessResp {
+            success: false,
+            error: Some("Transaction not in the mempool".to_string()),
+        },
+        "https://127.0.0.1/push_tx",
+    )
+    .unwrap_err();
+    assert_eq!(rejected.kind(), ErrorKind::InvalidData);
+    assert_eq!(RpcErrorClass::of(&rejected), RpcErrorClass::Rpc);
+    assert!(rejected.to_string().contains("not in the mempool"));
+}
@@ -0,0 +1,698 @@
+use crate::api::full_node::{FullnodeAPI, FullnodeExtAPI};
+use async_trait::async_trait;
+use dg_xch_core::blockchain::block_record::BlockRecord;
+use dg_xch_core::blockchain::blockchain_state::BlockchainState;
+use dg_xch_core::blockchain::coin_record::{CoinRecord, HintedCoinRecord, PaginatedCoinRecord};
+use dg_xch_core::blockchain::coin_spend::CoinSpend;
+use dg_xch_core::blockchain::full_block::FullBlock;
+use dg_xch_core::blockchain::mempool_item::MempoolItem;
+use dg_xch_core::blockchain::network_info::NetworkInfo;
+use dg_xch_core::blockchain::signage_point_or_eos::SignagePointOrEOS;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::blockchain::tx_status::TXStatus;
+use dg_xch_core::blockchain::unfinished_header_block::UnfinishedHeaderBlock;
+use dg_xch_core::protocols::full_node::{BlockCountMetrics, FeeEstimate};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+const BLOCKS: &str = "blocks";
+const BLOCK_RECORDS: &str = "block_records";
+const ADDITIONS_AND_REMOVALS: &str = "additions_and_removals";
+const COIN_SPENDS: &str = "coin_spends";
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    //Entries kept in memory for each cached endpoint
+    pub capacity: usize,
+    //Results are only cached once they are at least this many blocks below the peak
+    pub reorg_depth: u32,
+    //How long a fetched peak height is trusted before get_blockchain_state is called again
+    pub peak_refresh: Duration,
+    pub disk_path: Option<PathBuf>,
+}
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            reorg_depth: 32,
+            peak_refresh: Duration::from_secs(10),
+            disk_path: None,
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub disk_hits: u64,
+    pub misses: u64,
+    pub stores: u64,
+    //Misses that could not be cached because the result was still within reorg_depth of the peak
+    pub too_recent: u64,
+}
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+struct CacheMetrics {
+    hits: AtomicU64,
+    disk_hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+    too_recent: AtomicU64,
+}
+
+pub struct LruCache<V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, (V, u64)>,
+    order: BTreeMap<u64, String>,
+}
+impl<V: Clone> LruCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<V> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.order.remove(last_used);
+        *last_used = tick;
+        self.order.insert(tick, key.to_string());
+        Some(value.clone())
+    }
+
+    pub fn insert(&mut self, key: String, value: V) {
+        self.tick += 1;
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.order.remove(&last_used);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > self.capacity {
+            match self.order.pop_first() {
+                Some((_, oldest)) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+//One json file per entry under <path>/<kind>/<key>.json
+pub struct DiskCache {
+    pub path: PathBuf,
+}
+impl DiskCache {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn entry_path(&self, kind: &str, key: &str) -> PathBuf {
+        self.path.join(kind).join(format!("{key}.json"))
+    }
+
+    pub async fn get<V: DeserializeOwned>(&self, kind: &str, key: &str) -> Option<V> {
+        let bytes = tokio::fs::read(self.entry_path(kind, key)).await.ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                debug!("Ignoring corrupt cache entry {kind}/{key}: {:?}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn put<V: Serialize>(&self, kind: &str, key: &str, value: &V) -> Result<(), Error> {
+        let path = self.entry_path(kind, key);
+        let bytes =
+            serde_json::to_vec(value).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        tokio::fs::create_dir_all(self.path.join(kind)).await?;
+        let tmp = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, &path).await
+    }
+}
+
+struct PeakState {
+    height: u32,
+    checked: Option<Instant>,
+}
+
+//Caches lookups by header hash or coin name, which never change once they are buried deeper
+//than any reorg, in memory and optionally on disk
+pub struct CachingFullnodeClient<T> {
+    pub inner: T,
+    pub config: CacheConfig,
+    blocks: Mutex<LruCache<FullBlock>>,
+    block_records: Mutex<LruCache<BlockRecord>>,
+    additions_and_removals: Mutex<LruCache<(Vec<CoinRecord>, Vec<CoinRecord>)>>,
+    coin_spends: Mutex<LruCache<CoinSpend>>,
+    disk: Option<DiskCache>,
+    peak: RwLock<PeakState>,
+    metrics: CacheMetrics,
+}
+impl<T: FullnodeAPI + Send + Sync> CachingFullnodeClient<T> {
+    pub fn new(inner: T, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            blocks: Mutex::new(LruCache::new(config.capacity)),
+            block_records: Mutex::new(LruCache::new(config.capacity)),
+            additions_and_removals: Mutex::new(LruCache::new(config.capacity)),
+            coin_spends: Mutex::new(LruCache::new(config.capacity)),
+            disk: config.disk_path.clone().map(DiskCache::new),
+            peak: RwLock::new(PeakState {
+                height: 0,
+                checked: None,
+            }),
+            metrics: CacheMetrics::default(),
+            config,
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.metrics.hits.load(Ordering::Relaxed),
+            disk_hits: self.metrics.disk_hits.load(Ordering::Relaxed),
+            misses: self.metrics.misses.load(Ordering::Relaxed),
+            stores: self.metrics.stores.load(Ordering::Relaxed),
+            too_recent: self.metrics.too_recent.load(Ordering::Relaxed),
+        }
+    }
+
+    async fn update_peak(&self, state: &BlockchainState) {
+        if let Some(peak) = &state.peak {
+            let mut current = self.peak.write().await;
+            current.height = peak.height;
+            current.checked = Some(Instant::now());
+        }
+    }
+
+    //Highest height that is safe to cache, None when the peak is unknown
+    async fn buried_height(&self) -> Option<u32> {
+        {
+            let peak = self.peak.read().await;
+            if let Some(checked) = peak.checked {
+                if checked.elapsed() < self.config.peak_refresh {
+                    return peak.height.checked_sub(self.config.reorg_depth);
+                }
+            }
+        }
+        match self.inner.get_blockchain_state().await {
+            Ok(state) => {
+                self.update_peak(&state).await;
+                state
+                    .peak
+                    .and_then(|p| p.height.checked_sub(self.config.reorg_depth))
+            }
+            Err(e) => {
+                debug!("Failed to refresh peak for cache: {:?}", e);
+                None
+            }
+        }
+    }
+
+    async fn lookup<V: Clone + DeserializeOwned>(
+        &self,
+        memory: &Mutex<LruCache<V>>,
+        kind: &str,
+        key: &str,
+    ) -> Option<V> {
+        let cached = match memory.lock() {
+            Ok(mut cache) => cache.get(key),
+            Err(_) => None,
+        };
+        if cached.is_some() {
+            self.metrics.hits.fetch_add(1, Ordering::Relaxed);
+            return cached;
+        }
+        if let Some(disk) = &self.disk {
+            if let Some(value) = disk.get::<V>(kind, key).await {
+                self.metrics.hits.fetch_add(1, Ordering::Relaxed);
+                self.metrics.disk_hits.fetch_add(1, Ordering::Relaxed);
+                if let Ok(mut cache) = memory.lock() {
+                    cache.insert(key.to_string(), value.clone());
+                }
+                return Some(value);
+            }
+        }
+        self.metrics.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    async fn store<V: Clone + Serialize>(
+        &self,
+        memory: &Mutex<LruCache<V>>,
+        kind: &str,
+        key: String,
+        value: &V,
+        height: u32,
+    ) {
+        match self.buried_height().await {
+            Some(buried) if height <= buried => {}
+            _ => {
+                self.metrics.too_recent.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+        if let Some(disk) = &self.disk {
+            if let Err(e) = disk.put(kind, &key, value).await {
+                debug!("Failed to write cache entry {kind}/{key}: {:?}", e);
+            }
+        }
+        if let Ok(mut cache) = memory.lock() {
+            cache.insert(key, value.clone());
+        }
+        self.metrics.stores.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl<T: FullnodeAPI + Send + Sync> FullnodeAPI for CachingFullnodeClient<T> {
+    async fn get_blockchain_state(&self) -> Result<BlockchainState, Error> {
+        let state = self.inner.get_blockchain_state().await?;
+        self.update_peak(&state).await;
+        Ok(state)
+    }
+    async fn get_block(&self, header_hash: &Bytes32) -> Result<FullBlock, Error> {
+        let key = hex::encode(header_hash.bytes);
+        if let Some(block) = self.lookup(&self.blocks, BLOCKS, &key).await {
+            return Ok(block);
+        }
+        let block = self.inner.get_block(header_hash).await?;
+        let height = block.reward_chain_block.height as u32;
+        self.store(&self.blocks, BLOCKS, key, &block, height).await;
+        Ok(block)
+    }
+    async fn get_blocks(
+        &self,
+        start: u32,
+        end: u32,
+        exclude_header_hash: bool,
+        exclude_reorged: bool,
+    ) -> Result<Vec<FullBlock>, Error> {
+        self.inner
+            .get_blocks(start, end, exclude_header_hash, exclude_reorged)
+            .await
+    }
+    async fn get_all_blocks(&self, start: u32, end: u32) -> Result<Vec<FullBlock>, Error> {
+        self.inner.get_all_blocks(start, end).await
+    }
+    async fn get_block_count_metrics(&self) -> Result<BlockCountMetrics, Error> {
+        self.inner.get_block_count_metrics().await
+    }
+    async fn get_block_record_by_height(&self, height: u32) -> Result<BlockRecord, Error> {
+        self.inner.get_block_record_by_height(height).await
+    }
+    async fn get_block_record(&self, header_hash: &Bytes32) -> Result<BlockRecord, Error> {
+        let key = hex::encode(header_hash.bytes);
+        if let Some(record) = self.lookup(&self.block_records, BLOCK_RECORDS, &key).await {
+            return Ok(record);
+        }
+        let record = self.inner.get_block_record(header_hash).await?;
+        self.store(
+            &self.block_records,
+            BLOCK_RECORDS,
+            key,
+            &record,
+            record.height,
+        )
+        .await;
+        Ok(record)
+    }
+    async fn get_block_records(&self, start: u32, end: u32) -> Result<Vec<BlockRecord>, Error> {
+        self.inner.get_block_records(start, end).await
+    }
+    async fn get_unfinished_block_headers(&self) -> Result<Vec<UnfinishedHeaderBlock>, Error> {
+        self.inner.get_unfinished_block_headers().await
+    }
+    async fn get_network_space(
+        &self,
+        older_block_header_hash: &Bytes32,
+        newer_block_header_hash: &Bytes32,
+    ) -> Result<u64, Error> {
+        self.inner
+            .get_network_space(older_block_header_hash, newer_block_header_hash)
+            .await
+    }
+    async fn get_network_space_by_height(
+        &self,
+        older_block_height: u32,
+        newer_block_height: u32,
+    ) -> Result<u64, Error> {
+        self.inner
+            .get_network_space_by_height(older_block_height, newer_block_height)
+            .await
+    }
+    async fn get_additions_and_removals(
+        &self,
+        header_hash: &Bytes32,
+    ) -> Result<(Vec<CoinRecord>, Vec<CoinRecord>), Error> {
+        let key = hex::encode(header_hash.bytes);
+        if let Some(value) = self
+            .lookup(&self.additions_and_removals, ADDITIONS_AND_REMOVALS, &key)
+            .await
+        {
+            return Ok(value);
+        }
+        let value = self.inner.get_additions_and_removals(header_hash).await?;
+        //Blocks without transactions have no coins to take the height from, the block record is
+        //fetched from the inner client so the lookup does not count against the cache stats
+        let height = match coin_records_height(&value.0, &value.1) {
+            Some(height) => Some(height),
+            None => self
+                .inner
+                .get_block_record(header_hash)
+                .await
+                .ok()
+                .map(|record| record.height),
+        };
+        if let Some(height) = height {
+            self.store(
+                &self.additions_and_removals,
+                ADDITIONS_AND_REMOVALS,
+                key,
+                &value,
+                height,
+            )
+            .await;
+        }
+        Ok(value)
+    }
+    async fn get_initial_freeze_period(&self) -> Result<u64, Error> {
+        self.inner.get_initial_freeze_period().await
+    }
+    async fn get_network_info(&self) -> Result<NetworkInfo, Error> {
+        self.inner.get_network_info().await
+    }
+    async fn get_recent_signage_point_or_eos(
+        &self,
+        sp_hash: Option<&Bytes32>,
+        challenge_hash: Option<&Bytes32>,
+    ) -> Result<SignagePointOrEOS, Error> {
+        self.inner
+            .get_recent_signage_point_or_eos(sp_hash, challenge_hash)
+            .await
+    }
+    async fn get_coin_records_by_puzzle_hash(
+        &self,
+        puzzle_hash: &Bytes32,
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.inner
+            .get_coin_records_by_puzzle_hash(
+                puzzle_hash,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+            .await
+    }
+    async fn get_coin_records_by_puzzle_hashes(
+        &self,
+        puzzle_hashes: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.inner
+            .get_coin_records_by_puzzle_hashes(
+                puzzle_hashes,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+            .await
+    }
+    async fn get_coin_record_by_name(&self, name: &Bytes32) -> Result<Option<CoinRecord>, Error> {
+        self.inner.get_coin_record_by_name(name).await
+    }
+    async fn get_coin_records_by_names(
+        &self,
+        names: &[Bytes32],
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.inner
+            .get_coin_records_by_names(names, include_spent_coins, start_height, end_height)
+            .await
+    }
+    async fn get_coin_records_by_parent_ids(
+        &self,
+        parent_ids: &[Bytes32],
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.inner
+            .get_coin_records_by_parent_ids(
+                parent_ids,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+            .await
+    }
+    async fn get_coin_records_by_hint(
+        &self,
+        hint: &Bytes32,
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.inner
+            .get_coin_records_by_hint(hint, include_spent_coins, start_height, end_height)
+            .await
+    }
+    async fn push_tx(&self, spend_bundle: &SpendBundle) -> Result<TXStatus, Error> {
+        self.inner.push_tx(spend_bundle).await
+    }
+    async fn get_puzzle_and_solution(
+        &self,
+        coin_id: &Bytes32,
+        height: u32,
+    ) -> Result<CoinSpend, Error> {
+        let key = format!("{}_{height}", hex::encode(coin_id.bytes));
+        if let Some(spend) = self.lookup(&self.coin_spends, COIN_SPENDS, &key).await {
+            return Ok(spend);
+        }
+        let spend = self.inner.get_puzzle_and_solution(coin_id, height).await?;
+        self.store(&self.coin_spends, COIN_SPENDS, key, &spend, height)
+            .await;
+        Ok(spend)
+    }
+    async fn get_coin_spend(&self, coin_record: &CoinRecord) -> Result<CoinSpend, Error> {
+        self.get_puzzle_and_solution(&coin_record.coin.name(), coin_record.spent_block_index)
+            .await
+    }
+    async fn get_all_mempool_tx_ids(&self) -> Result<Vec<Bytes32>, Error> {
+        self.inner.get_all_mempool_tx_ids().await
+    }
+    async fn get_all_mempool_items(&self) -> Result<HashMap<Bytes32, MempoolItem>, Error> {
+        self.inner.get_all_mempool_items().await
+    }
+    async fn get_mempool_item_by_tx_id(&self, tx_id: &str) -> Result<MempoolItem, Error> {
+        self.inner.get_mempool_item_by_tx_id(tx_id).await
+    }
+    async fn get_mempool_items_by_coin_name(
+        &self,
+        coin_name: &Bytes32,
+    ) -> Result<Vec<MempoolItem>, Error> {
+        self.inner.get_mempool_items_by_coin_name(coin_name).await
+    }
+    async fn get_fee_estimate(
+        &self,
+        cost: Option<u64>,
+        spend_bundle: Option<SpendBundle>,
+        spend_type: Option<String>,
+        target_times: &[u64],
+    ) -> Result<FeeEstimate, Error> {
+        self.inner
+            .get_fee_estimate(cost, spend_bundle, spend_type, target_times)
+            .await
+    }
+}
+
+#[async_trait]
+impl<T: FullnodeAPI + FullnodeExtAPI + Send + Sync> FullnodeExtAPI for CachingFullnodeClient<T> {
+    async fn get_additions_and_removals_with_hints(
+        &self,
+        header_hash: &Bytes32,
+    ) -> Result<(Vec<HintedCoinRecord>, Vec<HintedCoinRecord>), Error> {
+        self.inner
+            .get_additions_and_removals_with_hints(header_hash)
+            .await
+    }
+    async fn get_singleton_by_launcher_id(
+        &self,
+        launcher_id: &Bytes32,
+    ) -> Result<(CoinRecord, CoinSpend), Error> {
+        self.inner.get_singleton_by_launcher_id(launcher_id).await
+    }
+    async fn get_coin_records_by_hints(
+        &self,
+        hints: &[Bytes32],
+        include_spent_coins: bool,
+        start_height: u32,
+        end_height: u32,
+    ) -> Result<Vec<CoinRecord>, Error> {
+        self.inner
+            .get_coin_records_by_hints(hints, include_spent_coins, start_height, end_height)
+            .await
+    }
+    async fn get_coin_records_by_hints_paginated(
+        &self,
+        hints: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        page_size: u32,
+        last_id: Option<Bytes32>,
+    ) -> Result<(Vec<PaginatedCoinRecord>, Option<Bytes32>, Option<i32>), Error> {
+        self.inner
+            .get_coin_records_by_hints_paginated(
+                hints,
+                include_spent_coins,
+                start_height,
+                end_height,
+                page_size,
+                last_id,
+            )
+            .await
+    }
+    async fn get_coin_records_by_puzzle_hashes_paginated(
+        &self,
+        puzzle_hashes: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+        page_size: u32,
+        last_id: Option<Bytes32>,
+    ) -> Result<(Vec<PaginatedCoinRecord>, Option<Bytes32>, Option<i32>), Error> {
+        self.inner
+            .get_coin_records_by_puzzle_hashes_paginated(
+                puzzle_hashes,
+                include_spent_coins,
+                start_height,
+                end_height,
+                page_size,
+                last_id,
+            )
+            .await
+    }
+    async fn get_hints_by_coin_ids(
+        &self,
+        coin_ids: &[Bytes32],
+    ) -> Result<HashMap<Bytes32, Bytes32>, Error> {
+        self.inner.get_hints_by_coin_ids(coin_ids).await
+    }
+    async fn get_puzzles_and_solutions_by_names(
+        &self,
+        names: &[Bytes32],
+        include_spent_coins: Option<bool>,
+        start_height: Option<u32>,
+        end_height: Option<u32>,
+    ) -> Result<HashMap<Bytes32, Option<CoinSpend>>, Error> {
+        self.inner
+            .get_puzzles_and_solutions_by_names(
+                names,
+                include_spent_coins,
+                start_height,
+                end_height,
+            )
+            .await
+    }
+}
+
+//Additions are confirmed and removals spent in the block they were returned for
+fn coin_records_height(additions: &[CoinRecord], removals: &[CoinRecord]) -> Option<u32> {
+    additions
+        .first()
+        .map(|record| record.confirmed_block_index)
+        .or_else(|| removals.first().map(|record| record.spent_block_index))
+}
+
+#[tokio::test]
+async fn test_response_cache() {
+    let mut lru = LruCache::new(2);
+    lru.insert("a".to_string(), 1u32);
+    lru.insert("b".to_string(), 2);
+    assert_eq!(lru.get("a"), Some(1));
+    lru.insert("c".to_string(), 3);
+    assert_eq!(lru.get("b"), None);
+    assert_eq!(lru.get("a"), Some(1));
+    assert_eq!(lru.get("c"), Some(3));
+    lru.insert("c".to_string(), 4);
+    assert_eq!(lru.len(), 2);
+    assert_eq!(lru.get("c"), Some(4));
+    let dir = std::env::temp_dir().join(format!("dg_cache_{}", uuid::Uuid::new_v4()));
+    let disk = DiskCache::new(dir.clone());
+    assert_eq!(disk.get::<Vec<u32>>(COIN_SPENDS, "missing").await, None);
+    disk.put(COIN_SPENDS, "key", &vec![1u32, 2, 3])
+        .await
+        .unwrap();
+    assert_eq!(
+        disk.get::<Vec<u32>>(COIN_SPENDS, "key").await,
+        Some(vec![1, 2, 3])
+    );
+    let stats = CacheStats {
+        hits: 3,
+        misses: 1,
+        ..Default::default()
+    };
+    assert_eq!(stats.hit_ratio(), 0.75);
+    use dg_xch_core::blockchain::coin::Coin;
+    let record = |confirmed, spent| CoinRecord {
+        coin: Coin {
+            parent_coin_info: Bytes32::default(),
+            puzzle_hash: Bytes32::default(),
+            amount: 1,
+        },
+        confirmed_block_index: confirmed,
+        spent_block_index: spent,
+        coinbase: false,
+        timestamp: 0,
+        spent: spent > 0,
+    };
+    assert_eq!(
+        coin_records_height(&[record(12, 0)], &[record(3, 12)]),
+        Some(12)
+    );
+    assert_eq!(coin_records_height(&[], &[record(3, 15)]), Some(15));
+    assert_eq!(coin_records_height(&[], &[]), None);
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,88 @@
+use crate::api::crawler::CrawlerAPI;
+use crate::api::responses::{IpsAfterTimestampResp, PeerCountsResp};
+use crate::rpc::{get_client, get_url, post};
+use crate::ClientSSLConfig;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::peer_counts::PeerCounts;
+use reqwest::Client;
+use serde_json::{json, Map};
+use std::collections::HashMap;
+use std::io::Error;
+
+pub struct CrawlerClient {
+    client: Client,
+    pub host: String,
+    pub port: u16,
+    pub ssl_path: Option<ClientSSLConfig>,
+    pub additional_headers: Option<HashMap<String, String>>,
+}
+
+impl CrawlerClient {
+    pub fn new(
+        host: &str,
+        port: u16,
+        timeout: u64,
+        ssl_path: Option<ClientSSLConfig>,
+        additional_headers: &Option<HashMap<String, String>>,
+    ) -> Self {
+        CrawlerClient {
+            client: get_client(ssl_path.clone(), timeout).unwrap(),
+            host: host.to_string(),
+            port,
+            ssl_path,
+            additional_headers: additional_headers.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl CrawlerAPI for CrawlerClient {
+    async fn get_peer_counts(&self) -> Result<PeerCounts, Error> {
+        Ok(post::<PeerCountsResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_peer_counts"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .peer_counts)
+    }
+    async fn get_ips_after_timestamp(
+        &self,
+        after: u64,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<(Vec<String>, u64), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("after".to_string(), json!(after));
+        if let Some(offset) = offset {
+            request_body.insert("offset".to_string(), json!(offset));
+        }
+        if let Some(limit) = limit {
+            request_body.insert("limit".to_string(), json!(limit));
+        }
+        let resp = post::<IpsAfterTimestampResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_ips_after_timestamp"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok((resp.ips, resp.total))
+    }
+}
+
+#[test]
+fn test_crawler_responses() {
+    let resp: PeerCountsResp = serde_json::from_str(
+        r#"{"peer_counts": {"total_last_5_days": 25000, "reliable_nodes": 9000,
+            "ipv4_last_5_days": 24000, "ipv6_last_5_days": 1000,
+            "versions": {"2.1.4": 6000, "2.2.1": 3000}}, "success": true}"#,
+    )
+    .unwrap();
+    assert_eq!(resp.peer_counts.versions["2.1.4"], 6000);
+    let resp: IpsAfterTimestampResp =
+        serde_json::from_str(r#"{"ips": ["1.2.3.4", "2001:db8::1"], "total": 2, "success": true}"#)
+            .unwrap();
+    assert_eq!(resp.ips.len() as u64, resp.total);
+}
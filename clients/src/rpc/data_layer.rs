@@ -0,0 +1,390 @@
+use crate::api::data_layer::DataLayerAPI;
+use crate::api::responses::{
+    AddMirrorReq, BatchUpdateReq, BatchUpdateResp, CreateDataStoreReq, CreateDataStoreResp,
+    DataLayerChange, DataLayerDiff, DataLayerKeyValue, DataLayerMirror, DataLayerRoot,
+    DeleteMirrorReq, GetKeysValuesReq, GetKeysValuesResp, GetKvDiffReq, GetKvDiffResp,
+    GetMirrorsResp, GetRootHistoryResp, GetRootResp, GetValueReq, GetValueResp, StoreIdReq,
+    SubscribeReq, SubscriptionsResp, SuccessResp, UnsubscribeReq,
+};
+use crate::rpc::{check_success, get_client, get_url, post, to_request_body};
+use crate::ClientSSLConfig;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::blockchain::unsized_bytes::UnsizedBytes;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Map;
+use std::collections::HashMap;
+use std::io::Error;
+
+pub struct DataLayerClient {
+    client: Client,
+    pub host: String,
+    pub port: u16,
+    pub ssl_path: Option<ClientSSLConfig>,
+    pub additional_headers: Option<HashMap<String, String>>,
+}
+
+impl DataLayerClient {
+    pub fn new(
+        host: &str,
+        port: u16,
+        timeout: u64,
+        ssl_path: Option<ClientSSLConfig>,
+        additional_headers: &Option<HashMap<String, String>>,
+    ) -> Self {
+        DataLayerClient {
+            client: get_client(ssl_path.clone(), timeout).unwrap(),
+            host: host.to_string(),
+            port,
+            ssl_path,
+            additional_headers: additional_headers.clone(),
+        }
+    }
+
+    async fn call<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        request: &Req,
+    ) -> Result<Resp, Error> {
+        post::<Resp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, endpoint),
+            &to_request_body(request)?,
+            &self.additional_headers,
+        )
+        .await
+    }
+
+    async fn call_empty<Req: Serialize>(&self, endpoint: &str, request: &Req) -> Result<(), Error> {
+        let resp = self.call::<_, SuccessResp>(endpoint, request).await?;
+        check_success(resp, endpoint)
+    }
+}
+
+#[async_trait]
+impl DataLayerAPI for DataLayerClient {
+    async fn create_data_store(&self, fee: Option<u64>) -> Result<Bytes32, Error> {
+        Ok(self
+            .call::<_, CreateDataStoreResp>(
+                "create_data_store",
+                &CreateDataStoreReq {
+                    fee,
+                    verbose: false,
+                },
+            )
+            .await?
+            .id)
+    }
+    async fn batch_update(
+        &self,
+        id: &Bytes32,
+        changelist: Vec<DataLayerChange>,
+        fee: Option<u64>,
+        submit_on_chain: Option<bool>,
+    ) -> Result<Option<Bytes32>, Error> {
+        Ok(self
+            .call::<_, BatchUpdateResp>(
+                "batch_update",
+                &BatchUpdateReq {
+                    id: *id,
+                    changelist,
+                    fee,
+                    submit_on_chain,
+                },
+            )
+            .await?
+            .tx_id)
+    }
+    async fn get_value(
+        &self,
+        id: &Bytes32,
+        key: &[u8],
+        root_hash: Option<Bytes32>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self
+            .call::<_, GetValueResp>(
+                "get_value",
+                &GetValueReq {
+                    id: *id,
+                    key: UnsizedBytes {
+                        bytes: key.to_vec(),
+                    },
+                    root_hash,
+                },
+            )
+            .await?
+            .value
+            .map(|v| v.bytes))
+    }
+    async fn get_keys_values(
+        &self,
+        id: &Bytes32,
+        root_hash: Option<Bytes32>,
+        page: Option<u32>,
+        max_page_size: Option<u32>,
+    ) -> Result<Vec<DataLayerKeyValue>, Error> {
+        Ok(self
+            .call::<_, GetKeysValuesResp>(
+                "get_keys_values",
+                &GetKeysValuesReq {
+                    id: *id,
+                    root_hash,
+                    page,
+                    max_page_size,
+                },
+            )
+            .await?
+            .keys_values)
+    }
+    async fn get_root(&self, id: &Bytes32) -> Result<DataLayerRoot, Error> {
+        let resp = self
+            .call::<_, GetRootResp>("get_root", &StoreIdReq { id: *id })
+            .await?;
+        Ok(DataLayerRoot {
+            hash: resp.hash,
+            confirmed: resp.confirmed,
+            timestamp: resp.timestamp,
+        })
+    }
+    async fn get_root_history(&self, id: &Bytes32) -> Result<Vec<DataLayerRoot>, Error> {
+        Ok(self
+            .call::<_, GetRootHistoryResp>("get_root_history", &StoreIdReq { id: *id })
+            .await?
+            .root_history)
+    }
+    async fn get_kv_diff(
+        &self,
+        id: &Bytes32,
+        hash_1: &Bytes32,
+        hash_2: &Bytes32,
+        page: Option<u32>,
+        max_page_size: Option<u32>,
+    ) -> Result<Vec<DataLayerDiff>, Error> {
+        Ok(self
+            .call::<_, GetKvDiffResp>(
+                "get_kv_diff",
+                &GetKvDiffReq {
+                    id: *id,
+                    hash_1: *hash_1,
+                    hash_2: *hash_2,
+                    page,
+                    max_page_size,
+                },
+            )
+            .await?
+            .diff)
+    }
+    async fn subscribe(&self, id: &Bytes32, urls: Vec<String>) -> Result<(), Error> {
+        self.call_empty("subscribe", &SubscribeReq { id: *id, urls })
+            .await
+    }
+    async fn unsubscribe(&self, id: &Bytes32, retain: bool) -> Result<(), Error> {
+        self.call_empty("unsubscribe", &UnsubscribeReq { id: *id, retain })
+            .await
+    }
+    async fn subscriptions(&self) -> Result<Vec<Bytes32>, Error> {
+        Ok(self
+            .call::<_, SubscriptionsResp>("subscriptions", &Map::new())
+            .await?
+            .store_ids)
+    }
+    async fn add_mirror(
+        &self,
+        id: &Bytes32,
+        urls: Vec<String>,
+        amount: u64,
+        fee: Option<u64>,
+    ) -> Result<(), Error> {
+        self.call_empty(
+            "add_mirror",
+            &AddMirrorReq {
+                id: *id,
+                urls,
+                amount,
+                fee,
+            },
+        )
+        .await
+    }
+    async fn delete_mirror(&self, coin_id: &Bytes32, fee: Option<u64>) -> Result<(), Error> {
+        self.call_empty(
+            "delete_mirror",
+            &DeleteMirrorReq {
+                coin_id: *coin_id,
+                fee,
+            },
+        )
+        .await
+    }
+    async fn get_mirrors(&self, id: &Bytes32) -> Result<Vec<DataLayerMirror>, Error> {
+        Ok(self
+            .call::<_, GetMirrorsResp>("get_mirrors", &StoreIdReq { id: *id })
+            .await?
+            .mirrors)
+    }
+}
+
+#[tokio::test]
+async fn test_data_layer_client() {
+    use crate::api::responses::{DataLayerAction, DataLayerDiffType};
+    use dg_xch_core::blockchain::sized_bytes::SizedBytes;
+    use dg_xch_core::ssl::{
+        generate_ca_signed_cert_data, load_certs_from_bytes, load_private_key_from_bytes,
+        CHIA_CA_CRT, CHIA_CA_KEY,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::sync::Mutex;
+    //Stub DataLayer service answering each endpoint with a canned body and recording requests
+    let (cert, key) =
+        generate_ca_signed_cert_data(CHIA_CA_CRT.as_bytes(), CHIA_CA_KEY.as_bytes()).unwrap();
+    let server_config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(
+            load_certs_from_bytes(&cert).unwrap(),
+            load_private_key_from_bytes(&key).unwrap(),
+        )
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let store_id = Bytes32::new(&[1u8; 32]);
+    let root_hash = Bytes32::new(&[2u8; 32]);
+    let responses: HashMap<&str, Value> = HashMap::from([
+        (
+            "create_data_store",
+            json!({"id": store_id, "success": true}),
+        ),
+        ("batch_update", json!({"tx_id": root_hash, "success": true})),
+        ("get_value", json!({"value": "beef", "success": true})),
+        (
+            "get_keys_values",
+            json!({"keys_values": [{"hash": root_hash, "key": "0x01", "value": "0x02"}], "success": true}),
+        ),
+        (
+            "get_root",
+            json!({"hash": root_hash, "confirmed": true, "timestamp": 1700000000, "success": true}),
+        ),
+        (
+            "get_root_history",
+            json!({"root_history": [{"root_hash": root_hash, "confirmed": false, "timestamp": 1700000001}], "success": true}),
+        ),
+        (
+            "get_kv_diff",
+            json!({"diff": [{"type": "DELETE", "key": "01", "value": "02"}], "success": true}),
+        ),
+        ("subscribe", json!({"success": true})),
+        (
+            "add_mirror",
+            json!({"success": false, "error": "Insufficient funds"}),
+        ),
+        (
+            "get_mirrors",
+            json!({"mirrors": [{"coin_id": root_hash, "launcher_id": store_id, "amount": 1, "urls": ["http://mirror"], "ours": true}], "success": true}),
+        ),
+    ]);
+    let requests: Arc<Mutex<Vec<(String, Value)>>> = Arc::default();
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            let mut data = vec![];
+            let mut buf = [0u8; 4096];
+            let (header_end, content_length) = loop {
+                let read = stream.read(&mut buf).await.unwrap();
+                data.extend_from_slice(&buf[..read]);
+                if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                    let headers = String::from_utf8_lossy(&data[..pos]).to_lowercase();
+                    let length = headers
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .map(|l| l.trim().parse::<usize>().unwrap())
+                        .unwrap_or_default();
+                    break (pos + 4, length);
+                }
+            };
+            while data.len() < header_end + content_length {
+                let read = stream.read(&mut buf).await.unwrap();
+                data.extend_from_slice(&buf[..read]);
+            }
+            let request_line = String::from_utf8_lossy(&data[..header_end]).to_string();
+            let endpoint = request_line
+                .split_whitespace()
+                .nth(1)
+                .unwrap()
+                .trim_start_matches('/')
+                .to_string();
+            let body: Value = serde_json::from_slice(&data[header_end..]).unwrap();
+            let response = responses[endpoint.as_str()].to_string();
+            recorded.lock().await.push((endpoint, body));
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            );
+            stream.write_all(reply.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+    let client = DataLayerClient::new("127.0.0.1", port, 10, None, &None);
+    assert_eq!(client.create_data_store(Some(5)).await.unwrap(), store_id);
+    let tx_id = client
+        .batch_update(
+            &store_id,
+            vec![DataLayerChange {
+                action: DataLayerAction::Insert,
+                key: UnsizedBytes { bytes: vec![1] },
+                value: Some(UnsizedBytes { bytes: vec![2] }),
+            }],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(tx_id, Some(root_hash));
+    assert_eq!(
+        client.get_value(&store_id, &[1], None).await.unwrap(),
+        Some(vec![0xbe, 0xef])
+    );
+    let keys_values = client
+        .get_keys_values(&store_id, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(keys_values[0].value.bytes, vec![2]);
+    let root = client.get_root(&store_id).await.unwrap();
+    assert!(root.confirmed);
+    let history = client.get_root_history(&store_id).await.unwrap();
+    assert_eq!(history[0].hash, root_hash);
+    let diff = client
+        .get_kv_diff(&store_id, &root_hash, &root_hash, None, None)
+        .await
+        .unwrap();
+    assert_eq!(diff[0].diff_type, DataLayerDiffType::Delete);
+    client
+        .subscribe(&store_id, vec!["http://mirror".to_string()])
+        .await
+        .unwrap();
+    let err = client
+        .add_mirror(&store_id, vec![], 1, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Insufficient funds"));
+    assert!(client.get_mirrors(&store_id).await.unwrap()[0].ours);
+    let requests = requests.lock().await;
+    assert_eq!(
+        requests[0],
+        (
+            "create_data_store".to_string(),
+            json!({"fee": 5, "verbose": false})
+        )
+    );
+    assert_eq!(
+        requests[1].1,
+        json!({"id": store_id, "changelist": [{"action": "insert", "key": "0x01", "value": "0x02"}]})
+    );
+    assert_eq!(requests[2].1, json!({"id": store_id, "key": "0x01"}));
+}
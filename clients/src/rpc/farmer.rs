@@ -0,0 +1,283 @@
+use crate::api::farmer::{FarmerAPI, PlotPageRequest};
+use crate::api::responses::{
+    HarvestersResp, HarvestersSummaryResp, PaginatedPlotsResp, PoolStateResp, RewardTargetsResp,
+    SignagePointProofsAryResp, SignagePointProofsResp, SuccessResp,
+};
+use crate::rpc::{check_success, get_client, get_url, post};
+use crate::ClientSSLConfig;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::harvester_info::{HarvesterInfo, HarvesterSummary};
+use dg_xch_core::blockchain::paginated_plots::{PaginatedPlots, PlotFilter};
+use dg_xch_core::blockchain::pool_state_info::PoolStateInfo;
+use dg_xch_core::blockchain::reward_targets::RewardTargets;
+use dg_xch_core::blockchain::signage_point_proofs::SignagePointProofs;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::protocols::harvester::Plot;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::io::Error;
+
+pub struct FarmerClient {
+    client: Client,
+    pub host: String,
+    pub port: u16,
+    pub ssl_path: Option<ClientSSLConfig>,
+    pub additional_headers: Option<HashMap<String, String>>,
+}
+
+impl FarmerClient {
+    pub fn new(
+        host: &str,
+        port: u16,
+        timeout: u64,
+        ssl_path: Option<ClientSSLConfig>,
+        additional_headers: &Option<HashMap<String, String>>,
+    ) -> Self {
+        FarmerClient {
+            client: get_client(ssl_path.clone(), timeout).unwrap(),
+            host: host.to_string(),
+            port,
+            ssl_path,
+            additional_headers: additional_headers.clone(),
+        }
+    }
+
+    async fn post_empty(
+        &self,
+        endpoint: &str,
+        request_body: &Map<String, Value>,
+    ) -> Result<(), Error> {
+        let url = get_url(self.host.as_str(), self.port, endpoint);
+        let resp =
+            post::<SuccessResp>(&self.client, &url, request_body, &self.additional_headers).await?;
+        check_success(resp, &url)
+    }
+
+    async fn get_plot_page<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        page: &PlotPageRequest,
+        filter: Value,
+        sort_key: Option<String>,
+    ) -> Result<PaginatedPlots<T>, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("node_id".to_string(), json!(page.node_id));
+        request_body.insert("page".to_string(), json!(page.page));
+        request_body.insert("page_size".to_string(), json!(page.page_size));
+        request_body.insert("filter".to_string(), filter);
+        request_body.insert("reverse".to_string(), json!(page.reverse));
+        if let Some(sort_key) = sort_key {
+            request_body.insert("sort_key".to_string(), json!(sort_key));
+        }
+        let resp = post::<PaginatedPlotsResp<T>>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, endpoint),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok(PaginatedPlots {
+            node_id: resp.node_id,
+            page: resp.page,
+            page_count: resp.page_count,
+            total_count: resp.total_count,
+            plots: resp.plots,
+        })
+    }
+}
+
+#[async_trait]
+impl FarmerAPI for FarmerClient {
+    async fn get_signage_point(&self, sp_hash: &Bytes32) -> Result<SignagePointProofs, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("sp_hash".to_string(), json!(sp_hash));
+        let resp = post::<SignagePointProofsResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_signage_point"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok(SignagePointProofs {
+            signage_point: resp.signage_point,
+            proofs: resp.proofs,
+        })
+    }
+    async fn get_signage_points(&self) -> Result<Vec<SignagePointProofs>, Error> {
+        Ok(post::<SignagePointProofsAryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_signage_points"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .signage_points)
+    }
+    async fn get_reward_targets(
+        &self,
+        search_for_private_key: bool,
+        max_ph_to_search: Option<u32>,
+    ) -> Result<RewardTargets, Error> {
+        let mut request_body = Map::new();
+        request_body.insert(
+            "search_for_private_key".to_string(),
+            json!(search_for_private_key),
+        );
+        if let Some(max_ph_to_search) = max_ph_to_search {
+            request_body.insert("max_ph_to_search".to_string(), json!(max_ph_to_search));
+        }
+        let resp = post::<RewardTargetsResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_reward_targets"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok(RewardTargets {
+            farmer_target: resp.farmer_target,
+            pool_target: resp.pool_target,
+            have_farmer_sk: resp.have_farmer_sk,
+            have_pool_sk: resp.have_pool_sk,
+        })
+    }
+    async fn set_reward_targets(
+        &self,
+        farmer_target: Option<String>,
+        pool_target: Option<String>,
+    ) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        if let Some(farmer_target) = farmer_target {
+            request_body.insert("farmer_target".to_string(), json!(farmer_target));
+        }
+        if let Some(pool_target) = pool_target {
+            request_body.insert("pool_target".to_string(), json!(pool_target));
+        }
+        self.post_empty("set_reward_targets", &request_body).await
+    }
+    async fn get_pool_state(&self) -> Result<Vec<PoolStateInfo>, Error> {
+        Ok(post::<PoolStateResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_pool_state"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .pool_state)
+    }
+    async fn set_payout_instructions(
+        &self,
+        launcher_id: &Bytes32,
+        payout_instructions: String,
+    ) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("launcher_id".to_string(), json!(launcher_id));
+        request_body.insert(
+            "payout_instructions".to_string(),
+            json!(payout_instructions),
+        );
+        self.post_empty("set_payout_instructions", &request_body)
+            .await
+    }
+    async fn get_harvesters(&self) -> Result<Vec<HarvesterInfo>, Error> {
+        Ok(post::<HarvestersResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_harvesters"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .harvesters)
+    }
+    async fn get_harvesters_summary(&self) -> Result<Vec<HarvesterSummary>, Error> {
+        Ok(post::<HarvestersSummaryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_harvesters_summary"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .harvesters)
+    }
+    async fn get_harvester_plots_valid(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[PlotFilter],
+        sort_key: Option<String>,
+    ) -> Result<PaginatedPlots<Plot>, Error> {
+        self.get_plot_page("get_harvester_plots_valid", page, json!(filter), sort_key)
+            .await
+    }
+    async fn get_harvester_plots_invalid(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[String],
+    ) -> Result<PaginatedPlots<String>, Error> {
+        self.get_plot_page("get_harvester_plots_invalid", page, json!(filter), None)
+            .await
+    }
+    async fn get_harvester_plots_keys_missing(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[String],
+    ) -> Result<PaginatedPlots<String>, Error> {
+        self.get_plot_page(
+            "get_harvester_plots_keys_missing",
+            page,
+            json!(filter),
+            None,
+        )
+        .await
+    }
+    async fn get_harvester_plots_duplicates(
+        &self,
+        page: &PlotPageRequest,
+        filter: &[String],
+    ) -> Result<PaginatedPlots<String>, Error> {
+        self.get_plot_page("get_harvester_plots_duplicates", page, json!(filter), None)
+            .await
+    }
+}
+
+#[test]
+fn test_farmer_responses() {
+    let harvesters: HarvestersResp = serde_json::from_str(
+        r#"{"harvesters": [{
+            "connection": {"node_id": "0x7f3a6c0e4b1d4f6a9a2e8c5d3b1f0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a39", "host": "10.0.0.2", "port": 8448},
+            "plots": [{
+                "filename": "/plots/plot-k32.plot", "size": 32,
+                "plot_id": "0x7f3a6c0e4b1d4f6a9a2e8c5d3b1f0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a39",
+                "pool_public_key": null,
+                "pool_contract_puzzle_hash": "0x7f3a6c0e4b1d4f6a9a2e8c5d3b1f0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a39",
+                "plot_public_key": "0xa572f2a0d3a7b1c4e5d6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c",
+                "file_size": 108834854912, "time_modified": 1700000000, "compression_level": 0
+            }],
+            "failed_to_open_filenames": [], "no_key_filenames": [], "duplicates": [],
+            "total_plot_size": 108834854912, "total_effective_plot_size": 108834854912,
+            "syncing": null, "last_sync_time": 1700000000.5, "harvesting_mode": 1
+        }], "success": true}"#,
+    )
+    .unwrap();
+    assert_eq!(harvesters.harvesters[0].plots[0].size, 32);
+    assert_eq!(harvesters.harvesters[0].connection.port, 8448);
+    let pool_state: PoolStateResp = serde_json::from_str(
+        r#"{"pool_state": [{
+            "p2_singleton_puzzle_hash": "0x7f3a6c0e4b1d4f6a9a2e8c5d3b1f0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a39",
+            "points_found_since_start": 10, "points_found_24h": [[1700000000.0, 10]],
+            "points_acknowledged_since_start": 10, "points_acknowledged_24h": [[1700000000.0, 10]],
+            "next_farmer_update": 1700000300.0, "next_pool_info_update": 1700003600.0,
+            "current_points": 10, "current_difficulty": 1,
+            "pool_errors_24h": [[1700000000.0, {"error_code": 6, "error_message": "Too late"}]],
+            "authentication_token_timeout": 5,
+            "pool_config": {"launcher_id": "0x7f3a6c0e4b1d4f6a9a2e8c5d3b1f0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a39", "pool_url": "https://pool.example"},
+            "plot_count": 1
+        }], "success": true}"#,
+    )
+    .unwrap();
+    assert_eq!(pool_state.pool_state[0].current_difficulty, Some(1));
+    assert_eq!(pool_state.pool_state[0].pool_errors_24h.len(), 1);
+    let resp: SuccessResp =
+        serde_json::from_str(r#"{"success": false, "error": "Invalid launcher id"}"#).unwrap();
+    assert!(check_success(resp, "set_payout_instructions").is_err());
+}
@@ -732,7 +732,7 @@ impl FullnodeExtAPI for FullnodeClient {
 #[tokio::test]
 async fn test_extended_functions() {
     let fnc = FullnodeClient::new("localhost", 8555, 10, None, &None);
-    let by_puz = fnc
+    let _by_puz = fnc
         .get_coin_records_by_puzzle_hashes_paginated(
             &[Bytes32::from(
                 "1c69feee1fb42ffa6c60fcc222c3aa8fb6cc719937a83f5aa068dc7045e0a633",
@@ -740,7 +740,7 @@ async fn test_extended_functions() {
             None,
             None,
             None,
-            None,
+            50,
             None,
         )
         .await
@@ -777,13 +777,16 @@ async fn test_extended_functions() {
             Some(true),
             Some(4540000),
             Some(4542825),
-            Some(2),
+            2,
             None,
         )
         .await
         .unwrap();
     assert!(!by_puz.0.is_empty());
-    assert!(by_puz.0.iter().all(|v| by_hints.contains(v)));
+    assert!(by_puz
+        .0
+        .iter()
+        .all(|v| by_hints.iter().any(|h| h.coin == v.coin)));
     assert!(!fnc
         .get_puzzles_and_solutions_by_names(&coin_ids, Some(true), Some(4540000), Some(4542825))
         .await
@@ -0,0 +1,94 @@
+use crate::api::harvester::HarvesterAPI;
+use crate::api::responses::{PlotDirectoriesResp, PlotsResp, SuccessResp};
+use crate::rpc::{check_success, get_client, get_url, post};
+use crate::ClientSSLConfig;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::harvester_info::HarvesterPlots;
+use reqwest::Client;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::io::Error;
+
+pub struct HarvesterClient {
+    client: Client,
+    pub host: String,
+    pub port: u16,
+    pub ssl_path: Option<ClientSSLConfig>,
+    pub additional_headers: Option<HashMap<String, String>>,
+}
+
+impl HarvesterClient {
+    pub fn new(
+        host: &str,
+        port: u16,
+        timeout: u64,
+        ssl_path: Option<ClientSSLConfig>,
+        additional_headers: &Option<HashMap<String, String>>,
+    ) -> Self {
+        HarvesterClient {
+            client: get_client(ssl_path.clone(), timeout).unwrap(),
+            host: host.to_string(),
+            port,
+            ssl_path,
+            additional_headers: additional_headers.clone(),
+        }
+    }
+
+    async fn post_empty(
+        &self,
+        endpoint: &str,
+        request_body: &Map<String, Value>,
+    ) -> Result<(), Error> {
+        let url = get_url(self.host.as_str(), self.port, endpoint);
+        let resp =
+            post::<SuccessResp>(&self.client, &url, request_body, &self.additional_headers).await?;
+        check_success(resp, &url)
+    }
+}
+
+#[async_trait]
+impl HarvesterAPI for HarvesterClient {
+    async fn get_plots(&self) -> Result<HarvesterPlots, Error> {
+        let resp = post::<PlotsResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_plots"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?;
+        Ok(HarvesterPlots {
+            plots: resp.plots,
+            failed_to_open_filenames: resp.failed_to_open_filenames,
+            not_found_filenames: resp.not_found_filenames,
+        })
+    }
+    async fn refresh_plots(&self) -> Result<(), Error> {
+        self.post_empty("refresh_plots", &Map::new()).await
+    }
+    async fn delete_plot(&self, filename: &str) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("filename".to_string(), json!(filename));
+        self.post_empty("delete_plot", &request_body).await
+    }
+    async fn get_plot_directories(&self) -> Result<Vec<String>, Error> {
+        Ok(post::<PlotDirectoriesResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_plot_directories"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .directories)
+    }
+    async fn add_plot_directory(&self, dirname: &str) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("dirname".to_string(), json!(dirname));
+        self.post_empty("add_plot_directory", &request_body).await
+    }
+    async fn remove_plot_directory(&self, dirname: &str) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("dirname".to_string(), json!(dirname));
+        self.post_empty("remove_plot_directory", &request_body)
+            .await
+    }
+}
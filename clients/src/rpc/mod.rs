@@ -1,6 +1,15 @@
+pub mod balanced_full_node;
+pub mod cached_full_node;
+pub mod crawler;
+pub mod data_layer;
+pub mod farmer;
 pub mod full_node;
+pub mod harvester;
+pub mod pagination;
+pub mod timelord;
 pub mod wallet;
 
+use crate::api::responses::SuccessResp;
 use crate::ClientSSLConfig;
 use dg_xch_core::protocols::shared::NoCertificateVerification;
 use dg_xch_core::ssl::{
@@ -10,6 +19,7 @@ use dg_xch_core::ssl::{
 use reqwest::{Client, ClientBuilder};
 use rustls::ClientConfig;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::{Map, Value};
 use std::cmp::min;
 use std::collections::HashMap;
@@ -106,23 +116,144 @@ where
                 let body = resp
                     .text()
                     .await
-                    .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+                    .map_err(|e| RpcError::error(request_error_class(&e), e.to_string()))?;
                 serde_json::from_str(body.as_str()).map_err(|e| {
-                    Error::new(
-                        ErrorKind::InvalidData,
-                        format!(
-                            "Failed to Parse Json {},\r\n {}",
-                            &body[0..min(body.len(), 1024)],
-                            e
+                    //Failed calls come back as {success: false, error: ..} instead of the payload
+                    match serde_json::from_str::<SuccessResp>(body.as_str()) {
+                        Ok(SuccessResp {
+                            success: false,
+                            error,
+                        }) => RpcError::error(
+                            RpcErrorClass::Rpc,
+                            format!("Request to {url} failed: {}", error.unwrap_or_default()),
                         ),
-                    )
+                        _ => RpcError::error(
+                            RpcErrorClass::Deserialization,
+                            format!(
+                                "Failed to Parse Json {},\r\n {}",
+                                &body[0..min(body.len(), 1024)],
+                                e
+                            ),
+                        ),
+                    }
                 })
             }
-            _ => Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("Bad Status Code: {:?}, for URL {:?}", resp.status(), url),
+            status => Err(RpcError::error(
+                if status.is_server_error() {
+                    RpcErrorClass::Connection
+                } else {
+                    RpcErrorClass::Rpc
+                },
+                format!("Bad Status Code: {:?}, for URL {:?}", status, url),
             )),
         },
-        Err(err) => Err(Error::new(ErrorKind::InvalidData, format!("{:?}", err))),
+        Err(err) => Err(RpcError::error(
+            request_error_class(&err),
+            format!("{:?}", err),
+        )),
+    }
+}
+
+fn request_error_class(err: &reqwest::Error) -> RpcErrorClass {
+    if err.is_timeout() {
+        RpcErrorClass::Timeout
+    } else if err.is_connect() {
+        RpcErrorClass::Refused
+    } else if err.is_request() || err.is_body() {
+        RpcErrorClass::Connection
+    } else {
+        RpcErrorClass::Other
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RpcErrorClass {
+    //The connection could not be opened so the request was never sent
+    Refused,
+    //The node dropped the request or failed with a server error
+    Connection,
+    Timeout,
+    //The node answered with success: false or rejected the request
+    Rpc,
+    Deserialization,
+    Other,
+}
+impl RpcErrorClass {
+    //Errors from the rpc clients carry an RpcError, anything else is classified by its kind
+    pub fn of(error: &Error) -> Self {
+        if let Some(rpc_error) = error.get_ref().and_then(|e| e.downcast_ref::<RpcError>()) {
+            return rpc_error.class;
+        }
+        match error.kind() {
+            ErrorKind::ConnectionRefused => RpcErrorClass::Refused,
+            ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof => RpcErrorClass::Connection,
+            ErrorKind::TimedOut => RpcErrorClass::Timeout,
+            ErrorKind::InvalidInput => RpcErrorClass::Rpc,
+            ErrorKind::InvalidData => RpcErrorClass::Deserialization,
+            _ => RpcErrorClass::Other,
+        }
+    }
+
+    //Another node may succeed where this one failed, Rpc and Deserialization errors would repeat
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            RpcErrorClass::Refused | RpcErrorClass::Connection | RpcErrorClass::Timeout
+        )
+    }
+}
+
+//Carried inside the InvalidData errors of the rpc clients so callers can tell failures apart
+#[derive(Debug)]
+pub struct RpcError {
+    pub class: RpcErrorClass,
+    pub message: String,
+}
+impl RpcError {
+    pub fn error(class: RpcErrorClass, message: impl Into<String>) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            RpcError {
+                class,
+                message: message.into(),
+            },
+        )
+    }
+}
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+impl std::error::Error for RpcError {}
+
+//Endpoints without a payload only report success, failures carry an error message instead
+pub(crate) fn check_success(resp: SuccessResp, url: &str) -> Result<(), Error> {
+    if resp.success {
+        Ok(())
+    } else {
+        Err(RpcError::error(
+            RpcErrorClass::Rpc,
+            format!(
+                "Request to {url} failed: {}",
+                resp.error.unwrap_or_default()
+            ),
+        ))
+    }
+}
+
+//Typed request structs are sent as the same json object the Map based helpers build
+pub(crate) fn to_request_body<T: Serialize>(request: &T) -> Result<Map<String, Value>, Error> {
+    match serde_json::to_value(request) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(other) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Request is not a json object: {other}"),
+        )),
+        Err(e) => Err(Error::new(ErrorKind::InvalidInput, e.to_string())),
     }
 }
@@ -0,0 +1,269 @@
+use crate::api::full_node::{FullnodeAPI, FullnodeExtAPI};
+use dg_xch_core::blockchain::block_record::BlockRecord;
+use dg_xch_core::blockchain::coin_record::PaginatedCoinRecord;
+use dg_xch_core::blockchain::full_block::FullBlock;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, Stream, StreamExt};
+use std::io::Error;
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChunkOptions {
+    //Heights requested per call
+    pub chunk_size: u32,
+    //Calls in flight at once, results are still yielded in height order
+    pub concurrency: usize,
+}
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 100,
+            concurrency: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinRecordQuery {
+    pub include_spent_coins: Option<bool>,
+    pub start_height: Option<u32>,
+    pub end_height: Option<u32>,
+    pub page_size: u32,
+}
+impl Default for CoinRecordQuery {
+    fn default() -> Self {
+        Self {
+            include_spent_coins: None,
+            start_height: None,
+            end_height: None,
+            page_size: 1000,
+        }
+    }
+}
+
+//A page of records and the cursor that follows it, storing last_id is enough to resume later
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinRecordPage {
+    pub records: Vec<PaginatedCoinRecord>,
+    pub last_id: Option<Bytes32>,
+    pub total_count: Option<i32>,
+}
+
+fn chunks(range: Range<u32>, chunk_size: u32) -> impl Iterator<Item = Range<u32>> {
+    let chunk_size = chunk_size.max(1);
+    let end = range.end;
+    range
+        .step_by(chunk_size as usize)
+        .map(move |start| start..start.saturating_add(chunk_size).min(end))
+}
+
+//Runs one request per chunk with bounded concurrency, flattening the results in order and
+//stopping after the first error
+fn chunked_stream<'a, V, F>(
+    range: Range<u32>,
+    options: ChunkOptions,
+    request: F,
+) -> impl Stream<Item = Result<V, Error>> + 'a
+where
+    V: Send + 'a,
+    F: Fn(Range<u32>) -> BoxFuture<'a, Result<Vec<V>, Error>> + 'a,
+{
+    stream::iter(chunks(range, options.chunk_size))
+        .map(request)
+        .buffered(options.concurrency.max(1))
+        .scan(false, |failed, result| {
+            let items = if *failed {
+                None
+            } else {
+                match result {
+                    Ok(items) => Some(items.into_iter().map(Ok).collect::<Vec<_>>()),
+                    Err(e) => {
+                        *failed = true;
+                        Some(vec![Err(e)])
+                    }
+                }
+            };
+            async move { items }
+        })
+        .flat_map(stream::iter)
+}
+
+//Streams full blocks for heights in range, resume by starting after the last processed height
+pub fn stream_blocks<'a, T: FullnodeAPI + Sync>(
+    client: &'a T,
+    range: Range<u32>,
+    options: ChunkOptions,
+) -> impl Stream<Item = Result<FullBlock, Error>> + 'a {
+    chunked_stream(range, options, move |chunk| {
+        client.get_all_blocks(chunk.start, chunk.end)
+    })
+}
+
+pub fn stream_block_records<'a, T: FullnodeAPI + Sync>(
+    client: &'a T,
+    range: Range<u32>,
+    options: ChunkOptions,
+) -> impl Stream<Item = Result<BlockRecord, Error>> + 'a {
+    chunked_stream(range, options, move |chunk| {
+        client.get_block_records(chunk.start, chunk.end)
+    })
+}
+
+struct CursorState<'a, T> {
+    client: &'a T,
+    keys: Vec<Bytes32>,
+    query: CoinRecordQuery,
+    last_id: Option<Bytes32>,
+    done: bool,
+}
+
+//Follows last_id cursors until the node stops returning one, resume_from is a last_id from a
+//previously yielded page
+fn cursor_stream<'a, T, F>(
+    state: CursorState<'a, T>,
+    request: F,
+) -> impl Stream<Item = Result<CoinRecordPage, Error>> + 'a
+where
+    T: Sync + 'a,
+    F: for<'b> Fn(
+            &'b CursorState<'a, T>,
+        ) -> BoxFuture<
+            'b,
+            Result<(Vec<PaginatedCoinRecord>, Option<Bytes32>, Option<i32>), Error>,
+        > + Copy
+        + Send
+        + 'a,
+{
+    stream::unfold(state, move |mut state| async move {
+        if state.done {
+            return None;
+        }
+        match request(&state).await {
+            Ok((records, last_id, total_count)) => {
+                //A repeated or missing cursor means the node has nothing after this page
+                state.done = records.is_empty() || last_id.is_none() || last_id == state.last_id;
+                if records.is_empty() && state.done {
+                    return None;
+                }
+                state.last_id = last_id;
+                Some((
+                    Ok(CoinRecordPage {
+                        records,
+                        last_id,
+                        total_count,
+                    }),
+                    state,
+                ))
+            }
+            Err(e) => {
+                state.done = true;
+                Some((Err(e), state))
+            }
+        }
+    })
+}
+
+pub fn stream_coin_records_by_puzzle_hashes<'a, T: FullnodeExtAPI + Sync>(
+    client: &'a T,
+    puzzle_hashes: Vec<Bytes32>,
+    query: CoinRecordQuery,
+    resume_from: Option<Bytes32>,
+) -> impl Stream<Item = Result<CoinRecordPage, Error>> + 'a {
+    let state = CursorState {
+        client,
+        keys: puzzle_hashes,
+        query,
+        last_id: resume_from,
+        done: false,
+    };
+    cursor_stream(state, |state| {
+        state.client.get_coin_records_by_puzzle_hashes_paginated(
+            &state.keys,
+            state.query.include_spent_coins,
+            state.query.start_height,
+            state.query.end_height,
+            state.query.page_size,
+            state.last_id,
+        )
+    })
+}
+
+pub fn stream_coin_records_by_hints<'a, T: FullnodeExtAPI + Sync>(
+    client: &'a T,
+    hints: Vec<Bytes32>,
+    query: CoinRecordQuery,
+    resume_from: Option<Bytes32>,
+) -> impl Stream<Item = Result<CoinRecordPage, Error>> + 'a {
+    let state = CursorState {
+        client,
+        keys: hints,
+        query,
+        last_id: resume_from,
+        done: false,
+    };
+    cursor_stream(state, |state| {
+        state.client.get_coin_records_by_hints_paginated(
+            &state.keys,
+            state.query.include_spent_coins,
+            state.query.start_height,
+            state.query.end_height,
+            state.query.page_size,
+            state.last_id,
+        )
+    })
+}
+
+#[tokio::test]
+async fn test_pagination_streams() {
+    use std::io::ErrorKind;
+    assert_eq!(
+        chunks(10..35, 10).collect::<Vec<_>>(),
+        vec![10..20, 20..30, 30..35]
+    );
+    assert_eq!(chunks(5..5, 10).count(), 0);
+    let options = ChunkOptions {
+        chunk_size: 3,
+        concurrency: 2,
+    };
+    let heights: Vec<u32> = chunked_stream(0..10, options, |chunk| {
+        Box::pin(async move {
+            //Later chunks finish first, output order must not change
+            tokio::time::sleep(std::time::Duration::from_millis(10 - chunk.start as u64)).await;
+            Ok(chunk.collect::<Vec<u32>>())
+        })
+    })
+    .map(|r| r.unwrap())
+    .collect()
+    .await;
+    assert_eq!(heights, (0..10).collect::<Vec<u32>>());
+    let results: Vec<Result<u32, Error>> = chunked_stream(0..10, options, |chunk| {
+        Box::pin(async move {
+            if chunk.start == 3 {
+                Err(Error::new(ErrorKind::TimedOut, "timed out"))
+            } else {
+                Ok(chunk.collect::<Vec<u32>>())
+            }
+        })
+    })
+    .collect()
+    .await;
+    assert_eq!(results.len(), 4);
+    assert!(results[3].is_err());
+    //Nothing listens on this port, the cursor stream yields the error once and ends
+    let client = crate::rpc::full_node::FullnodeClient::new("127.0.0.1", 1, 2, None, &None);
+    let pages: Vec<_> = stream_coin_records_by_hints(
+        &client,
+        vec![Bytes32::default()],
+        CoinRecordQuery::default(),
+        None,
+    )
+    .collect()
+    .await;
+    assert_eq!(pages.len(), 1);
+    assert!(pages[0].is_err());
+    let blocks: Vec<_> = stream_blocks(&client, 0..10, ChunkOptions::default())
+        .collect()
+        .await;
+    assert_eq!(blocks.len(), 1);
+}
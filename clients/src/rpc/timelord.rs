@@ -0,0 +1,80 @@
+use crate::api::responses::{ConnectionsResp, RoutesResp, SuccessResp, VersionResp};
+use crate::api::timelord::TimelordAPI;
+use crate::rpc::{get_client, get_url, post};
+use crate::ClientSSLConfig;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::connection_info::ConnectionInfo;
+use reqwest::Client;
+use serde_json::Map;
+use std::collections::HashMap;
+use std::io::Error;
+
+pub struct TimelordClient {
+    client: Client,
+    pub host: String,
+    pub port: u16,
+    pub ssl_path: Option<ClientSSLConfig>,
+    pub additional_headers: Option<HashMap<String, String>>,
+}
+
+impl TimelordClient {
+    pub fn new(
+        host: &str,
+        port: u16,
+        timeout: u64,
+        ssl_path: Option<ClientSSLConfig>,
+        additional_headers: &Option<HashMap<String, String>>,
+    ) -> Self {
+        TimelordClient {
+            client: get_client(ssl_path.clone(), timeout).unwrap(),
+            host: host.to_string(),
+            port,
+            ssl_path,
+            additional_headers: additional_headers.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl TimelordAPI for TimelordClient {
+    async fn healthz(&self) -> Result<bool, Error> {
+        Ok(post::<SuccessResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "healthz"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .success)
+    }
+    async fn get_connections(&self) -> Result<Vec<ConnectionInfo>, Error> {
+        Ok(post::<ConnectionsResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_connections"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .connections)
+    }
+    async fn get_routes(&self) -> Result<Vec<String>, Error> {
+        Ok(post::<RoutesResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_routes"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .routes)
+    }
+    async fn get_version(&self) -> Result<String, Error> {
+        Ok(post::<VersionResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_version"),
+            &Map::new(),
+            &self.additional_headers,
+        )
+        .await?
+        .version)
+    }
+}
@@ -2,21 +2,32 @@ use crate::api::wallet::WalletAPI;
 use async_trait::async_trait;
 use dg_xch_core::blockchain::announcement::Announcement;
 use dg_xch_core::blockchain::coin::Coin;
+use dg_xch_core::blockchain::nft_info::NftInfo;
+use dg_xch_core::blockchain::notification::Notification;
 use dg_xch_core::blockchain::pending_payment::PendingPayment;
+use dg_xch_core::blockchain::pool_wallet_info::{PoolWalletInfo, PoolWalletTransaction};
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48, Bytes96};
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::blockchain::trade_record::{OfferSummary, TradeRecord};
 use dg_xch_core::blockchain::transaction_record::TransactionRecord;
 use dg_xch_core::blockchain::wallet_balance::WalletBalance;
 use dg_xch_core::blockchain::wallet_info::WalletInfo;
 use dg_xch_core::blockchain::wallet_sync::WalletSync;
 use reqwest::Client;
-use serde_json::{json, Map};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 
 use crate::api::responses::{
-    LoginResp, SignedTransactionRecordResp, TransactionRecordResp, WalletBalanceResp,
-    WalletInfoResp, WalletSyncResp,
+    CatAssetIdResp, CoinAryResp, CountResp, CreateWalletResp, DidMetadataResp, DidResp, LoginResp,
+    NextAddressResp, NftInfoResp, NftListResp, NftMintReq, NftMintResp, NotificationTXResp,
+    NotificationsResp, OfferResp, OfferSummaryResp, OfferValidityResp, PoolWalletStatusResp,
+    PoolWalletTransactionResp, SignMessageResp, SignedMessage, SignedTransactionRecordResp,
+    SpendBundleResp, SpendableCoins, SpendableCoinsResp, SuccessResp, TradeRecordAryResp,
+    TradeRecordResp, TransactionRecordAryResp, TransactionRecordResp, VerifySignatureResp,
+    WalletBalanceResp, WalletInfoResp, WalletNameResp, WalletSyncResp,
 };
-use crate::rpc::{get_client, get_url, post};
+use crate::rpc::{check_success, get_client, get_url, post, to_request_body};
 use crate::ClientSSLConfig;
 
 pub struct WalletClient {
@@ -40,6 +51,17 @@ impl WalletClient {
             additional_headers,
         }
     }
+
+    async fn post_empty(
+        &self,
+        endpoint: &str,
+        request_body: &Map<String, Value>,
+    ) -> Result<(), Error> {
+        let url = get_url(self.host.as_str(), self.port, endpoint);
+        let resp =
+            post::<SuccessResp>(&self.client, &url, request_body, &self.additional_headers).await?;
+        check_success(resp, &url)
+    }
 }
 #[async_trait]
 impl WalletAPI for WalletClient {
@@ -188,4 +210,780 @@ impl WalletAPI for WalletClient {
         .await?
         .signed_tx)
     }
+    async fn get_transactions(
+        &self,
+        wallet_id: u32,
+        start: Option<u32>,
+        end: Option<u32>,
+        reverse: Option<bool>,
+    ) -> Result<Vec<TransactionRecord>, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        if let Some(start) = start {
+            request_body.insert("start".to_string(), json!(start));
+        }
+        if let Some(end) = end {
+            request_body.insert("end".to_string(), json!(end));
+        }
+        if let Some(reverse) = reverse {
+            request_body.insert("reverse".to_string(), json!(reverse));
+        }
+        Ok(post::<TransactionRecordAryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_transactions"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transactions)
+    }
+    async fn get_transaction_count(&self, wallet_id: u32) -> Result<u64, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        Ok(post::<CountResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_transaction_count"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .count)
+    }
+    async fn get_next_address(&self, wallet_id: u32, new_address: bool) -> Result<String, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("new_address".to_string(), json!(new_address));
+        Ok(post::<NextAddressResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_next_address"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .address)
+    }
+    async fn select_coins(
+        &self,
+        wallet_id: u32,
+        amount: u64,
+        excluded_coin_ids: Vec<Bytes32>,
+    ) -> Result<Vec<Coin>, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("amount".to_string(), json!(amount));
+        request_body.insert("excluded_coin_ids".to_string(), json!(excluded_coin_ids));
+        Ok(post::<CoinAryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "select_coins"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .coins)
+    }
+    async fn get_spendable_coins(
+        &self,
+        wallet_id: u32,
+        min_coin_amount: Option<u64>,
+        max_coin_amount: Option<u64>,
+    ) -> Result<SpendableCoins, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        if let Some(min_coin_amount) = min_coin_amount {
+            request_body.insert("min_coin_amount".to_string(), json!(min_coin_amount));
+        }
+        if let Some(max_coin_amount) = max_coin_amount {
+            request_body.insert("max_coin_amount".to_string(), json!(max_coin_amount));
+        }
+        Ok(post::<SpendableCoinsResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_spendable_coins"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .coins)
+    }
+    async fn combine_coins(
+        &self,
+        wallet_id: u32,
+        number_of_coins: u32,
+        target_coin_ids: Vec<Bytes32>,
+        fee: u64,
+    ) -> Result<Vec<TransactionRecord>, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("number_of_coins".to_string(), json!(number_of_coins));
+        request_body.insert("target_coin_ids".to_string(), json!(target_coin_ids));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<TransactionRecordAryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "combine_coins"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transactions)
+    }
+    async fn split_coins(
+        &self,
+        wallet_id: u32,
+        number_of_coins: u32,
+        amount_per_coin: u64,
+        target_coin_id: &Bytes32,
+        fee: u64,
+    ) -> Result<Vec<TransactionRecord>, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("number_of_coins".to_string(), json!(number_of_coins));
+        request_body.insert("amount_per_coin".to_string(), json!(amount_per_coin));
+        request_body.insert("target_coin_id".to_string(), json!(target_coin_id));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<TransactionRecordAryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "split_coins"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transactions)
+    }
+    async fn create_offer_for_ids(
+        &self,
+        offer: HashMap<String, i64>,
+        fee: u64,
+        validate_only: bool,
+    ) -> Result<(String, TradeRecord), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("offer".to_string(), json!(offer));
+        request_body.insert("fee".to_string(), json!(fee));
+        request_body.insert("validate_only".to_string(), json!(validate_only));
+        let resp = post::<OfferResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "create_offer_for_ids"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok((resp.offer, resp.trade_record))
+    }
+    async fn take_offer(&self, offer: &str, fee: u64) -> Result<TradeRecord, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("offer".to_string(), json!(offer));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<TradeRecordResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "take_offer"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .trade_record)
+    }
+    async fn cancel_offer(&self, trade_id: &Bytes32, secure: bool, fee: u64) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("trade_id".to_string(), json!(trade_id));
+        request_body.insert("secure".to_string(), json!(secure));
+        request_body.insert("fee".to_string(), json!(fee));
+        self.post_empty("cancel_offer", &request_body).await
+    }
+    async fn get_all_offers(
+        &self,
+        start: Option<u32>,
+        end: Option<u32>,
+        exclude_my_offers: bool,
+        exclude_taken_offers: bool,
+        include_completed: bool,
+    ) -> Result<Vec<TradeRecord>, Error> {
+        let mut request_body = Map::new();
+        if let Some(start) = start {
+            request_body.insert("start".to_string(), json!(start));
+        }
+        if let Some(end) = end {
+            request_body.insert("end".to_string(), json!(end));
+        }
+        request_body.insert("exclude_my_offers".to_string(), json!(exclude_my_offers));
+        request_body.insert(
+            "exclude_taken_offers".to_string(),
+            json!(exclude_taken_offers),
+        );
+        request_body.insert("include_completed".to_string(), json!(include_completed));
+        Ok(post::<TradeRecordAryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_all_offers"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .trade_records)
+    }
+    async fn get_offer_summary(&self, offer: &str) -> Result<OfferSummary, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("offer".to_string(), json!(offer));
+        Ok(post::<OfferSummaryResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_offer_summary"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .summary)
+    }
+    async fn check_offer_validity(&self, offer: &str) -> Result<bool, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("offer".to_string(), json!(offer));
+        Ok(post::<OfferValidityResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "check_offer_validity"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .valid)
+    }
+    async fn create_new_cat_wallet(
+        &self,
+        amount: u64,
+        fee: u64,
+        name: Option<String>,
+    ) -> Result<(u32, Bytes32), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_type".to_string(), json!("cat_wallet"));
+        request_body.insert("mode".to_string(), json!("new"));
+        request_body.insert("amount".to_string(), json!(amount));
+        request_body.insert("fee".to_string(), json!(fee));
+        if let Some(name) = name {
+            request_body.insert("name".to_string(), json!(name));
+        }
+        let url = get_url(self.host.as_str(), self.port, "create_new_wallet");
+        let resp =
+            post::<CreateWalletResp>(&self.client, &url, &request_body, &self.additional_headers)
+                .await?;
+        match resp.asset_id {
+            Some(asset_id) => Ok((resp.wallet_id, asset_id)),
+            None => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Missing asset_id in response from {url}"),
+            )),
+        }
+    }
+    async fn create_cat_wallet_for_asset(
+        &self,
+        asset_id: &Bytes32,
+        name: Option<String>,
+    ) -> Result<u32, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_type".to_string(), json!("cat_wallet"));
+        request_body.insert("mode".to_string(), json!("existing"));
+        //The wallet matches this against its default CAT list, which is keyed without a prefix
+        request_body.insert("asset_id".to_string(), json!(hex::encode(asset_id.bytes)));
+        if let Some(name) = name {
+            request_body.insert("name".to_string(), json!(name));
+        }
+        Ok(post::<CreateWalletResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "create_new_wallet"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .wallet_id)
+    }
+    async fn cat_get_name(&self, wallet_id: u32) -> Result<String, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        Ok(post::<WalletNameResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "cat_get_name"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .name
+        .unwrap_or_default())
+    }
+    async fn cat_set_name(&self, wallet_id: u32, name: &str) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("name".to_string(), json!(name));
+        self.post_empty("cat_set_name", &request_body).await
+    }
+    async fn cat_get_asset_id(&self, wallet_id: u32) -> Result<Bytes32, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        Ok(post::<CatAssetIdResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "cat_get_asset_id"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .asset_id)
+    }
+    async fn cat_asset_id_to_name(
+        &self,
+        asset_id: &Bytes32,
+    ) -> Result<(Option<u32>, Option<String>), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("asset_id".to_string(), json!(asset_id));
+        let resp = post::<WalletNameResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "cat_asset_id_to_name"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok((resp.wallet_id, resp.name))
+    }
+    async fn cat_spend(
+        &self,
+        wallet_id: u32,
+        inner_address: &str,
+        amount: u64,
+        fee: u64,
+        memos: Vec<String>,
+    ) -> Result<TransactionRecord, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("inner_address".to_string(), json!(inner_address));
+        request_body.insert("amount".to_string(), json!(amount));
+        request_body.insert("fee".to_string(), json!(fee));
+        request_body.insert("memos".to_string(), json!(memos));
+        Ok(post::<TransactionRecordResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "cat_spend"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transaction)
+    }
+    async fn nft_mint_nft(
+        &self,
+        request: &NftMintReq,
+    ) -> Result<(Option<String>, SpendBundle), Error> {
+        let resp = post::<NftMintResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "nft_mint_nft"),
+            &to_request_body(request)?,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok((resp.nft_id, resp.spend_bundle))
+    }
+    async fn nft_get_nfts(
+        &self,
+        wallet_id: u32,
+        start_index: Option<u32>,
+        num: Option<u32>,
+    ) -> Result<Vec<NftInfo>, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        if let Some(start_index) = start_index {
+            request_body.insert("start_index".to_string(), json!(start_index));
+        }
+        if let Some(num) = num {
+            request_body.insert("num".to_string(), json!(num));
+        }
+        Ok(post::<NftListResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "nft_get_nfts"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .nft_list)
+    }
+    async fn nft_get_info(&self, coin_id: &str) -> Result<NftInfo, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("coin_id".to_string(), json!(coin_id));
+        Ok(post::<NftInfoResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "nft_get_info"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .nft_info)
+    }
+    async fn nft_count_nfts(&self, wallet_id: u32) -> Result<u64, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        Ok(post::<CountResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "nft_count_nfts"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .count)
+    }
+    async fn nft_transfer_nft(
+        &self,
+        wallet_id: u32,
+        target_address: &str,
+        nft_coin_id: &str,
+        fee: u64,
+    ) -> Result<SpendBundle, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("target_address".to_string(), json!(target_address));
+        request_body.insert("nft_coin_id".to_string(), json!(nft_coin_id));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<SpendBundleResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "nft_transfer_nft"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .spend_bundle)
+    }
+    async fn nft_set_nft_did(
+        &self,
+        wallet_id: u32,
+        did_id: &str,
+        nft_coin_id: &str,
+        fee: u64,
+    ) -> Result<SpendBundle, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("did_id".to_string(), json!(did_id));
+        request_body.insert("nft_coin_id".to_string(), json!(nft_coin_id));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<SpendBundleResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "nft_set_nft_did"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .spend_bundle)
+    }
+    async fn create_new_did_wallet(
+        &self,
+        amount: u64,
+        fee: u64,
+        backup_dids: Vec<String>,
+        num_of_backup_ids_needed: u64,
+    ) -> Result<(u32, String), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_type".to_string(), json!("did_wallet"));
+        request_body.insert("did_type".to_string(), json!("new"));
+        request_body.insert("amount".to_string(), json!(amount));
+        request_body.insert("fee".to_string(), json!(fee));
+        request_body.insert("backup_dids".to_string(), json!(backup_dids));
+        request_body.insert(
+            "num_of_backup_ids_needed".to_string(),
+            json!(num_of_backup_ids_needed),
+        );
+        let url = get_url(self.host.as_str(), self.port, "create_new_wallet");
+        let resp =
+            post::<CreateWalletResp>(&self.client, &url, &request_body, &self.additional_headers)
+                .await?;
+        match resp.my_did {
+            Some(my_did) => Ok((resp.wallet_id, my_did)),
+            None => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Missing my_did in response from {url}"),
+            )),
+        }
+    }
+    async fn did_get_did(&self, wallet_id: u32) -> Result<(String, Option<Bytes32>), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        let resp = post::<DidResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "did_get_did"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok((resp.my_did, resp.coin_id))
+    }
+    async fn did_get_wallet_name(&self, wallet_id: u32) -> Result<String, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        Ok(post::<WalletNameResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "did_get_wallet_name"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .name
+        .unwrap_or_default())
+    }
+    async fn did_set_wallet_name(&self, wallet_id: u32, name: &str) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("name".to_string(), json!(name));
+        self.post_empty("did_set_wallet_name", &request_body).await
+    }
+    async fn did_get_metadata(&self, wallet_id: u32) -> Result<HashMap<String, String>, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        Ok(post::<DidMetadataResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "did_get_metadata"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .metadata)
+    }
+    async fn did_update_metadata(
+        &self,
+        wallet_id: u32,
+        metadata: HashMap<String, String>,
+        fee: u64,
+    ) -> Result<SpendBundle, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("metadata".to_string(), json!(metadata));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<SpendBundleResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "did_update_metadata"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .spend_bundle)
+    }
+    async fn did_transfer_did(
+        &self,
+        wallet_id: u32,
+        inner_address: &str,
+        fee: u64,
+        with_recovery_info: bool,
+    ) -> Result<TransactionRecord, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("inner_address".to_string(), json!(inner_address));
+        request_body.insert("fee".to_string(), json!(fee));
+        request_body.insert("with_recovery_info".to_string(), json!(with_recovery_info));
+        Ok(post::<TransactionRecordResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "did_transfer_did"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transaction)
+    }
+    async fn pw_join_pool(
+        &self,
+        wallet_id: u32,
+        target_puzzlehash: &Bytes32,
+        pool_url: &str,
+        relative_lock_height: u32,
+        fee: u64,
+    ) -> Result<PoolWalletTransaction, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("target_puzzlehash".to_string(), json!(target_puzzlehash));
+        request_body.insert("pool_url".to_string(), json!(pool_url));
+        request_body.insert(
+            "relative_lock_height".to_string(),
+            json!(relative_lock_height),
+        );
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<PoolWalletTransactionResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "pw_join_pool"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transaction)
+    }
+    async fn pw_self_pool(&self, wallet_id: u32, fee: u64) -> Result<PoolWalletTransaction, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<PoolWalletTransactionResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "pw_self_pool"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transaction)
+    }
+    async fn pw_absorb_rewards(
+        &self,
+        wallet_id: u32,
+        fee: u64,
+        max_spends_in_tx: Option<u32>,
+    ) -> Result<PoolWalletTransaction, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        request_body.insert("fee".to_string(), json!(fee));
+        if let Some(max_spends_in_tx) = max_spends_in_tx {
+            request_body.insert("max_spends_in_tx".to_string(), json!(max_spends_in_tx));
+        }
+        Ok(post::<PoolWalletTransactionResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "pw_absorb_rewards"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .transaction)
+    }
+    async fn pw_status(
+        &self,
+        wallet_id: u32,
+    ) -> Result<(PoolWalletInfo, Vec<TransactionRecord>), Error> {
+        let mut request_body = Map::new();
+        request_body.insert("wallet_id".to_string(), json!(wallet_id));
+        let resp = post::<PoolWalletStatusResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "pw_status"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?;
+        Ok((resp.state, resp.unconfirmed_transactions))
+    }
+    async fn sign_message_by_address(
+        &self,
+        address: &str,
+        message: &str,
+    ) -> Result<SignedMessage, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("address".to_string(), json!(address));
+        request_body.insert("message".to_string(), json!(message));
+        Ok(post::<SignMessageResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "sign_message_by_address"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .message)
+    }
+    async fn sign_message_by_id(&self, id: &str, message: &str) -> Result<SignedMessage, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("id".to_string(), json!(id));
+        request_body.insert("message".to_string(), json!(message));
+        Ok(post::<SignMessageResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "sign_message_by_id"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .message)
+    }
+    async fn verify_signature(
+        &self,
+        pubkey: &Bytes48,
+        message: &str,
+        signature: &Bytes96,
+        address: Option<String>,
+        signing_mode: Option<String>,
+    ) -> Result<bool, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("pubkey".to_string(), json!(pubkey));
+        request_body.insert("message".to_string(), json!(message));
+        request_body.insert("signature".to_string(), json!(signature));
+        if let Some(address) = address {
+            request_body.insert("address".to_string(), json!(address));
+        }
+        if let Some(signing_mode) = signing_mode {
+            request_body.insert("signing_mode".to_string(), json!(signing_mode));
+        }
+        Ok(post::<VerifySignatureResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "verify_signature"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .is_valid)
+    }
+    async fn get_notifications(
+        &self,
+        ids: Option<Vec<Bytes32>>,
+        start: Option<u32>,
+        end: Option<u32>,
+    ) -> Result<Vec<Notification>, Error> {
+        let mut request_body = Map::new();
+        if let Some(ids) = ids {
+            request_body.insert("ids".to_string(), json!(ids));
+        }
+        if let Some(start) = start {
+            request_body.insert("start".to_string(), json!(start));
+        }
+        if let Some(end) = end {
+            request_body.insert("end".to_string(), json!(end));
+        }
+        Ok(post::<NotificationsResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "get_notifications"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .notifications)
+    }
+    async fn delete_notifications(&self, ids: Option<Vec<Bytes32>>) -> Result<(), Error> {
+        let mut request_body = Map::new();
+        if let Some(ids) = ids {
+            request_body.insert("ids".to_string(), json!(ids));
+        }
+        self.post_empty("delete_notifications", &request_body).await
+    }
+    async fn send_notification(
+        &self,
+        target: &Bytes32,
+        message: &[u8],
+        amount: u64,
+        fee: u64,
+    ) -> Result<TransactionRecord, Error> {
+        let mut request_body = Map::new();
+        request_body.insert("target".to_string(), json!(target));
+        request_body.insert("message".to_string(), json!(hex::encode(message)));
+        request_body.insert("amount".to_string(), json!(amount));
+        request_body.insert("fee".to_string(), json!(fee));
+        Ok(post::<NotificationTXResp>(
+            &self.client,
+            &get_url(self.host.as_str(), self.port, "send_notification"),
+            &request_body,
+            &self.additional_headers,
+        )
+        .await?
+        .tx)
+    }
+}
+
+#[test]
+fn test_wallet_responses() {
+    let resp: TradeRecordResp = serde_json::from_str(
+        r#"{"trade_record": {"confirmed_at_index": 0, "accepted_at_time": null,
+            "created_at_time": 1700000000, "is_my_offer": true, "sent": 0, "sent_to": [],
+            "taken_offer": null, "coins_of_interest": [{"parent_coin_info":
+            "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "puzzle_hash": "0x0202020202020202020202020202020202020202020202020202020202020202",
+            "amount": 1000}], "trade_id":
+            "0x0303030303030303030303030303030303030303030303030303030303030303",
+            "status": "PENDING_ACCEPT", "summary": {"offered": {"xch": 1000},
+            "requested": {"a628c1c2c6fcb74d53746157e438e108eab5c0bb3e5c80ff9b1910b3e4832913": 5},
+            "fees": 0, "infos": {}}, "pending": {"xch": 1000}}, "success": true}"#,
+    )
+    .unwrap();
+    assert_eq!(resp.trade_record.status, "PENDING_ACCEPT");
+    assert_eq!(resp.trade_record.summary.unwrap().offered["xch"], 1000);
+    let resp: NotificationsResp = serde_json::from_str(
+        r#"{"notifications": [{"id":
+            "0404040404040404040404040404040404040404040404040404040404040404",
+            "message": "68656c6c6f", "amount": 1, "height": 42}], "success": true}"#,
+    )
+    .unwrap();
+    assert_eq!(resp.notifications[0].message.bytes, b"hello".to_vec());
+    let resp: VerifySignatureResp =
+        serde_json::from_str(r#"{"isValid": false, "error": "bad", "success": true}"#).unwrap();
+    assert!(!resp.is_valid);
 }
@@ -0,0 +1,133 @@
+use dg_xch_core::blockchain::blockchain_state::BlockchainState;
+use dg_xch_core::blockchain::connection_info::ConnectionInfo;
+use dg_xch_core::blockchain::harvester_info::HarvesterPlots;
+use dg_xch_core::blockchain::signage_point_proofs::SignagePointProofs;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+//Envelope for everything sent to or from the daemon, responses reuse the request_id with ack set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonMessage {
+    pub command: String,
+    pub ack: bool,
+    pub data: Value,
+    pub request_id: String,
+    pub destination: String,
+    pub origin: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewFarmingInfo {
+    pub challenge_hash: Bytes32,
+    pub signage_point: Bytes32,
+    pub passed_filter: u32,
+    pub proofs: u32,
+    pub total_plots: u32,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub node_id: Option<Bytes32>,
+    #[serde(default)]
+    pub lookup_time: f64,
+}
+
+//Events services publish through the daemon, anything not recognised is passed through untouched
+#[derive(Debug, Clone, PartialEq)]
+pub enum DaemonEvent {
+    BlockchainState(Box<BlockchainState>),
+    Connections(Vec<ConnectionInfo>),
+    NewSignagePoint(Box<SignagePointProofs>),
+    NewFarmingInfo(NewFarmingInfo),
+    Proof(Value),
+    Plots(HarvesterPlots),
+    WalletStateChanged {
+        state: String,
+        wallet_id: Option<u32>,
+        additional_data: Option<Value>,
+    },
+    Other(DaemonMessage),
+}
+
+fn field<T: DeserializeOwned>(data: &Value, name: &str) -> Option<T> {
+    serde_json::from_value(data.get(name)?.clone()).ok()
+}
+
+impl From<DaemonMessage> for DaemonEvent {
+    fn from(msg: DaemonMessage) -> Self {
+        let data = &msg.data;
+        let event = match msg.command.as_str() {
+            "get_blockchain_state" => {
+                field(data, "blockchain_state").map(|s| DaemonEvent::BlockchainState(Box::new(s)))
+            }
+            "get_connections" => field(data, "connections").map(DaemonEvent::Connections),
+            "new_signage_point" => serde_json::from_value(data.clone())
+                .ok()
+                .map(|s| DaemonEvent::NewSignagePoint(Box::new(s))),
+            "new_farming_info" => field(data, "farming_info").map(DaemonEvent::NewFarmingInfo),
+            "proof" => Some(DaemonEvent::Proof(data.clone())),
+            "get_plots" => serde_json::from_value(data.clone())
+                .ok()
+                .map(DaemonEvent::Plots),
+            "state_changed" => field(data, "state").map(|state| DaemonEvent::WalletStateChanged {
+                state,
+                wallet_id: field(data, "wallet_id"),
+                additional_data: data.get("additional_data").cloned(),
+            }),
+            _ => None,
+        };
+        event.unwrap_or(DaemonEvent::Other(msg))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyringStatus {
+    pub is_keyring_locked: bool,
+    pub passphrase_support_enabled: bool,
+    pub can_save_passphrase: bool,
+    pub user_passphrase_is_set: bool,
+    #[serde(default)]
+    pub can_set_passphrase_hint: bool,
+    #[serde(default)]
+    pub passphrase_hint: Option<String>,
+    #[serde(default)]
+    pub passphrase_requirements: Value,
+}
+
+#[test]
+fn test_daemon_events() {
+    let msg: DaemonMessage = serde_json::from_str(
+        r#"{"command": "state_changed", "ack": false, "data": {"state": "tx_update",
+            "wallet_id": 1, "additional_data": {"transaction": null}, "success": true},
+            "request_id": "", "destination": "wallet_ui", "origin": "chia_wallet"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        DaemonEvent::from(msg),
+        DaemonEvent::WalletStateChanged {
+            state: "tx_update".to_string(),
+            wallet_id: Some(1),
+            additional_data: Some(serde_json::json!({"transaction": null})),
+        }
+    );
+    let msg: DaemonMessage = serde_json::from_str(
+        r#"{"command": "new_farming_info", "ack": false, "data": {"farming_info": {
+            "challenge_hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+            "signage_point": "0x0202020202020202020202020202020202020202020202020202020202020202",
+            "passed_filter": 3, "proofs": 0, "total_plots": 1500, "timestamp": 1700000000,
+            "node_id": "0x0303030303030303030303030303030303030303030303030303030303030303",
+            "lookup_time": 0.25}, "success": true}, "request_id": "",
+            "destination": "wallet_ui", "origin": "chia_farmer"}"#,
+    )
+    .unwrap();
+    match DaemonEvent::from(msg) {
+        DaemonEvent::NewFarmingInfo(info) => assert_eq!(info.total_plots, 1500),
+        e => panic!("Unexpected event {e:?}"),
+    }
+    let msg: DaemonMessage = serde_json::from_str(
+        r#"{"command": "get_blockchain_state", "ack": false, "data": {"success": false},
+            "request_id": "", "destination": "metrics", "origin": "chia_full_node"}"#,
+    )
+    .unwrap();
+    assert!(matches!(DaemonEvent::from(msg), DaemonEvent::Other(_)));
+}
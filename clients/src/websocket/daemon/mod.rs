@@ -0,0 +1,355 @@
+pub mod events;
+
+use crate::websocket::daemon::events::{DaemonEvent, DaemonMessage, KeyringStatus};
+use crate::websocket::{
+    load_client_certs, server_cert_verifier, ServerVerification, WsClientConfig,
+};
+use crate::ClientSSLConfig;
+use dg_xch_core::config::{CaSsl, ChiaConfig};
+use dg_xch_core::protocols::shared::Capabilities;
+use dg_xch_serialize::ChiaProtocolVersion;
+use futures_util::stream::{unfold, SplitSink};
+use futures_util::{SinkExt, Stream, StreamExt};
+use log::debug;
+use rustls::ClientConfig;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::select;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{
+    connect_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream,
+};
+use uuid::Uuid;
+
+pub const DAEMON_SERVICE: &str = "daemon";
+pub const WALLET_UI_SERVICE: &str = "wallet_ui";
+pub const METRICS_SERVICE: &str = "metrics";
+const DAEMON_CLIENT_ORIGIN: &str = "client";
+const EVENT_BUFFER_SIZE: usize = 256;
+
+type DaemonSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+type PendingRequests = Arc<Mutex<HashMap<String, oneshot::Sender<DaemonMessage>>>>;
+
+//Client for the daemon websocket, which speaks json rather than the chia peer protocol
+pub struct DaemonClient {
+    pub client_config: Arc<WsClientConfig>,
+    sink: Arc<Mutex<DaemonSink>>,
+    pending: PendingRequests,
+    events: broadcast::Sender<DaemonEvent>,
+    handle: JoinHandle<()>,
+    run: Arc<AtomicBool>,
+}
+impl DaemonClient {
+    pub async fn new(
+        client_config: Arc<WsClientConfig>,
+        run: Arc<AtomicBool>,
+    ) -> Result<Self, Error> {
+        let (certs, key, _, signing_ca) = load_client_certs(&client_config)?;
+        let verifier = server_cert_verifier(&client_config, signing_ca.as_deref())?;
+        let request = format!("wss://{}:{}", client_config.host, client_config.port)
+            .into_client_request()
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Failed to Parse Request: {}", e),
+                )
+            })?;
+        let (stream, _) = connect_async_tls_with_config(
+            request,
+            None,
+            false,
+            Some(Connector::Rustls(Arc::new(
+                ClientConfig::builder()
+                    .with_safe_defaults()
+                    .with_custom_certificate_verifier(verifier)
+                    .with_client_auth_cert(certs, key)
+                    .map_err(|e| {
+                        Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Error Building Client: {:?}", e),
+                        )
+                    })?,
+            ))),
+        )
+        .await
+        .map_err(|e| {
+            Error::new(
+                ErrorKind::ConnectionRefused,
+                format!(
+                    "Error Connecting to Daemon at {}:{}: {:?}",
+                    client_config.host, client_config.port, e
+                ),
+            )
+        })?;
+        let (sink, mut read) = stream.split();
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let (events, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+        let handle_run = run.clone();
+        let handle_pending = pending.clone();
+        let handle_events = events.clone();
+        let handle = tokio::spawn(async move {
+            while handle_run.load(Ordering::Relaxed) {
+                select! {
+                    msg = read.next() => match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            match serde_json::from_str::<DaemonMessage>(&text) {
+                                Ok(msg) => {
+                                    let waiter = if msg.ack {
+                                        handle_pending.lock().await.remove(&msg.request_id)
+                                    } else {
+                                        None
+                                    };
+                                    match waiter {
+                                        Some(waiter) => {
+                                            let _ = waiter.send(msg);
+                                        }
+                                        None => {
+                                            let _ = handle_events.send(msg.into());
+                                        }
+                                    }
+                                }
+                                Err(e) => debug!("Invalid Daemon Message: {:?}", e),
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => break,
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => {
+                            debug!("Daemon Connection Error: {:?}", e);
+                            break;
+                        }
+                    },
+                    _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                }
+            }
+            //Dropping the waiters fails any request still in flight
+            handle_pending.lock().await.clear();
+        });
+        Ok(DaemonClient {
+            client_config,
+            sink: Arc::new(Mutex::new(sink)),
+            pending,
+            events,
+            handle,
+            run,
+        })
+    }
+
+    //Connects with the daemon certificate and private CA of a chia root, paths in the config are relative to it
+    pub async fn from_chia_config(
+        root: &Path,
+        config: &ChiaConfig,
+        run: Arc<AtomicBool>,
+    ) -> Result<Self, Error> {
+        let in_root = |path: &str| root.join(path).to_string_lossy().to_string();
+        let ca = CaSsl {
+            crt: in_root(&config.private_ssl_ca.crt),
+            key: in_root(&config.private_ssl_ca.key),
+        };
+        let client_config = WsClientConfig {
+            host: config.ui.daemon_host.clone(),
+            port: config.ui.daemon_port,
+            network_id: config.selected_network.clone(),
+            ssl_info: Some(ClientSSLConfig {
+                ssl_crt_path: in_root(&config.daemon_ssl.private_crt),
+                ssl_key_path: in_root(&config.daemon_ssl.private_key),
+                ssl_ca_crt_path: ca.crt.clone(),
+            }),
+            software_version: None,
+            protocol_version: ChiaProtocolVersion::default(),
+            additional_headers: None,
+            peer_policy: None,
+            server_verification: ServerVerification::PrivateCa(ca),
+            capabilities: Capabilities::default(),
+        };
+        Self::new(Arc::new(client_config), run).await
+    }
+
+    //Sends a command to the daemon, or through it to a running service, and waits for the ack
+    pub async fn request(
+        &self,
+        destination: &str,
+        command: &str,
+        data: Value,
+        timeout: Option<u64>,
+    ) -> Result<Value, Error> {
+        let request_id = Uuid::new_v4().simple().to_string();
+        let msg = DaemonMessage {
+            command: command.to_string(),
+            ack: false,
+            data,
+            request_id: request_id.clone(),
+            destination: destination.to_string(),
+            origin: DAEMON_CLIENT_ORIGIN.to_string(),
+        };
+        let text = serde_json::to_string(&msg).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Failed to serialize daemon command {command}: {:?}", e),
+            )
+        })?;
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(request_id.clone(), tx);
+        if let Err(e) = self.sink.lock().await.send(Message::Text(text)).await {
+            self.pending.lock().await.remove(&request_id);
+            return Err(Error::new(
+                ErrorKind::BrokenPipe,
+                format!("Failed to send daemon command {command}: {:?}", e),
+            ));
+        }
+        match tokio::time::timeout(Duration::from_millis(timeout.unwrap_or(15000)), rx).await {
+            Ok(Ok(resp)) => {
+                if resp.data.get("success").and_then(Value::as_bool) == Some(false) {
+                    Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "Daemon command {command} failed: {}",
+                            resp.data
+                                .get("error")
+                                .and_then(Value::as_str)
+                                .unwrap_or_default()
+                        ),
+                    ))
+                } else {
+                    Ok(resp.data)
+                }
+            }
+            Ok(Err(_)) => Err(Error::new(
+                ErrorKind::ConnectionAborted,
+                format!("Daemon connection closed before {command} completed"),
+            )),
+            Err(_) => {
+                self.pending.lock().await.remove(&request_id);
+                Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!("Timeout before daemon command {command} completed"),
+                ))
+            }
+        }
+    }
+
+    //Every event received from now on, slow consumers skip the events they fell behind on
+    pub fn events(&self) -> impl Stream<Item = DaemonEvent> {
+        unfold(self.events.subscribe(), |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => return Some((event, rx)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("Daemon event stream skipped {skipped} events");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    //Registers for the events the daemon routes to service, usually WALLET_UI_SERVICE or METRICS_SERVICE
+    pub async fn subscribe(&self, service: &str) -> Result<impl Stream<Item = DaemonEvent>, Error> {
+        let events = self.events();
+        self.register_service(service).await?;
+        Ok(events)
+    }
+
+    pub async fn register_service(&self, service: &str) -> Result<(), Error> {
+        self.daemon_command("register_service", json!({ "service": service }))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn ping(&self) -> Result<(), Error> {
+        self.daemon_command("ping", json!({})).await.map(|_| ())
+    }
+
+    pub async fn start_service(&self, service: &str) -> Result<(), Error> {
+        self.daemon_command("start_service", json!({ "service": service }))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn stop_service(&self, service: &str) -> Result<(), Error> {
+        self.daemon_command("stop_service", json!({ "service": service }))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn is_running(&self, service: &str) -> Result<bool, Error> {
+        let data = self
+            .daemon_command("is_running", json!({ "service": service }))
+            .await?;
+        Ok(data
+            .get("is_running")
+            .and_then(Value::as_bool)
+            .unwrap_or_default())
+    }
+
+    pub async fn running_services(&self) -> Result<Vec<String>, Error> {
+        let data = self.daemon_command("running_services", json!({})).await?;
+        parse_field(&data, "running_services")
+    }
+
+    pub async fn keyring_status(&self) -> Result<KeyringStatus, Error> {
+        let data = self.daemon_command("keyring_status", json!({})).await?;
+        serde_json::from_value(data).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to parse keyring status: {:?}", e),
+            )
+        })
+    }
+
+    pub async fn unlock_keyring(&self, passphrase: &str) -> Result<(), Error> {
+        self.daemon_command("unlock_keyring", json!({ "key": passphrase }))
+            .await
+            .map(|_| ())
+    }
+
+    //Asks the daemon to stop all services and exit
+    pub async fn exit(&self) -> Result<(), Error> {
+        self.daemon_command("exit", json!({})).await.map(|_| ())
+    }
+
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        self.run.store(false, Ordering::Relaxed);
+        self.sink.lock().await.close().await.map_err(|e| {
+            Error::new(
+                ErrorKind::BrokenPipe,
+                format!("Failed to close daemon connection: {:?}", e),
+            )
+        })
+    }
+
+    pub async fn join(self) -> Result<(), Error> {
+        self.handle.await.map_err(|e| {
+            Error::new(
+                ErrorKind::Interrupted,
+                format!("Failed to join daemon client: {:?}", e),
+            )
+        })
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    async fn daemon_command(&self, command: &str, data: Value) -> Result<Value, Error> {
+        self.request(DAEMON_SERVICE, command, data, None).await
+    }
+}
+
+fn parse_field<T: serde::de::DeserializeOwned>(data: &Value, name: &str) -> Result<T, Error> {
+    serde_json::from_value(data.get(name).cloned().unwrap_or_default()).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse {name} from daemon: {:?}", e),
+        )
+    })
+}
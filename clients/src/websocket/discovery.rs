@@ -0,0 +1,551 @@
+use crate::websocket::full_node::FullnodeClient;
+use crate::websocket::introducer::IntroducerClient;
+use crate::websocket::WsClientConfig;
+use dg_xch_core::blockchain::peer_info::TimestampedPeerInfo;
+use dg_xch_core::config::{IntroducerPeer, WalletConfig};
+use log::{debug, info, warn};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Error, ErrorKind};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::lookup_host;
+use tokio::sync::RwLock;
+
+//Limits and eviction rules follow the reference address manager
+const MAX_NEW_ENTRIES: usize = 16384;
+const MAX_TRIED_ENTRIES: usize = 4096;
+const HORIZON_SECONDS: u64 = 30 * 24 * 60 * 60;
+const NEW_RETRIES: u32 = 3;
+const TRIED_MAX_FAILURES: u32 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerEntry {
+    pub host: String,
+    pub port: u16,
+    //Last time the peer was seen alive, as reported by the source
+    pub timestamp: u64,
+    pub source: String,
+    pub last_try: u64,
+    pub last_success: u64,
+    pub attempts: u32,
+}
+impl PeerEntry {
+    pub fn key(&self) -> String {
+        peer_key(&self.host, self.port)
+    }
+
+    fn is_terrible(&self, now: u64) -> bool {
+        if self.last_try > 0 && now.saturating_sub(self.last_try) < 60 {
+            //Never evict something that was just tried
+            return false;
+        }
+        self.timestamp > now + 600
+            || now.saturating_sub(self.timestamp) > HORIZON_SECONDS
+            || (self.last_success == 0 && self.attempts >= NEW_RETRIES)
+            || self.attempts >= TRIED_MAX_FAILURES
+    }
+}
+impl From<&PeerEntry> for TimestampedPeerInfo {
+    fn from(entry: &PeerEntry) -> Self {
+        TimestampedPeerInfo {
+            host: entry.host.clone(),
+            port: entry.port,
+            timestamp: entry.timestamp,
+        }
+    }
+}
+
+//Peers start in the new table and move to the tried table after a successful connection
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AddressManager {
+    new_table: HashMap<String, PeerEntry>,
+    tried_table: HashMap<String, PeerEntry>,
+}
+impl AddressManager {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let data = std::fs::read(path)?;
+        serde_json::from_slice(&data).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to parse peer table {}: {:?}", path.display(), e),
+            )
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(self).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to serialize peer table: {:?}", e),
+            )
+        })?;
+        //Write then rename so a crash never leaves a truncated table behind
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(tmp_path, path)
+    }
+
+    pub fn len_new(&self) -> usize {
+        self.new_table.len()
+    }
+
+    pub fn len_tried(&self) -> usize {
+        self.tried_table.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len_new() + self.len_tried()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn add(&mut self, peers: &[TimestampedPeerInfo], source: &str) -> usize {
+        let now = now();
+        let mut added = 0;
+        for peer in peers {
+            if peer.host.is_empty() || peer.port == 0 {
+                continue;
+            }
+            let key = peer_key(&peer.host, peer.port);
+            if let Some(existing) = self.tried_table.get_mut(&key) {
+                existing.timestamp = existing.timestamp.max(peer.timestamp);
+                continue;
+            }
+            if let Some(existing) = self.new_table.get_mut(&key) {
+                existing.timestamp = existing.timestamp.max(peer.timestamp);
+                continue;
+            }
+            let entry = PeerEntry {
+                host: peer.host.clone(),
+                port: peer.port,
+                timestamp: peer.timestamp,
+                source: source.to_string(),
+                last_try: 0,
+                last_success: 0,
+                attempts: 0,
+            };
+            if entry.is_terrible(now) {
+                continue;
+            }
+            self.new_table.insert(key, entry);
+            added += 1;
+        }
+        self.evict(now);
+        added
+    }
+
+    pub fn attempt(&mut self, host: &str, port: u16) {
+        let key = peer_key(host, port);
+        if let Some(entry) = self
+            .tried_table
+            .get_mut(&key)
+            .or_else(|| self.new_table.get_mut(&key))
+        {
+            entry.last_try = now();
+            entry.attempts += 1;
+        }
+    }
+
+    pub fn mark_good(&mut self, host: &str, port: u16) {
+        let key = peer_key(host, port);
+        let now = now();
+        let mut entry = match self.new_table.remove(&key) {
+            Some(entry) => entry,
+            None => match self.tried_table.remove(&key) {
+                Some(entry) => entry,
+                None => PeerEntry {
+                    host: host.to_string(),
+                    port,
+                    timestamp: now,
+                    source: "direct".to_string(),
+                    last_try: now,
+                    last_success: 0,
+                    attempts: 0,
+                },
+            },
+        };
+        entry.timestamp = now;
+        entry.last_success = now;
+        entry.attempts = 0;
+        self.tried_table.insert(key, entry);
+        self.evict(now);
+    }
+
+    //Alternates between tried and new entries, least recently tried first
+    pub fn select(&self, count: usize, exclude: &HashSet<String>) -> Vec<PeerEntry> {
+        let mut tried = least_recently_tried(&self.tried_table, exclude).into_iter();
+        let mut new = least_recently_tried(&self.new_table, exclude).into_iter();
+        let mut selected = vec![];
+        while selected.len() < count {
+            let before = selected.len();
+            if let Some(entry) = tried.next() {
+                selected.push(entry.clone());
+            }
+            if selected.len() < count {
+                if let Some(entry) = new.next() {
+                    selected.push(entry.clone());
+                }
+            }
+            if selected.len() == before {
+                break;
+            }
+        }
+        selected
+    }
+
+    //Peers seen within max_age, freshest first, in the format sent to other nodes
+    pub fn recent(&self, count: usize, max_age: u64) -> Vec<TimestampedPeerInfo> {
+        let now = now();
+        let mut entries: Vec<&PeerEntry> = self
+            .tried_table
+            .values()
+            .chain(self.new_table.values())
+            .filter(|e| now.saturating_sub(e.timestamp) <= max_age)
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        entries.into_iter().take(count).map(|e| e.into()).collect()
+    }
+
+    //A random sample of peers that accepted a connection within max_age, so every caller does not
+    //hand out the same few nodes
+    pub fn reachable(&self, count: usize, max_age: u64) -> Vec<TimestampedPeerInfo> {
+        let now = now();
+        let mut entries: Vec<&PeerEntry> = self
+            .tried_table
+            .values()
+            .filter(|e| now.saturating_sub(e.last_success) <= max_age)
+            .collect();
+        entries.shuffle(&mut rand::thread_rng());
+        entries.into_iter().take(count).map(|e| e.into()).collect()
+    }
+
+    fn evict(&mut self, now: u64) {
+        self.new_table.retain(|_, e| !e.is_terrible(now));
+        self.tried_table.retain(|_, e| !e.is_terrible(now));
+        trim_oldest(&mut self.new_table, MAX_NEW_ENTRIES);
+        trim_oldest(&mut self.tried_table, MAX_TRIED_ENTRIES);
+    }
+}
+
+fn least_recently_tried<'a>(
+    table: &'a HashMap<String, PeerEntry>,
+    exclude: &HashSet<String>,
+) -> Vec<&'a PeerEntry> {
+    let mut entries: Vec<&PeerEntry> = table
+        .values()
+        .filter(|e| !exclude.contains(&e.key()))
+        .collect();
+    entries.sort_by_key(|e| (e.last_try, e.attempts));
+    entries
+}
+
+fn trim_oldest(table: &mut HashMap<String, PeerEntry>, max: usize) {
+    if table.len() <= max {
+        return;
+    }
+    let mut by_age: Vec<(u64, String)> = table
+        .iter()
+        .map(|(k, e)| (e.timestamp, k.clone()))
+        .collect();
+    by_age.sort();
+    for (_, key) in by_age.into_iter().take(table.len() - max) {
+        table.remove(&key);
+    }
+}
+
+fn peer_key(host: &str, port: u16) -> String {
+    format!("{host}:{port}")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn is_public_host(host: &str) -> bool {
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        Ok(IpAddr::V6(ip)) => {
+            let first = ip.segments()[0];
+            //Unique local (fc00::/7) and link local (fe80::/10) ranges
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+        Err(_) => true,
+    }
+}
+
+pub struct PeerDiscoveryConfig {
+    pub introducers: Vec<IntroducerPeer>,
+    pub dns_servers: Vec<String>,
+    pub default_port: u16,
+    pub peers_file: Option<PathBuf>,
+    //Introducers and DNS seeds are only queried while fewer peers than this are known
+    pub min_peers: usize,
+    pub request_timeout: Duration,
+    pub enable_private_networks: bool,
+}
+impl Default for PeerDiscoveryConfig {
+    fn default() -> Self {
+        let wallet_config = WalletConfig::default();
+        Self {
+            enable_private_networks: wallet_config.introducer_peer.enable_private_networks,
+            introducers: vec![wallet_config.introducer_peer],
+            dns_servers: wallet_config.dns_servers,
+            default_port: 8444,
+            peers_file: None,
+            min_peers: 32,
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+pub struct PeerDiscovery {
+    pub config: PeerDiscoveryConfig,
+    //Template for connections to discovered peers, only the host and port are replaced
+    pub client_template: Arc<WsClientConfig>,
+    pub address_manager: Arc<RwLock<AddressManager>>,
+}
+impl PeerDiscovery {
+    pub fn new(
+        config: PeerDiscoveryConfig,
+        client_template: Arc<WsClientConfig>,
+    ) -> Result<Self, Error> {
+        let address_manager = match &config.peers_file {
+            Some(path) if path.exists() => AddressManager::load(path)?,
+            _ => AddressManager::default(),
+        };
+        info!(
+            "Loaded peer table with {} tried and {} new peers",
+            address_manager.len_tried(),
+            address_manager.len_new()
+        );
+        Ok(Self {
+            config,
+            client_template,
+            address_manager: Arc::new(RwLock::new(address_manager)),
+        })
+    }
+
+    pub async fn save(&self) -> Result<(), Error> {
+        if let Some(path) = &self.config.peers_file {
+            self.address_manager.read().await.save(path)?;
+        }
+        Ok(())
+    }
+
+    pub async fn add_peers(&self, peers: &[TimestampedPeerInfo], source: &str) -> usize {
+        let peers: Vec<TimestampedPeerInfo> = peers
+            .iter()
+            .filter(|p| self.config.enable_private_networks || is_public_host(&p.host))
+            .cloned()
+            .collect();
+        self.address_manager.write().await.add(&peers, source)
+    }
+
+    pub async fn query_dns(&self) -> Vec<TimestampedPeerInfo> {
+        let now = now();
+        let mut peers = vec![];
+        for seed in &self.config.dns_servers {
+            match lookup_host((seed.as_str(), self.config.default_port)).await {
+                Ok(addrs) => {
+                    for addr in addrs {
+                        peers.push(TimestampedPeerInfo {
+                            host: addr.ip().to_string(),
+                            port: addr.port(),
+                            timestamp: now,
+                        });
+                    }
+                }
+                Err(e) => warn!("Failed to resolve DNS seed {seed}: {:?}", e),
+            }
+        }
+        peers
+    }
+
+    pub async fn query_introducers(&self, run: Arc<AtomicBool>) -> Vec<TimestampedPeerInfo> {
+        let mut peers = vec![];
+        for introducer in &self.config.introducers {
+            let client_config = self.peer_config(&introducer.host, introducer.port);
+            let result = match IntroducerClient::new(client_config, run.clone()).await {
+                Ok(client) => {
+                    let result = client.request_peers(self.config.request_timeout).await;
+                    let _ = client.join().await;
+                    result
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(list) => peers.extend(list),
+                Err(e) => warn!(
+                    "Failed to get peers from introducer {}:{}: {:?}",
+                    introducer.host, introducer.port, e
+                ),
+            }
+        }
+        peers
+    }
+
+    //Tops up the peer table from DNS seeds first and introducers second
+    pub async fn refresh(&self, run: Arc<AtomicBool>) -> Result<usize, Error> {
+        let mut added = 0;
+        if self.address_manager.read().await.len() < self.config.min_peers {
+            let dns_peers = self.query_dns().await;
+            added += self.add_peers(&dns_peers, "dns").await;
+        }
+        if self.address_manager.read().await.len() < self.config.min_peers {
+            let introducer_peers = self.query_introducers(run).await;
+            added += self.add_peers(&introducer_peers, "introducer").await;
+        }
+        debug!("Added {added} peers during refresh");
+        self.save().await?;
+        Ok(added)
+    }
+
+    //Client configs for the next peers to try, each one is recorded as an attempt
+    pub async fn next_peers(
+        &self,
+        count: usize,
+        exclude: &HashSet<String>,
+    ) -> Vec<Arc<WsClientConfig>> {
+        let mut address_manager = self.address_manager.write().await;
+        let selected = address_manager.select(count, exclude);
+        selected
+            .into_iter()
+            .map(|entry| {
+                address_manager.attempt(&entry.host, entry.port);
+                self.peer_config(&entry.host, entry.port)
+            })
+            .collect()
+    }
+
+    pub async fn mark_good(&self, host: &str, port: u16) {
+        self.address_manager.write().await.mark_good(host, port);
+    }
+
+    //Connects to the first reachable full node and asks it for more peers
+    pub async fn connect_fullnode(
+        &self,
+        run: Arc<AtomicBool>,
+        exclude: &HashSet<String>,
+    ) -> Result<FullnodeClient, Error> {
+        if self.address_manager.read().await.is_empty() {
+            self.refresh(run.clone()).await?;
+        }
+        for client_config in self.next_peers(self.config.min_peers, exclude).await {
+            match FullnodeClient::new(client_config.clone(), run.clone()).await {
+                Ok(client) => {
+                    self.mark_good(&client_config.host, client_config.port)
+                        .await;
+                    match client.request_peers(self.config.request_timeout).await {
+                        Ok(Some(peers)) => {
+                            let source = peer_key(&client_config.host, client_config.port);
+                            self.add_peers(&peers, &source).await;
+                        }
+                        Ok(None) => {}
+                        Err(e) => debug!("Peer did not share its peers: {:?}", e),
+                    }
+                    self.save().await?;
+                    return Ok(client);
+                }
+                Err(e) => debug!(
+                    "Failed to connect to {}:{}: {:?}",
+                    client_config.host, client_config.port, e
+                ),
+            }
+        }
+        self.save().await?;
+        Err(Error::new(
+            ErrorKind::NotConnected,
+            "Failed to connect to any known peer",
+        ))
+    }
+
+    fn peer_config(&self, host: &str, port: u16) -> Arc<WsClientConfig> {
+        let mut client_config = self.client_template.as_ref().clone();
+        client_config.host = host.to_string();
+        client_config.port = port;
+        Arc::new(client_config)
+    }
+}
+
+#[test]
+fn test_address_manager() {
+    let now = now();
+    let mut address_manager = AddressManager::default();
+    let peers: Vec<TimestampedPeerInfo> = (1..=4)
+        .map(|i| TimestampedPeerInfo {
+            host: format!("1.1.1.{i}"),
+            port: 8444,
+            timestamp: now,
+        })
+        .chain([TimestampedPeerInfo {
+            host: "2.2.2.2".to_string(),
+            port: 8444,
+            timestamp: now - HORIZON_SECONDS - 1,
+        }])
+        .collect();
+    assert_eq!(address_manager.add(&peers, "test"), 4);
+    assert_eq!(address_manager.add(&peers, "test"), 0);
+    address_manager.mark_good("1.1.1.3", 8444);
+    assert_eq!(
+        (address_manager.len_tried(), address_manager.len_new()),
+        (1, 3)
+    );
+    let selected = address_manager.select(2, &HashSet::new());
+    assert_eq!(selected[0].host, "1.1.1.3");
+    let exclude = HashSet::from([peer_key("1.1.1.3", 8444)]);
+    assert!(address_manager
+        .select(10, &exclude)
+        .iter()
+        .all(|e| e.host != "1.1.1.3"));
+    for _ in 0..NEW_RETRIES {
+        address_manager.attempt("1.1.1.1", 8444);
+    }
+    assert_eq!(address_manager.recent(10, 60).len(), 4);
+    let mut good = AddressManager::default();
+    let many: Vec<TimestampedPeerInfo> = (1..=20)
+        .map(|i| TimestampedPeerInfo {
+            host: format!("3.3.3.{i}"),
+            port: 8444,
+            timestamp: now,
+        })
+        .collect();
+    good.add(&many, "test");
+    for peer in &many {
+        good.mark_good(&peer.host, peer.port);
+    }
+    let sampled: HashSet<String> = (0..50)
+        .flat_map(|_| good.reachable(2, 60))
+        .map(|p| p.host)
+        .collect();
+    assert!(sampled.len() > 2);
+    let path = std::env::temp_dir().join(format!("peers_{}.json", uuid::Uuid::new_v4()));
+    address_manager.save(&path).unwrap();
+    let loaded = AddressManager::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.len(), 4);
+    assert!(!is_public_host("192.168.1.4"));
+    assert!(!is_public_host("fe80::1"));
+    assert!(is_public_host("node.chia.net"));
+}
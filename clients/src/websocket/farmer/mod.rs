@@ -1,7 +1,9 @@
 use crate::websocket::farmer::request_signed_values::RequestSignedValuesHandle;
 use crate::websocket::farmer::signage_point::NewSignagePointHandle;
+use crate::websocket::supervisor::{ReconnectPolicy, SupervisedWsClient};
 use crate::websocket::{WsClient, WsClientConfig};
-use dg_xch_core::consensus::constants::{ConsensusConstants, CONSENSUS_CONSTANTS_MAP, MAINNET};
+use dg_xch_core::consensus::constants::ConsensusConstants;
+use dg_xch_core::consensus::registry::CONSENSUS_CONSTANTS_REGISTRY;
 use dg_xch_core::protocols::farmer::FarmerSharedState;
 use dg_xch_core::protocols::{
     ChiaMessageFilter, ChiaMessageHandler, NodeType, ProtocolMessageTypes,
@@ -26,10 +28,7 @@ impl<T> FarmerClient<T> {
         shared_state: Arc<FarmerSharedState<T>>,
         run: Arc<AtomicBool>,
     ) -> Result<Self, Error> {
-        let constants = CONSENSUS_CONSTANTS_MAP
-            .get(&client_config.network_id)
-            .cloned()
-            .unwrap_or(MAINNET.clone());
+        let constants = CONSENSUS_CONSTANTS_REGISTRY.get_or_err(&client_config.network_id)?;
         let handles = Arc::new(RwLock::new(handles(constants, shared_state.clone())));
         let client = WsClient::new(client_config, NodeType::Farmer, handles, run).await?;
         Ok(FarmerClient {
@@ -38,6 +37,17 @@ impl<T> FarmerClient<T> {
         })
     }
 
+    pub fn supervised(
+        client_config: Arc<WsClientConfig>,
+        shared_state: Arc<FarmerSharedState<T>>,
+        run: Arc<AtomicBool>,
+        policy: ReconnectPolicy,
+    ) -> SupervisedWsClient {
+        let constants = CONSENSUS_CONSTANTS_REGISTRY.get_or_mainnet(&client_config.network_id);
+        let handles = Arc::new(RwLock::new(handles(constants, shared_state)));
+        SupervisedWsClient::new(client_config, NodeType::Farmer, handles, run, policy)
+    }
+
     pub async fn join(self) -> Result<(), Error> {
         self.client.connection.write().await.shutdown().await?;
         self.client.join().await
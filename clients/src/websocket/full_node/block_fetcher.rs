@@ -0,0 +1,410 @@
+use crate::websocket::full_node::{FullnodeClient, SupervisedFullnodeClient};
+use crate::websocket::supervisor::ReconnectPolicy;
+use crate::websocket::WsClientConfig;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::full_block::FullBlock;
+use futures_util::future::join_all;
+use log::{debug, info, warn};
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{channel, Receiver};
+use tokio::task::JoinHandle;
+
+pub struct BlockFetcherConfig {
+    pub start_height: u32,
+    //Full nodes reject RequestBlocks spanning more than 32 blocks
+    pub batch_size: u32,
+    pub include_transaction_block: bool,
+    pub request_timeout: Duration,
+    pub max_retries: u32,
+    pub poll_interval: Duration,
+    pub buffer_size: usize,
+    //Backoff between failed rounds while streaming, the stream ends with the error once
+    //max_attempts rounds in a row have failed
+    pub retry_policy: ReconnectPolicy,
+}
+impl Default for BlockFetcherConfig {
+    fn default() -> Self {
+        Self {
+            start_height: 0,
+            batch_size: 32,
+            include_transaction_block: true,
+            request_timeout: Duration::from_secs(60),
+            max_retries: 3,
+            poll_interval: Duration::from_secs(1),
+            buffer_size: 256,
+            retry_policy: ReconnectPolicy::default(),
+        }
+    }
+}
+
+//A full node the fetcher can download blocks from
+#[async_trait]
+pub trait BlockPeer: Send + Sync {
+    async fn peak_height(&self) -> Option<u32>;
+    async fn is_connected(&self) -> bool;
+    async fn request_blocks(
+        &self,
+        start: u32,
+        end: u32,
+        include_transaction_block: bool,
+        timeout: Duration,
+    ) -> Result<Option<Vec<FullBlock>>, Error>;
+}
+
+#[async_trait]
+impl BlockPeer for FullnodeClient {
+    async fn peak_height(&self) -> Option<u32> {
+        FullnodeClient::peak_height(self).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        !self.is_closed()
+    }
+
+    async fn request_blocks(
+        &self,
+        start: u32,
+        end: u32,
+        include_transaction_block: bool,
+        timeout: Duration,
+    ) -> Result<Option<Vec<FullBlock>>, Error> {
+        FullnodeClient::request_blocks(self, start, end, include_transaction_block, timeout).await
+    }
+}
+
+#[async_trait]
+impl BlockPeer for SupervisedFullnodeClient {
+    async fn peak_height(&self) -> Option<u32> {
+        SupervisedFullnodeClient::peak_height(self).await
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.client.is_connected().await
+    }
+
+    async fn request_blocks(
+        &self,
+        start: u32,
+        end: u32,
+        include_transaction_block: bool,
+        timeout: Duration,
+    ) -> Result<Option<Vec<FullBlock>>, Error> {
+        SupervisedFullnodeClient::request_blocks(
+            self,
+            start,
+            end,
+            include_transaction_block,
+            timeout,
+        )
+        .await
+    }
+}
+
+//Downloads blocks from one or more full nodes over the peer protocol, batches are spread over the
+//connected peers and retried on the next peer when one fails
+pub struct BlockFetcher {
+    pub peers: Vec<Arc<dyn BlockPeer>>,
+    pub config: BlockFetcherConfig,
+}
+impl BlockFetcher {
+    pub fn new(peers: Vec<Arc<dyn BlockPeer>>, config: BlockFetcherConfig) -> Self {
+        Self { peers, config }
+    }
+
+    //Each peer reconnects on its own, so a node that restarts rejoins the fetcher
+    pub fn supervised(
+        client_configs: &[Arc<WsClientConfig>],
+        run: Arc<AtomicBool>,
+        policy: ReconnectPolicy,
+        config: BlockFetcherConfig,
+    ) -> Self {
+        let peers = client_configs
+            .iter()
+            .map(|client_config| {
+                Arc::new(SupervisedFullnodeClient::new(
+                    client_config.clone(),
+                    run.clone(),
+                    policy.clone(),
+                )) as Arc<dyn BlockPeer>
+            })
+            .collect();
+        Self::new(peers, config)
+    }
+
+    //Highest peak announced by any connected peer
+    pub async fn peak_height(&self) -> Option<u32> {
+        let mut height = None;
+        for peer in self.live_peers().await {
+            height = height.max(peer.peak_height().await);
+        }
+        height
+    }
+
+    //Fetches the inclusive range start..=end
+    pub async fn fetch_range(&self, start: u32, end: u32) -> Result<Vec<FullBlock>, Error> {
+        let count = end
+            .checked_sub(start)
+            .and_then(|span| span.checked_add(1))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid block range {start} to {end}"),
+                )
+            })?;
+        let peers = self.live_peers().await;
+        if peers.is_empty() {
+            return Err(Error::new(
+                ErrorKind::NotConnected,
+                "No connected full node peers",
+            ));
+        }
+        let batch_size = self.config.batch_size.max(1);
+        let batches: Vec<(u32, u32)> = (start..=end)
+            .step_by(batch_size as usize)
+            .map(|s| (s, s.saturating_add(batch_size - 1).min(end)))
+            .collect();
+        let mut blocks = Vec::with_capacity(count as usize);
+        for round in batches.chunks(peers.len()) {
+            let results = join_all(
+                round
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (s, e))| self.fetch_batch(&peers, i, *s, *e)),
+            )
+            .await;
+            for result in results {
+                blocks.extend(result?);
+            }
+        }
+        Ok(blocks)
+    }
+
+    //Follows the peak of the connected peers and sends every block from start_height in order.
+    //Failed rounds are retried with the configured backoff.
+    //Reorgs are not unwound, consumers should compare prev_header_hash to detect them
+    pub fn stream(
+        self,
+        run: Arc<AtomicBool>,
+    ) -> (Receiver<FullBlock>, JoinHandle<Result<(), Error>>) {
+        let (tx, rx) = channel(self.config.buffer_size.max(1));
+        let handle = tokio::spawn(async move {
+            let mut next_height = self.config.start_height;
+            let round_size = self
+                .config
+                .batch_size
+                .max(1)
+                .saturating_mul(self.peers.len().max(1) as u32);
+            let mut failures = 0u32;
+            while run.load(Ordering::Relaxed) {
+                let peak = match self.peak_height().await {
+                    Some(peak) if peak >= next_height => peak,
+                    _ => {
+                        tokio::time::sleep(self.config.poll_interval).await;
+                        continue;
+                    }
+                };
+                let end = peak.min(next_height.saturating_add(round_size - 1));
+                debug!("Fetching blocks {next_height} to {end}, peak is {peak}");
+                let blocks = match self.fetch_range(next_height, end).await {
+                    Ok(blocks) => {
+                        failures = 0;
+                        blocks
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        if let Some(max_attempts) = self.config.retry_policy.max_attempts {
+                            if failures >= max_attempts {
+                                return Err(e);
+                            }
+                        }
+                        let delay = self.config.retry_policy.delay(failures);
+                        warn!("Failed to fetch blocks {next_height} to {end}, retrying in {delay:?}: {e:?}");
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                };
+                for block in blocks {
+                    if tx.send(block).await.is_err() {
+                        info!("Block receiver dropped, stopping fetcher");
+                        return Ok(());
+                    }
+                }
+                match end.checked_add(1) {
+                    Some(height) => next_height = height,
+                    None => {
+                        info!("Reached the largest block height, stopping fetcher");
+                        return Ok(());
+                    }
+                }
+            }
+            Ok(())
+        });
+        (rx, handle)
+    }
+
+    async fn live_peers(&self) -> Vec<Arc<dyn BlockPeer>> {
+        let mut live = vec![];
+        for peer in &self.peers {
+            if peer.is_connected().await {
+                live.push(peer.clone());
+            }
+        }
+        live
+    }
+
+    async fn fetch_batch(
+        &self,
+        peers: &[Arc<dyn BlockPeer>],
+        offset: usize,
+        start: u32,
+        end: u32,
+    ) -> Result<Vec<FullBlock>, Error> {
+        for attempt in 0..=self.config.max_retries {
+            let peer = &peers[(offset + attempt as usize) % peers.len()];
+            match peer
+                .request_blocks(
+                    start,
+                    end,
+                    self.config.include_transaction_block,
+                    self.config.request_timeout,
+                )
+                .await
+            {
+                Ok(Some(blocks)) if is_complete_range(&blocks, start, end) => return Ok(blocks),
+                Ok(Some(_)) => warn!("Peer returned an incomplete range for {start} to {end}"),
+                Ok(None) => debug!("Peer rejected blocks {start} to {end}"),
+                Err(e) => warn!("Failed to fetch blocks {start} to {end}: {:?}", e),
+            }
+        }
+        Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "No peer returned blocks {start} to {end} after {} attempts",
+                self.config.max_retries + 1
+            ),
+        ))
+    }
+}
+
+fn is_complete_range(blocks: &[FullBlock], start: u32, end: u32) -> bool {
+    blocks.len() as u64 == end as u64 - start as u64 + 1
+        && blocks
+            .iter()
+            .zip(start as u64..)
+            .all(|(b, height)| b.reward_chain_block.height == height)
+}
+
+#[tokio::test]
+async fn test_block_fetcher() {
+    use dg_xch_serialize::{ChiaProtocolVersion, ChiaSerialize};
+    use std::io::Cursor;
+    use std::sync::atomic::AtomicU32;
+    //Every field of an all zero buffer parses as an empty or zero value
+    let template = FullBlock::from_bytes(
+        &mut Cursor::new(vec![0u8; 8192]),
+        ChiaProtocolVersion::default(),
+    )
+    .unwrap();
+    struct MockPeer {
+        template: FullBlock,
+        peak: u32,
+        failures_left: AtomicU32,
+        requests: AtomicU32,
+    }
+    #[async_trait]
+    impl BlockPeer for MockPeer {
+        async fn peak_height(&self) -> Option<u32> {
+            Some(self.peak)
+        }
+
+        async fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn request_blocks(
+            &self,
+            start: u32,
+            end: u32,
+            _: bool,
+            _: Duration,
+        ) -> Result<Option<Vec<FullBlock>>, Error> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            if self
+                .failures_left
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |f| f.checked_sub(1))
+                .is_ok()
+            {
+                return Err(Error::new(ErrorKind::TimedOut, "Timed out"));
+            }
+            Ok(Some(
+                (start..=end)
+                    .map(|height| {
+                        let mut block = self.template.clone();
+                        block.reward_chain_block.height = height as u64;
+                        block
+                    })
+                    .collect(),
+            ))
+        }
+    }
+    let peer = |failures: u32| {
+        Arc::new(MockPeer {
+            template: template.clone(),
+            peak: 9,
+            failures_left: AtomicU32::new(failures),
+            requests: AtomicU32::new(0),
+        })
+    };
+    let config = || BlockFetcherConfig {
+        batch_size: 4,
+        max_retries: 0,
+        poll_interval: Duration::from_millis(10),
+        retry_policy: ReconnectPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(20),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: Some(5),
+        },
+        ..Default::default()
+    };
+    let fetcher = BlockFetcher::new(vec![peer(0), peer(0)], config());
+    let heights: Vec<u64> = fetcher
+        .fetch_range(0, 9)
+        .await
+        .unwrap()
+        .iter()
+        .map(|b| b.reward_chain_block.height)
+        .collect();
+    assert_eq!(heights, (0..=9).collect::<Vec<u64>>());
+    for (start, end) in [(5, 4), (0, u32::MAX)] {
+        let e = fetcher.fetch_range(start, end).await.unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    }
+    //The first rounds fail, the stream backs off and still delivers every block in order
+    let flaky = peer(3);
+    let run = Arc::new(AtomicBool::new(true));
+    let (mut blocks, handle) = BlockFetcher::new(vec![flaky.clone()], config()).stream(run.clone());
+    for height in 0..=9u64 {
+        let block = tokio::time::timeout(Duration::from_secs(5), blocks.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.reward_chain_block.height, height);
+    }
+    assert!(flaky.requests.load(Ordering::Relaxed) > 3);
+    run.store(false, Ordering::Relaxed);
+    handle.await.unwrap().unwrap();
+    //Rounds that keep failing end the stream once max_attempts is reached
+    let (_blocks, handle) =
+        BlockFetcher::new(vec![peer(u32::MAX)], config()).stream(Arc::new(AtomicBool::new(true)));
+    let e = tokio::time::timeout(Duration::from_secs(5), handle)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::NotFound);
+}
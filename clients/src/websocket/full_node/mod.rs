@@ -1,23 +1,119 @@
+use crate::websocket::full_node::new_peak::NewPeakHandle;
+use crate::websocket::supervisor::{ReconnectPolicy, SupervisedWsClient};
 use crate::websocket::{WsClient, WsClientConfig};
-use dg_xch_core::protocols::{ChiaMessageHandler, NodeType};
+use dg_xch_core::blockchain::full_block::FullBlock;
+use dg_xch_core::blockchain::peer_info::TimestampedPeerInfo;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::protocols::full_node::{
+    NewPeak, RequestBlock, RequestBlocks, RequestPeers, RespondBlock, RespondBlocks, RespondPeers,
+};
+use dg_xch_core::protocols::wallet::{
+    PuzzleSolutionResponse, RequestPuzzleSolution, RespondPuzzleSolution,
+};
+use dg_xch_core::protocols::{
+    ChiaMessageFilter, ChiaMessageHandler, NodeType, ProtocolMessageTypes, WebsocketConnection,
+};
+use dg_xch_serialize::ChiaProtocolVersion;
 use std::collections::HashMap;
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+pub mod block_fetcher;
+pub mod new_peak;
+
 pub struct FullnodeClient {
     pub client: WsClient,
+    pub peak: Arc<RwLock<Option<NewPeak>>>,
 }
 impl FullnodeClient {
     pub async fn new(
         client_config: Arc<WsClientConfig>,
         run: Arc<AtomicBool>,
     ) -> Result<Self, Error> {
-        let handles = Arc::new(RwLock::new(handles()));
+        let peak = Arc::new(RwLock::new(None));
+        let handles = Arc::new(RwLock::new(handles(peak.clone())));
         let client = WsClient::new(client_config, NodeType::FullNode, handles, run.clone()).await?;
-        Ok(FullnodeClient { client })
+        Ok(FullnodeClient { client, peak })
+    }
+
+    pub fn supervised(
+        client_config: Arc<WsClientConfig>,
+        peak: Arc<RwLock<Option<NewPeak>>>,
+        run: Arc<AtomicBool>,
+        policy: ReconnectPolicy,
+    ) -> SupervisedWsClient {
+        let handles = Arc::new(RwLock::new(handles(peak)));
+        SupervisedWsClient::new(client_config, NodeType::FullNode, handles, run, policy)
+    }
+
+    pub async fn peak_height(&self) -> Option<u32> {
+        self.peak.read().await.as_ref().map(|p| p.height)
+    }
+
+    pub async fn request_block(
+        &self,
+        height: u32,
+        include_transaction_block: bool,
+        timeout: Duration,
+    ) -> Result<Option<FullBlock>, Error> {
+        request_block(
+            &self.client.connection,
+            height,
+            include_transaction_block,
+            self.client.client_config.protocol_version,
+            timeout,
+        )
+        .await
+    }
+
+    pub async fn request_blocks(
+        &self,
+        start_height: u32,
+        end_height: u32,
+        include_transaction_block: bool,
+        timeout: Duration,
+    ) -> Result<Option<Vec<FullBlock>>, Error> {
+        request_blocks(
+            &self.client.connection,
+            start_height,
+            end_height,
+            include_transaction_block,
+            self.client.client_config.protocol_version,
+            timeout,
+        )
+        .await
+    }
+
+    pub async fn request_puzzle_solution(
+        &self,
+        coin_name: Bytes32,
+        height: u32,
+        timeout: Duration,
+    ) -> Result<Option<PuzzleSolutionResponse>, Error> {
+        request_puzzle_solution(
+            &self.client.connection,
+            coin_name,
+            height,
+            self.client.client_config.protocol_version,
+            timeout,
+        )
+        .await
+    }
+
+    pub async fn request_peers(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<Vec<TimestampedPeerInfo>>, Error> {
+        request_peers(
+            &self.client.connection,
+            self.client.client_config.protocol_version,
+            timeout,
+        )
+        .await
     }
 
     pub async fn join(self) -> Result<(), Error> {
@@ -30,6 +126,136 @@ impl FullnodeClient {
     }
 }
 
-fn handles() -> HashMap<Uuid, Arc<ChiaMessageHandler>> {
-    HashMap::from([])
+//A full node connection that reconnects on its own, requests fail with NotConnected while the
+//supervisor is between connections
+pub struct SupervisedFullnodeClient {
+    pub client: SupervisedWsClient,
+    pub peak: Arc<RwLock<Option<NewPeak>>>,
+}
+impl SupervisedFullnodeClient {
+    pub fn new(
+        client_config: Arc<WsClientConfig>,
+        run: Arc<AtomicBool>,
+        policy: ReconnectPolicy,
+    ) -> Self {
+        let peak = Arc::new(RwLock::new(None));
+        let client = FullnodeClient::supervised(client_config, peak.clone(), run, policy);
+        Self { client, peak }
+    }
+
+    pub async fn peak_height(&self) -> Option<u32> {
+        self.peak.read().await.as_ref().map(|p| p.height)
+    }
+
+    pub async fn request_blocks(
+        &self,
+        start_height: u32,
+        end_height: u32,
+        include_transaction_block: bool,
+        timeout: Duration,
+    ) -> Result<Option<Vec<FullBlock>>, Error> {
+        let connection = self.client.connection().await.ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotConnected,
+                format!(
+                    "Not connected to {}:{}",
+                    self.client.client_config.host, self.client.client_config.port
+                ),
+            )
+        })?;
+        request_blocks(
+            &connection,
+            start_height,
+            end_height,
+            include_transaction_block,
+            self.client.client_config.protocol_version,
+            timeout,
+        )
+        .await
+    }
+
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        self.client.shutdown().await
+    }
+}
+
+fn handles(peak: Arc<RwLock<Option<NewPeak>>>) -> HashMap<Uuid, Arc<ChiaMessageHandler>> {
+    HashMap::from([(
+        Uuid::new_v4(),
+        Arc::new(ChiaMessageHandler::new(
+            Arc::new(ChiaMessageFilter {
+                msg_type: Some(ProtocolMessageTypes::NewPeak),
+                id: None,
+            }),
+            Arc::new(NewPeakHandle { peak }),
+        )),
+    )])
+}
+
+//The request helpers return None when the peer rejects the request or has nothing to send
+
+pub async fn request_block(
+    connection: &RwLock<WebsocketConnection>,
+    height: u32,
+    include_transaction_block: bool,
+    protocol_version: ChiaProtocolVersion,
+    timeout: Duration,
+) -> Result<Option<FullBlock>, Error> {
+    let request = connection.read().await.request::<_, RespondBlock>(
+        &RequestBlock {
+            height,
+            include_transaction_block,
+        },
+        protocol_version,
+        timeout,
+    );
+    Ok(request.await?.ok().map(|r| r.block))
+}
+
+pub async fn request_blocks(
+    connection: &RwLock<WebsocketConnection>,
+    start_height: u32,
+    end_height: u32,
+    include_transaction_block: bool,
+    protocol_version: ChiaProtocolVersion,
+    timeout: Duration,
+) -> Result<Option<Vec<FullBlock>>, Error> {
+    let request = connection.read().await.request::<_, RespondBlocks>(
+        &RequestBlocks {
+            start_height,
+            end_height,
+            include_transaction_block,
+        },
+        protocol_version,
+        timeout,
+    );
+    Ok(request.await?.ok().map(|r| r.blocks))
+}
+
+pub async fn request_puzzle_solution(
+    connection: &RwLock<WebsocketConnection>,
+    coin_name: Bytes32,
+    height: u32,
+    protocol_version: ChiaProtocolVersion,
+    timeout: Duration,
+) -> Result<Option<PuzzleSolutionResponse>, Error> {
+    let request = connection.read().await.request::<_, RespondPuzzleSolution>(
+        &RequestPuzzleSolution { coin_name, height },
+        protocol_version,
+        timeout,
+    );
+    Ok(request.await?.ok().map(|r| r.response))
+}
+
+pub async fn request_peers(
+    connection: &RwLock<WebsocketConnection>,
+    protocol_version: ChiaProtocolVersion,
+    timeout: Duration,
+) -> Result<Option<Vec<TimestampedPeerInfo>>, Error> {
+    let request = connection.read().await.request::<_, RespondPeers>(
+        &RequestPeers {},
+        protocol_version,
+        timeout,
+    );
+    Ok(request.await?.ok().map(|r| r.peer_list))
 }
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::protocols::full_node::NewPeak;
+use dg_xch_core::protocols::{ChiaMessage, MessageHandler, PeerMap};
+use dg_xch_serialize::{ChiaProtocolVersion, ChiaSerialize};
+use log::debug;
+use std::io::{Cursor, Error};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct NewPeakHandle {
+    pub peak: Arc<RwLock<Option<NewPeak>>>,
+}
+#[async_trait]
+impl MessageHandler for NewPeakHandle {
+    async fn handle(
+        &self,
+        msg: Arc<ChiaMessage>,
+        peer_id: Arc<Bytes32>,
+        peers: PeerMap,
+    ) -> Result<(), Error> {
+        let mut cursor = Cursor::new(&msg.data);
+        let peer = peers.read().await.get(&peer_id).cloned();
+        let protocol_version = if let Some(peer) = peer.as_ref() {
+            *peer.protocol_version.read().await
+        } else {
+            ChiaProtocolVersion::default()
+        };
+        let new_peak = NewPeak::from_bytes(&mut cursor, protocol_version)?;
+        debug!(
+            "New peak {} at height {}",
+            new_peak.header_hash, new_peak.height
+        );
+        let mut peak = self.peak.write().await;
+        //Peaks can arrive out of order, only keep the heaviest one
+        if peak
+            .as_ref()
+            .map(|p| new_peak.weight > p.weight)
+            .unwrap_or(true)
+        {
+            *peak = Some(new_peak);
+        }
+        Ok(())
+    }
+}
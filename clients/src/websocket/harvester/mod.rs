@@ -1,8 +1,10 @@
 use crate::websocket::harvester::harvester_handshake::HarvesterHandshakeHandle;
 use crate::websocket::harvester::new_signage_point_harvester::NewSignagePointHarvesterHandle;
 use crate::websocket::harvester::request_signatures::RequestSignaturesHandle;
+use crate::websocket::supervisor::{ReconnectPolicy, SupervisedWsClient};
 use crate::websocket::{WsClient, WsClientConfig};
-use dg_xch_core::consensus::constants::{ConsensusConstants, CONSENSUS_CONSTANTS_MAP, MAINNET};
+use dg_xch_core::consensus::constants::ConsensusConstants;
+use dg_xch_core::consensus::registry::CONSENSUS_CONSTANTS_REGISTRY;
 use dg_xch_core::protocols::harvester::HarvesterState;
 use dg_xch_core::protocols::{
     ChiaMessageFilter, ChiaMessageHandler, NodeType, ProtocolMessageTypes,
@@ -29,9 +31,7 @@ impl HarvesterClient {
         harvester_state: Arc<RwLock<HarvesterState>>,
         run: Arc<AtomicBool>,
     ) -> Result<Self, Error> {
-        let constants = CONSENSUS_CONSTANTS_MAP
-            .get(&client_config.network_id)
-            .unwrap_or(&MAINNET);
+        let constants = CONSENSUS_CONSTANTS_REGISTRY.get_or_err(&client_config.network_id)?;
         let handles = Arc::new(RwLock::new(handles(
             constants,
             plot_manager.clone(),
@@ -42,6 +42,24 @@ impl HarvesterClient {
         Ok(HarvesterClient { client })
     }
 
+    pub fn supervised<T: PlotManagerAsync + Send + Sync + 'static>(
+        client_config: Arc<WsClientConfig>,
+        plot_manager: Arc<RwLock<T>>,
+        plots_ready: Arc<AtomicBool>,
+        harvester_state: Arc<RwLock<HarvesterState>>,
+        run: Arc<AtomicBool>,
+        policy: ReconnectPolicy,
+    ) -> SupervisedWsClient {
+        let constants = CONSENSUS_CONSTANTS_REGISTRY.get_or_mainnet(&client_config.network_id);
+        let handles = Arc::new(RwLock::new(handles(
+            constants,
+            plot_manager,
+            plots_ready,
+            harvester_state,
+        )));
+        SupervisedWsClient::new(client_config, NodeType::Harvester, handles, run, policy)
+    }
+
     pub async fn join(self) -> Result<(), Error> {
         self.client.join().await
     }
@@ -52,7 +70,7 @@ impl HarvesterClient {
 }
 
 fn handles<T: PlotManagerAsync + Send + Sync + 'static>(
-    constants: &'static ConsensusConstants,
+    constants: Arc<ConsensusConstants>,
     plot_manager: Arc<RwLock<T>>,
     plots_ready: Arc<AtomicBool>,
     harvester_state: Arc<RwLock<HarvesterState>>,
@@ -34,7 +34,7 @@ struct PlotCounts {
     compressed_total: Arc<AtomicUsize>,
 }
 pub struct NewSignagePointHarvesterHandle<T: PlotManagerAsync> {
-    pub constants: &'static ConsensusConstants,
+    pub constants: Arc<ConsensusConstants>,
     pub plot_manager: Arc<RwLock<T>>,
     pub plots_ready: Arc<AtomicBool>,
 }
@@ -61,7 +61,7 @@ impl<T: PlotManagerAsync + Send + Sync> MessageHandler for NewSignagePointHarves
         trace!("{:#?}", &harvester_point);
         let plot_counts = Arc::new(PlotCounts::default());
         let harvester_point = Arc::new(harvester_point);
-        let constants = Arc::new(self.constants);
+        let constants = self.constants.clone();
         let mut jobs = FuturesUnordered::new();
         self.plot_manager.read().await.plots().iter().map(|(path_info, plot_info)|{
             (path_info.clone(), plot_info.clone())
@@ -0,0 +1,56 @@
+use crate::websocket::{WsClient, WsClientConfig};
+use dg_xch_core::blockchain::peer_info::TimestampedPeerInfo;
+use dg_xch_core::protocols::introducer::{RequestPeersIntroducer, RespondPeersIntroducer};
+use dg_xch_core::protocols::{NodeType, PeerResponse};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+pub struct IntroducerClient {
+    pub client: WsClient,
+}
+impl IntroducerClient {
+    pub async fn new(
+        client_config: Arc<WsClientConfig>,
+        run: Arc<AtomicBool>,
+    ) -> Result<Self, Error> {
+        let handles = Arc::new(RwLock::new(HashMap::new()));
+        let client = WsClient::new(client_config, NodeType::FullNode, handles, run).await?;
+        Ok(IntroducerClient { client })
+    }
+
+    pub async fn request_peers(
+        &self,
+        timeout: Duration,
+    ) -> Result<Vec<TimestampedPeerInfo>, Error> {
+        let request = self
+            .client
+            .connection
+            .read()
+            .await
+            .request::<_, RespondPeersIntroducer>(
+                &RequestPeersIntroducer {},
+                self.client.client_config.protocol_version,
+                timeout,
+            );
+        match request.await? {
+            PeerResponse::Response(resp) => Ok(resp.peer_list),
+            _ => Err(Error::new(
+                ErrorKind::NotFound,
+                "Introducer did not return any peers",
+            )),
+        }
+    }
+
+    pub async fn join(self) -> Result<(), Error> {
+        self.client.connection.write().await.shutdown().await?;
+        self.client.join().await
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.client.handle.is_finished()
+    }
+}
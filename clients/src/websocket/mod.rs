@@ -1,12 +1,22 @@
+pub mod daemon;
+pub mod discovery;
 pub mod farmer;
 pub mod full_node;
 pub mod harvester;
+pub mod introducer;
+pub mod supervisor;
+pub mod timelord;
 pub mod wallet;
 
 use crate::ClientSSLConfig;
 use async_trait::async_trait;
 use dg_xch_core::blockchain::sized_bytes::{Bytes32, SizedBytes};
-use dg_xch_core::protocols::shared::{Handshake, NoCertificateVerification, CAPABILITIES};
+use dg_xch_core::config::CaSsl;
+use dg_xch_core::protocols::rate_limits::PeerPolicy;
+use dg_xch_core::protocols::shared::{
+    CaCertificateVerification, Capabilities, Capability, Handshake, NoCertificateVerification,
+    PinnedCertificateVerification,
+};
 use dg_xch_core::protocols::{
     ChiaMessage, ChiaMessageFilter, ChiaMessageHandler, MessageHandler, NodeType, SocketPeer,
     WebsocketConnection,
@@ -19,10 +29,12 @@ use dg_xch_core::ssl::{
 use dg_xch_serialize::{hash_256, ChiaProtocolVersion, ChiaSerialize};
 use log::debug;
 use reqwest::header::{HeaderName, HeaderValue};
-use rustls::{Certificate, ClientConfig, PrivateKey};
+use rustls::client::ServerCertVerifier;
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::io::{Cursor, Error, ErrorKind};
+use std::net::IpAddr;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -32,8 +44,9 @@ use tokio::select;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
-use tokio_tungstenite::{connect_async_tls_with_config, Connector};
+use tokio_tungstenite::{connect_async_tls_with_config, Connector, MaybeTlsStream};
 use urlencoding::encode;
 use uuid::Uuid;
 
@@ -53,6 +66,69 @@ fn test_version() {
     println!("{}", version());
 }
 
+#[test]
+fn test_private_ca_server_verification() {
+    use dg_xch_core::ssl::make_ca_cert;
+    use std::time::SystemTime;
+    let dir = std::env::temp_dir().join(format!("dg_private_ca_{}", Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let ca_crt_path = dir.join("private_ca.crt");
+    let (ca_crt, ca_key) = make_ca_cert(&ca_crt_path, &dir.join("private_ca.key")).unwrap();
+    let (server_crt, _) = generate_ca_signed_cert_data(&ca_crt, &ca_key).unwrap();
+    let server_cert = load_certs_from_bytes(&server_crt).unwrap().remove(0);
+    //Anyone can mint this one, the public Chia CA key ships with every node
+    let (public_crt, _) =
+        generate_ca_signed_cert_data(CHIA_CA_CRT.as_bytes(), CHIA_CA_KEY.as_bytes()).unwrap();
+    let public_cert = load_certs_from_bytes(&public_crt).unwrap().remove(0);
+    let config = |ssl_info: Option<ClientSSLConfig>, server_verification| WsClientConfig {
+        host: "127.0.0.1".to_string(),
+        port: 8444,
+        network_id: "mainnet".to_string(),
+        ssl_info,
+        software_version: None,
+        protocol_version: ChiaProtocolVersion::default(),
+        additional_headers: None,
+        peer_policy: None,
+        server_verification,
+        capabilities: Default::default(),
+    };
+    let verify = |client_config: &WsClientConfig, signing_ca: Option<&[u8]>, cert| {
+        server_cert_verifier(client_config, signing_ca)
+            .unwrap()
+            .verify_server_cert(
+                cert,
+                &[],
+                &rustls::ServerName::try_from("chia.net").unwrap(),
+                &mut std::iter::empty(),
+                &[],
+                SystemTime::now(),
+            )
+            .is_ok()
+    };
+    //The default only trusts the private CA from ssl_info and refuses to guess without one
+    let ssl_info = ClientSSLConfig {
+        ssl_crt_path: String::new(),
+        ssl_key_path: String::new(),
+        ssl_ca_crt_path: ca_crt_path.to_string_lossy().to_string(),
+    };
+    let private = config(Some(ssl_info), ServerVerification::default());
+    assert!(verify(&private, None, &server_cert));
+    assert!(!verify(&private, None, &public_cert));
+    let missing = config(None, ServerVerification::default());
+    assert_eq!(
+        server_cert_verifier(&missing, Some(&ca_crt))
+            .err()
+            .map(|e| e.kind()),
+        Some(ErrorKind::InvalidInput)
+    );
+    //Public peers are only trusted when asked for, along with the CA that signed our certificate
+    let public = config(None, ServerVerification::PublicChiaCa);
+    assert!(verify(&public, None, &public_cert));
+    assert!(!verify(&public, None, &server_cert));
+    assert!(verify(&public, Some(&ca_crt), &server_cert));
+    fs::remove_dir_all(dir).unwrap();
+}
+
 pub struct WsClient {
     pub connection: Arc<RwLock<WebsocketConnection>>,
     pub client_config: Arc<WsClientConfig>,
@@ -66,37 +142,7 @@ impl WsClient {
         message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
         run: Arc<AtomicBool>,
     ) -> Result<Self, Error> {
-        let (certs, key, cert_str) = if let Some(ssl_info) = &client_config.ssl_info {
-            (
-                load_certs(&ssl_info.ssl_crt_path)?,
-                load_private_key(&ssl_info.ssl_key_path)?,
-                fs::read(&ssl_info.ssl_crt_path)?,
-            )
-        } else if let (Some(crt), Some(key)) = (
-            env::var("PRIVATE_CA_CRT").ok(),
-            env::var("PRIVATE_CA_KEY").ok(),
-        ) {
-            let (cert_bytes, key_bytes) =
-                generate_ca_signed_cert_data(crt.as_bytes(), key.as_bytes()).map_err(|e| {
-                    Error::new(ErrorKind::Other, format!("OpenSSL Errors: {:?}", e))
-                })?;
-            (
-                load_certs_from_bytes(&cert_bytes)?,
-                load_private_key_from_bytes(&key_bytes)?,
-                cert_bytes,
-            )
-        } else {
-            let (cert_bytes, key_bytes) =
-                generate_ca_signed_cert_data(CHIA_CA_CRT.as_bytes(), CHIA_CA_KEY.as_bytes())
-                    .map_err(|e| {
-                        Error::new(ErrorKind::Other, format!("OpenSSL Errors: {:?}", e))
-                    })?;
-            (
-                load_certs_from_bytes(&cert_bytes)?,
-                load_private_key_from_bytes(&key_bytes)?,
-                cert_bytes,
-            )
-        };
+        let (certs, key, cert_str, signing_ca) = load_client_certs(&client_config)?;
         Self::build(
             client_config,
             node_type,
@@ -105,6 +151,7 @@ impl WsClient {
             certs,
             key,
             &cert_str,
+            signing_ca.as_deref(),
         )
         .await
     }
@@ -133,10 +180,12 @@ impl WsClient {
             certs,
             key,
             &cert_str,
+            Some(cert_data),
         )
         .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn build(
         client_config: Arc<crate::websocket::WsClientConfig>,
         node_type: NodeType,
@@ -145,7 +194,50 @@ impl WsClient {
         certs: Vec<Certificate>,
         key: PrivateKey,
         cert_str: &[u8],
+        signing_ca: Option<&[u8]>,
     ) -> Result<Self, Error> {
+        let ws_client = Self::connect(
+            client_config,
+            message_handlers,
+            run,
+            certs,
+            key,
+            cert_str,
+            signing_ca,
+        )
+        .await?;
+        let protocol_version = ws_client.client_config.protocol_version;
+        ws_client
+            .perform_handshake(node_type, protocol_version)
+            .await?;
+        Ok(ws_client)
+    }
+
+    async fn connect(
+        client_config: Arc<crate::websocket::WsClientConfig>,
+        message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+        run: Arc<AtomicBool>,
+        certs: Vec<Certificate>,
+        key: PrivateKey,
+        cert_str: &[u8],
+        signing_ca: Option<&[u8]>,
+    ) -> Result<Self, Error> {
+        let is_banned = |ip: &IpAddr| {
+            client_config
+                .peer_policy
+                .as_ref()
+                .is_some_and(|p| p.bans.is_banned(ip))
+        };
+        if client_config
+            .host
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| is_banned(&ip))
+        {
+            return Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                format!("Peer {} is banned", client_config.host),
+            ));
+        }
         let mut request = format!("wss://{}:{}/ws", client_config.host, client_config.port)
             .into_client_request()
             .map_err(|e| {
@@ -182,6 +274,7 @@ impl WsClient {
             })?,
         );
         let peer_id = Arc::new(Bytes32::new(&hash_256(&certs[0].0)));
+        let verifier = server_cert_verifier(&client_config, signing_ca)?;
         let (stream, _) = connect_async_tls_with_config(
             request,
             None,
@@ -189,7 +282,7 @@ impl WsClient {
             Some(Connector::Rustls(Arc::new(
                 ClientConfig::builder()
                     .with_safe_defaults()
-                    .with_custom_certificate_verifier(Arc::new(NoCertificateVerification {}))
+                    .with_custom_certificate_verifier(verifier)
                     .with_client_auth_cert(certs, key)
                     .map_err(|e| {
                         Error::new(ErrorKind::Other, format!("Error Building Client: {:?}", e))
@@ -197,12 +290,30 @@ impl WsClient {
             ))),
         )
         .await
-        .map_err(|e| {
-            Error::new(
+        .map_err(|e| match e {
+            tungstenite::Error::Io(e) if e.kind() == ErrorKind::InvalidData => Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Server certificate for {}:{} rejected ({:?}): {e}",
+                    client_config.host, client_config.port, client_config.server_verification
+                ),
+            ),
+            e => Error::new(
                 ErrorKind::Other,
                 format!("Error Connecting Client: {:?}", e),
-            )
+            ),
         })?;
+        let peer_addr = match stream.get_ref() {
+            MaybeTlsStream::Plain(s) => s.peer_addr().ok(),
+            MaybeTlsStream::Rustls(s) => s.get_ref().0.peer_addr().ok(),
+            _ => None,
+        };
+        if let Some(addr) = peer_addr.filter(|a| is_banned(&a.ip())) {
+            return Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                format!("Peer {} is banned", addr.ip()),
+            ));
+        }
         let peers = Arc::new(RwLock::new(HashMap::new()));
         let (ws_con, mut stream) = WebsocketConnection::new(
             WebsocketMsgStream::Tls(stream),
@@ -210,27 +321,28 @@ impl WsClient {
             peer_id.clone(),
             peers.clone(),
         );
+        if let Some(policy) = client_config.peer_policy.clone() {
+            stream.set_peer_policy(policy);
+        }
+        let capabilities = ws_con.capabilities();
         let connection = Arc::new(RwLock::new(ws_con));
         peers.write().await.insert(
             *peer_id.as_ref(),
             Arc::new(SocketPeer {
+                peer_addr,
                 node_type: Arc::new(RwLock::new(NodeType::Harvester)),
                 protocol_version: Arc::new(RwLock::new(ChiaProtocolVersion::default())),
+                capabilities,
                 websocket: connection.clone(),
             }),
         );
         let handle_run = run.clone();
-        let protocol_version = client_config.protocol_version;
-        let ws_client = WsClient {
+        Ok(WsClient {
             connection,
             client_config,
             handle: tokio::spawn(async move { stream.run(handle_run).await }),
             run,
-        };
-        ws_client
-            .perform_handshake(node_type, protocol_version)
-            .await?;
-        Ok(ws_client)
+        })
     }
 
     pub async fn shutdown(&mut self) -> Result<(), Error> {
@@ -248,12 +360,23 @@ impl WsClient {
         self.handle.is_finished()
     }
 
+    //Whether the server advertised the capability as well, false until the handshake completes
+    pub async fn has_capability(&self, capability: Capability) -> bool {
+        self.connection
+            .read()
+            .await
+            .capabilities()
+            .read()
+            .await
+            .has(capability)
+    }
+
     async fn perform_handshake(
         &self,
         node_type: NodeType,
         chia_protocol_version: ChiaProtocolVersion,
     ) -> Result<Handshake, Error> {
-        oneshot::<Handshake>(
+        let handshake = oneshot::<Handshake>(
             self.connection.clone(),
             ChiaMessage::new(
                 ProtocolMessageTypes::Handshake,
@@ -264,10 +387,7 @@ impl WsClient {
                     software_version: version(),
                     server_port: self.client_config.port,
                     node_type: node_type as u8,
-                    capabilities: CAPABILITIES
-                        .iter()
-                        .map(|e| (e.0, e.1.to_string()))
-                        .collect(),
+                    capabilities: self.client_config.capabilities.to_handshake(),
                 },
                 None,
             ),
@@ -276,10 +396,118 @@ impl WsClient {
             None,
             Some(15000),
         )
-        .await
+        .await?;
+        *self.connection.read().await.capabilities().write().await = self
+            .client_config
+            .capabilities
+            .negotiate(&Capabilities::from_handshake(&handshake.capabilities));
+        Ok(handshake)
     }
 }
 
+//Client certificate chain, key, certificate pem and the pem of the CA that signed it when that CA
+//is not already covered by ssl_info or the public Chia CA, it is only trusted with PublicChiaCa
+type ClientCerts = (Vec<Certificate>, PrivateKey, Vec<u8>, Option<Vec<u8>>);
+
+fn load_client_certs(client_config: &WsClientConfig) -> Result<ClientCerts, Error> {
+    if let Some(ssl_info) = &client_config.ssl_info {
+        Ok((
+            load_certs(&ssl_info.ssl_crt_path)?,
+            load_private_key(&ssl_info.ssl_key_path)?,
+            fs::read(&ssl_info.ssl_crt_path)?,
+            None,
+        ))
+    } else if let (Some(crt), Some(key)) = (
+        env::var("PRIVATE_CA_CRT").ok(),
+        env::var("PRIVATE_CA_KEY").ok(),
+    ) {
+        let (cert_bytes, key_bytes) = generate_ca_signed_cert_data(crt.as_bytes(), key.as_bytes())
+            .map_err(|e| Error::new(ErrorKind::Other, format!("OpenSSL Errors: {:?}", e)))?;
+        Ok((
+            load_certs_from_bytes(&cert_bytes)?,
+            load_private_key_from_bytes(&key_bytes)?,
+            cert_bytes,
+            Some(crt.into_bytes()),
+        ))
+    } else {
+        let (cert_bytes, key_bytes) =
+            generate_ca_signed_cert_data(CHIA_CA_CRT.as_bytes(), CHIA_CA_KEY.as_bytes())
+                .map_err(|e| Error::new(ErrorKind::Other, format!("OpenSSL Errors: {:?}", e)))?;
+        Ok((
+            load_certs_from_bytes(&cert_bytes)?,
+            load_private_key_from_bytes(&key_bytes)?,
+            cert_bytes,
+            None,
+        ))
+    }
+}
+
+fn server_cert_verifier(
+    client_config: &WsClientConfig,
+    signing_ca: Option<&[u8]>,
+) -> Result<Arc<dyn ServerCertVerifier>, Error> {
+    let roots = |certs: Vec<Certificate>| {
+        let mut roots = RootCertStore::empty();
+        for cert in certs {
+            roots.add(&cert).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid CA Cert for Client: {:?}", e),
+                )
+            })?;
+        }
+        Ok::<RootCertStore, Error>(roots)
+    };
+    Ok(match &client_config.server_verification {
+        ServerVerification::SslInfoCa => {
+            let ssl_info = client_config.ssl_info.as_ref().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    "No private CA to verify the server with, set ssl_info or choose a ServerVerification",
+                )
+            })?;
+            Arc::new(CaCertificateVerification::new(roots(load_certs(
+                &ssl_info.ssl_ca_crt_path,
+            )?)?))
+        }
+        ServerVerification::PublicChiaCa => {
+            let mut certs = load_certs_from_bytes(CHIA_CA_CRT.as_bytes())?;
+            if let Some(ssl_info) = &client_config.ssl_info {
+                certs.extend(load_certs(&ssl_info.ssl_ca_crt_path)?);
+            }
+            if let Some(signing_ca) = signing_ca {
+                certs.extend(load_certs_from_bytes(signing_ca)?);
+            }
+            Arc::new(CaCertificateVerification::new(roots(certs)?))
+        }
+        ServerVerification::PrivateCa(ca) => {
+            Arc::new(CaCertificateVerification::new(roots(load_certs(&ca.crt)?)?))
+        }
+        ServerVerification::NodeId(node_id) => {
+            Arc::new(PinnedCertificateVerification { node_id: *node_id })
+        }
+        ServerVerification::Insecure => Arc::new(NoCertificateVerification {}),
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum ServerVerification {
+    //Trusts only the private CA from ssl_info, connecting fails without one
+    #[default]
+    SslInfoCa,
+    //Also trusts the public Chia CA and the CA that signed the client certificate, from
+    //PRIVATE_CA_CRT or WsClient::with_ca. The public CA key is published so any node can mint a
+    //certificate that passes, only use this for public peers
+    PublicChiaCa,
+    //Trusts only certificates signed by this CA
+    PrivateCa(CaSsl),
+    //Trusts only the node whose certificate hashes to this id
+    NodeId(Bytes32),
+    //Accepts any certificate, the connection can be intercepted
+    Insecure,
+}
+
+#[derive(Debug, Clone)]
 pub struct WsClientConfig {
     pub host: String,
     pub port: u16,
@@ -289,6 +517,11 @@ pub struct WsClientConfig {
     pub software_version: Option<String>,
     pub protocol_version: ChiaProtocolVersion,
     pub additional_headers: Option<HashMap<String, String>>,
+    //Rate limits and bans applied to messages from the server, no limits are enforced if None
+    pub peer_policy: Option<Arc<PeerPolicy>>,
+    pub server_verification: ServerVerification,
+    //Capabilities advertised in the handshake, only those the server also advertises are used
+    pub capabilities: Capabilities,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -0,0 +1,299 @@
+use crate::websocket::{load_client_certs, WsClient, WsClientConfig};
+use dg_xch_core::protocols::{ChiaMessageHandler, NodeType, WebsocketConnection};
+use log::{info, warn};
+use rand::Rng;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::select;
+use tokio::sync::broadcast;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    Connected { attempt: u32 },
+    ConnectFailed { attempt: u32, error: String },
+    HandshakeFailed { attempt: u32, error: String },
+    Disconnected,
+    Stopped,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    //Fraction of the delay that is randomized, 0.2 gives +/- 20%
+    pub jitter: f64,
+    pub max_attempts: Option<u32>,
+}
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+        }
+    }
+}
+impl ReconnectPolicy {
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self.initial_delay.as_secs_f64()
+            * self
+                .multiplier
+                .powi(attempt.saturating_sub(1).min(64) as i32);
+        let capped = base.min(self.max_delay.as_secs_f64());
+        let jitter =
+            capped * self.jitter.clamp(0.0, 1.0) * rand::thread_rng().gen_range(-1.0..=1.0);
+        Duration::from_secs_f64((capped + jitter).max(0.0))
+    }
+}
+
+pub struct SupervisedWsClient {
+    pub client_config: Arc<WsClientConfig>,
+    pub message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+    connection: Arc<RwLock<Option<Arc<RwLock<WebsocketConnection>>>>>,
+    events: broadcast::Sender<ConnectionEvent>,
+    handle: JoinHandle<()>,
+    run: Arc<AtomicBool>,
+}
+impl SupervisedWsClient {
+    pub fn new(
+        client_config: Arc<WsClientConfig>,
+        node_type: NodeType,
+        message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+        run: Arc<AtomicBool>,
+        policy: ReconnectPolicy,
+    ) -> Self {
+        let (events, _) = broadcast::channel(64);
+        let connection = Arc::new(RwLock::new(None));
+        let handle = tokio::spawn(supervise(
+            client_config.clone(),
+            node_type,
+            message_handlers.clone(),
+            run.clone(),
+            policy,
+            connection.clone(),
+            events.clone(),
+        ));
+        Self {
+            client_config,
+            message_handlers,
+            connection,
+            events,
+            handle,
+            run,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.events.subscribe()
+    }
+
+    //The current connection, None while the supervisor is reconnecting
+    pub async fn connection(&self) -> Option<Arc<RwLock<WebsocketConnection>>> {
+        self.connection.read().await.clone()
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        self.connection.read().await.is_some()
+    }
+
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        self.run.store(false, Ordering::Relaxed);
+        if let Some(connection) = self.connection.write().await.take() {
+            connection.write().await.shutdown().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn join(self) -> Result<(), Error> {
+        self.handle.await.map_err(|e| {
+            Error::new(
+                ErrorKind::Interrupted,
+                format!("Failed to join supervisor: {:?}", e),
+            )
+        })
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+async fn supervise(
+    client_config: Arc<WsClientConfig>,
+    node_type: NodeType,
+    message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+    run: Arc<AtomicBool>,
+    policy: ReconnectPolicy,
+    current: Arc<RwLock<Option<Arc<RwLock<WebsocketConnection>>>>>,
+    events: broadcast::Sender<ConnectionEvent>,
+) {
+    //Failed attempts since the last successful connection
+    let mut attempt = 0u32;
+    let mut reconnecting = false;
+    while run.load(Ordering::Relaxed) {
+        if attempt > 0 || reconnecting {
+            if policy.max_attempts.map(|m| attempt >= m).unwrap_or(false) {
+                warn!(
+                    "Giving up on {}:{} after {} attempts",
+                    client_config.host, client_config.port, attempt
+                );
+                break;
+            }
+            //Start the backoff at the first step instead of reconnecting in a tight loop
+            let delay = policy.delay(attempt.max(1));
+            info!(
+                "Reconnecting to {}:{} in {:?}",
+                client_config.host, client_config.port, delay
+            );
+            if !sleep_while_running(delay, &run).await {
+                break;
+            }
+        }
+        attempt += 1;
+        let connected = match load_client_certs(&client_config) {
+            Ok((certs, key, cert_str, signing_ca)) => {
+                WsClient::connect(
+                    client_config.clone(),
+                    message_handlers.clone(),
+                    run.clone(),
+                    certs,
+                    key,
+                    &cert_str,
+                    signing_ca.as_deref(),
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        let client = match connected {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to connect: {:?}", e);
+                let _ = events.send(ConnectionEvent::ConnectFailed {
+                    attempt,
+                    error: format!("{:?}", e),
+                });
+                continue;
+            }
+        };
+        if let Err(e) = client
+            .perform_handshake(node_type.clone(), client_config.protocol_version)
+            .await
+        {
+            warn!("Handshake failed: {:?}", e);
+            let _ = client.connection.write().await.shutdown().await;
+            let _ = events.send(ConnectionEvent::HandshakeFailed {
+                attempt,
+                error: format!("{:?}", e),
+            });
+            continue;
+        }
+        info!("Connected to {}:{}", client_config.host, client_config.port);
+        *current.write().await = Some(client.connection.clone());
+        let _ = events.send(ConnectionEvent::Connected { attempt });
+        attempt = 0;
+        let _ = client.handle.await;
+        current.write().await.take();
+        let _ = events.send(ConnectionEvent::Disconnected);
+        reconnecting = true;
+    }
+    current.write().await.take();
+    let _ = events.send(ConnectionEvent::Stopped);
+}
+
+async fn sleep_while_running(delay: Duration, run: &AtomicBool) -> bool {
+    select! {
+        _ = tokio::time::sleep(delay) => run.load(Ordering::Relaxed),
+        _ = async {
+            while run.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        } => false
+    }
+}
+
+#[test]
+fn test_reconnect_delay() {
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(10),
+        multiplier: 2.0,
+        jitter: 0.5,
+        max_attempts: None,
+    };
+    for attempt in 1..20 {
+        let delay = policy.delay(attempt).as_secs_f64();
+        let expected = 2f64.powi(attempt as i32 - 1).min(10.0);
+        assert!(delay >= expected * 0.5 && delay <= expected * 1.5);
+    }
+}
+
+#[tokio::test]
+async fn test_supervise_refused_port() {
+    use dg_xch_serialize::ChiaProtocolVersion;
+    //Bind and drop a listener so nothing accepts on the port
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let client_config = Arc::new(WsClientConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        network_id: "mainnet".to_string(),
+        ssl_info: None,
+        software_version: None,
+        protocol_version: ChiaProtocolVersion::default(),
+        additional_headers: None,
+        peer_policy: None,
+        capabilities: Default::default(),
+        server_verification: crate::websocket::ServerVerification::PublicChiaCa,
+    });
+    let policy = ReconnectPolicy {
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        multiplier: 2.0,
+        jitter: 0.2,
+        max_attempts: Some(2),
+    };
+    let (events, mut receiver) = broadcast::channel(16);
+    let current = Arc::new(RwLock::new(None));
+    tokio::time::timeout(
+        Duration::from_secs(30),
+        supervise(
+            client_config,
+            NodeType::Farmer,
+            Default::default(),
+            Arc::new(AtomicBool::new(true)),
+            policy,
+            current.clone(),
+            events,
+        ),
+    )
+    .await
+    .unwrap();
+    let mut received = vec![];
+    while let Ok(event) = receiver.try_recv() {
+        received.push(event);
+    }
+    assert_eq!(received.len(), 3);
+    assert!(matches!(
+        received[0],
+        ConnectionEvent::ConnectFailed { attempt: 1, .. }
+    ));
+    assert!(matches!(
+        received[1],
+        ConnectionEvent::ConnectFailed { attempt: 2, .. }
+    ));
+    assert_eq!(received[2], ConnectionEvent::Stopped);
+    assert!(current.read().await.is_none());
+}
@@ -0,0 +1,149 @@
+use crate::websocket::supervisor::{ReconnectPolicy, SupervisedWsClient};
+use crate::websocket::timelord::new_peak_timelord::NewPeakTimelordHandle;
+use crate::websocket::timelord::new_unfinished_block_timelord::NewUnfinishedBlockTimelordHandle;
+use crate::websocket::timelord::request_compact_proof_of_time::RequestCompactProofOfTimeHandle;
+use crate::websocket::{WsClient, WsClientConfig};
+use dg_xch_core::protocols::timelord::{
+    NewEndOfSubSlotVDF, NewInfusionPointVDF, NewPeakTimelord, NewSignagePointVDF,
+    NewUnfinishedBlockTimelord, RequestCompactProofOfTime, RespondCompactProofOfTime,
+};
+use dg_xch_core::protocols::{
+    ChiaMessage, ChiaMessageFilter, ChiaMessageHandler, ChiaProtocolMessage, NodeType,
+    ProtocolMessageTypes, WebsocketConnection,
+};
+use dg_xch_serialize::{ChiaProtocolVersion, ChiaSerialize};
+use std::collections::HashMap;
+use std::io::Error;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+pub mod new_peak_timelord;
+pub mod new_unfinished_block_timelord;
+pub mod request_compact_proof_of_time;
+
+//Work pushed to a timelord by its full node, a bluebox only needs the compact proof requests
+#[derive(Debug, Clone)]
+pub enum TimelordWork {
+    NewPeak(NewPeakTimelord),
+    UnfinishedBlock(NewUnfinishedBlockTimelord),
+    CompactProofRequest(RequestCompactProofOfTime),
+}
+
+pub struct TimelordClient {
+    pub client: WsClient,
+}
+impl TimelordClient {
+    pub async fn new(
+        client_config: Arc<WsClientConfig>,
+        work_sender: Sender<TimelordWork>,
+        run: Arc<AtomicBool>,
+    ) -> Result<Self, Error> {
+        let handles = Arc::new(RwLock::new(handles(work_sender)));
+        let client = WsClient::new(client_config, NodeType::Timelord, handles, run).await?;
+        Ok(TimelordClient { client })
+    }
+
+    pub fn supervised(
+        client_config: Arc<WsClientConfig>,
+        work_sender: Sender<TimelordWork>,
+        run: Arc<AtomicBool>,
+        policy: ReconnectPolicy,
+    ) -> SupervisedWsClient {
+        let handles = Arc::new(RwLock::new(handles(work_sender)));
+        SupervisedWsClient::new(client_config, NodeType::Timelord, handles, run, policy)
+    }
+
+    pub async fn respond_compact_proof_of_time(
+        &self,
+        response: &RespondCompactProofOfTime,
+    ) -> Result<(), Error> {
+        self.send(response).await
+    }
+
+    pub async fn new_infusion_point_vdf(&self, vdf: &NewInfusionPointVDF) -> Result<(), Error> {
+        self.send(vdf).await
+    }
+
+    pub async fn new_signage_point_vdf(&self, vdf: &NewSignagePointVDF) -> Result<(), Error> {
+        self.send(vdf).await
+    }
+
+    pub async fn new_end_of_sub_slot_vdf(&self, vdf: &NewEndOfSubSlotVDF) -> Result<(), Error> {
+        self.send(vdf).await
+    }
+
+    async fn send<T: ChiaSerialize + ChiaProtocolMessage>(&self, msg: &T) -> Result<(), Error> {
+        send_message(
+            &self.client.connection,
+            msg,
+            self.client.client_config.protocol_version,
+        )
+        .await
+    }
+
+    pub async fn join(self) -> Result<(), Error> {
+        self.client.connection.write().await.shutdown().await?;
+        self.client.join().await
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.client.handle.is_finished()
+    }
+}
+
+fn handles(work_sender: Sender<TimelordWork>) -> HashMap<Uuid, Arc<ChiaMessageHandler>> {
+    HashMap::from([
+        (
+            Uuid::new_v4(),
+            Arc::new(ChiaMessageHandler::new(
+                Arc::new(ChiaMessageFilter {
+                    msg_type: Some(ProtocolMessageTypes::NewPeakTimelord),
+                    id: None,
+                }),
+                Arc::new(NewPeakTimelordHandle {
+                    work_sender: work_sender.clone(),
+                }),
+            )),
+        ),
+        (
+            Uuid::new_v4(),
+            Arc::new(ChiaMessageHandler::new(
+                Arc::new(ChiaMessageFilter {
+                    msg_type: Some(ProtocolMessageTypes::NewUnfinishedBlockTimelord),
+                    id: None,
+                }),
+                Arc::new(NewUnfinishedBlockTimelordHandle {
+                    work_sender: work_sender.clone(),
+                }),
+            )),
+        ),
+        (
+            Uuid::new_v4(),
+            Arc::new(ChiaMessageHandler::new(
+                Arc::new(ChiaMessageFilter {
+                    msg_type: Some(ProtocolMessageTypes::RequestCompactProofOfTime),
+                    id: None,
+                }),
+                Arc::new(RequestCompactProofOfTimeHandle { work_sender }),
+            )),
+        ),
+    ])
+}
+
+pub async fn send_message<T: ChiaSerialize + ChiaProtocolMessage>(
+    connection: &RwLock<WebsocketConnection>,
+    msg: &T,
+    protocol_version: ChiaProtocolVersion,
+) -> Result<(), Error> {
+    connection
+        .write()
+        .await
+        .send(Message::Binary(
+            ChiaMessage::new(T::msg_type(), protocol_version, msg, None).to_bytes(protocol_version),
+        ))
+        .await
+}
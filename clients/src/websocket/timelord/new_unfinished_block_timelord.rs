@@ -0,0 +1,40 @@
+use crate::websocket::timelord::TimelordWork;
+use async_trait::async_trait;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::protocols::timelord::NewUnfinishedBlockTimelord;
+use dg_xch_core::protocols::{ChiaMessage, MessageHandler, PeerMap};
+use dg_xch_serialize::{ChiaProtocolVersion, ChiaSerialize};
+use log::debug;
+use std::io::{Cursor, Error, ErrorKind};
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
+
+pub struct NewUnfinishedBlockTimelordHandle {
+    pub work_sender: Sender<TimelordWork>,
+}
+#[async_trait]
+impl MessageHandler for NewUnfinishedBlockTimelordHandle {
+    async fn handle(
+        &self,
+        msg: Arc<ChiaMessage>,
+        peer_id: Arc<Bytes32>,
+        peers: PeerMap,
+    ) -> Result<(), Error> {
+        let mut cursor = Cursor::new(&msg.data);
+        let peer = peers.read().await.get(&peer_id).cloned();
+        let protocol_version = if let Some(peer) = peer.as_ref() {
+            *peer.protocol_version.read().await
+        } else {
+            ChiaProtocolVersion::default()
+        };
+        let work = NewUnfinishedBlockTimelord::from_bytes(&mut cursor, protocol_version)?;
+        debug!(
+            "Received unfinished block at total iterations {}",
+            work.reward_chain_block.total_iters
+        );
+        self.work_sender
+            .send(TimelordWork::UnfinishedBlock(work))
+            .await
+            .map_err(|_| Error::new(ErrorKind::BrokenPipe, "Timelord work receiver closed"))
+    }
+}
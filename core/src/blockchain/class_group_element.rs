@@ -1,4 +1,4 @@
-use crate::blockchain::sized_bytes::Bytes100;
+use crate::blockchain::sized_bytes::{Bytes100, SizedBytes};
 use dg_xch_macros::ChiaSerial;
 use serde::{Deserialize, Serialize};
 
@@ -6,3 +6,13 @@ use serde::{Deserialize, Serialize};
 pub struct ClassgroupElement {
     pub data: Bytes100,
 }
+impl ClassgroupElement {
+    //Compressed encoding of the generator form (2, 1, c), the input of every VDF chain
+    pub fn get_default_element() -> Self {
+        let mut data = [0u8; 100];
+        data[0] = 0x08;
+        Self {
+            data: Bytes100::new(&data),
+        }
+    }
+}
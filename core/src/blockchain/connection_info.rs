@@ -0,0 +1,20 @@
+use crate::blockchain::sized_bytes::Bytes32;
+use serde::{Deserialize, Serialize};
+
+//A peer connection as listed by the get_connections endpoint every service exposes
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct ConnectionInfo {
+    #[serde(rename = "type")]
+    pub node_type: u8,
+    pub local_port: Option<u16>,
+    pub peer_host: String,
+    pub peer_port: u16,
+    pub peer_server_port: Option<u16>,
+    pub node_id: Bytes32,
+    pub creation_time: f64,
+    #[serde(default)]
+    pub bytes_read: Option<u64>,
+    #[serde(default)]
+    pub bytes_written: Option<u64>,
+    pub last_message_time: f64,
+}
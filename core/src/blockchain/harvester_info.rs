@@ -0,0 +1,59 @@
+use crate::blockchain::sized_bytes::Bytes32;
+use crate::protocols::harvester::Plot;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct HarvesterConnection {
+    pub node_id: Bytes32,
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct HarvesterSyncStatus {
+    pub initial: bool,
+    pub plot_files_processed: u32,
+    pub plot_files_total: u32,
+}
+
+//A harvester as seen by the farmer it is connected to
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct HarvesterInfo {
+    pub connection: HarvesterConnection,
+    pub plots: Vec<Plot>,
+    pub failed_to_open_filenames: Vec<String>,
+    pub no_key_filenames: Vec<String>,
+    pub duplicates: Vec<String>,
+    pub total_plot_size: u64,
+    #[serde(default)]
+    pub total_effective_plot_size: u64,
+    pub syncing: Option<HarvesterSyncStatus>,
+    pub last_sync_time: Option<f64>,
+    #[serde(default)]
+    pub harvesting_mode: Option<u8>,
+}
+
+//Same as HarvesterInfo with the file lists replaced by their lengths
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct HarvesterSummary {
+    pub connection: HarvesterConnection,
+    pub plots: u64,
+    pub failed_to_open_filenames: u64,
+    pub no_key_filenames: u64,
+    pub duplicates: u64,
+    pub total_plot_size: u64,
+    #[serde(default)]
+    pub total_effective_plot_size: u64,
+    pub syncing: Option<HarvesterSyncStatus>,
+    pub last_sync_time: Option<f64>,
+    #[serde(default)]
+    pub harvesting_mode: Option<u8>,
+}
+
+//Plots as reported by the harvester itself
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct HarvesterPlots {
+    pub plots: Vec<Plot>,
+    pub failed_to_open_filenames: Vec<String>,
+    pub not_found_filenames: Vec<String>,
+}
@@ -9,26 +9,36 @@ pub mod coin_record;
 pub mod coin_spend;
 pub mod condition_opcode;
 pub mod condition_with_args;
+pub mod connection_info;
 pub mod end_of_subslot_bundle;
 pub mod foliage;
 pub mod foliage_block_data;
 pub mod foliage_transaction_block;
 pub mod full_block;
+pub mod harvester_info;
 pub mod header_block;
 pub mod infused_challenge_chain_subslot;
 pub mod mempool_item;
 pub mod network_info;
+pub mod nft_info;
+pub mod notification;
 pub mod npc;
 pub mod npc_result;
+pub mod paginated_plots;
+pub mod peer_counts;
 pub mod peer_info;
 pub mod pending_payment;
+pub mod pool_state_info;
 pub mod pool_target;
+pub mod pool_wallet_info;
 pub mod proof_of_space;
 pub mod reward_chain_block;
 pub mod reward_chain_block_unfinished;
 pub mod reward_chain_subslot;
+pub mod reward_targets;
 pub mod signage_point;
 pub mod signage_point_or_eos;
+pub mod signage_point_proofs;
 pub mod sized_bytes;
 pub mod spend;
 pub mod spend_bundle;
@@ -37,6 +47,7 @@ pub mod sub_epoch_summary;
 pub mod subslot_bundle;
 pub mod subslot_proofs;
 pub mod sync;
+pub mod trade_record;
 pub mod transaction_peer;
 pub mod transaction_record;
 pub mod transactions_info;
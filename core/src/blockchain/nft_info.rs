@@ -0,0 +1,37 @@
+use crate::blockchain::sized_bytes::Bytes32;
+use crate::blockchain::unsized_bytes::UnsizedBytes;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct NftInfo {
+    #[serde(default)]
+    pub nft_id: String,
+    pub launcher_id: Bytes32,
+    pub nft_coin_id: Bytes32,
+    #[serde(default)]
+    pub nft_coin_confirmation_height: u32,
+    pub owner_did: Option<Bytes32>,
+    pub royalty_percentage: Option<u16>,
+    pub royalty_puzzle_hash: Option<Bytes32>,
+    pub data_uris: Vec<String>,
+    pub data_hash: UnsizedBytes,
+    pub metadata_uris: Vec<String>,
+    pub metadata_hash: UnsizedBytes,
+    pub license_uris: Vec<String>,
+    pub license_hash: UnsizedBytes,
+    pub edition_total: u64,
+    pub edition_number: u64,
+    pub updater_puzhash: Bytes32,
+    pub chain_info: String,
+    pub mint_height: u32,
+    pub supports_did: bool,
+    pub p2_address: Bytes32,
+    #[serde(default)]
+    pub pending_transaction: bool,
+    #[serde(default)]
+    pub minter_did: Option<Bytes32>,
+    #[serde(default)]
+    pub launcher_puzhash: Bytes32,
+    #[serde(default)]
+    pub off_chain_metadata: Option<String>,
+}
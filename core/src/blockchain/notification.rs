@@ -0,0 +1,11 @@
+use crate::blockchain::sized_bytes::Bytes32;
+use crate::blockchain::unsized_bytes::UnsizedBytes;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Notification {
+    pub id: Bytes32,
+    pub message: UnsizedBytes,
+    pub amount: u64,
+    pub height: u32,
+}
@@ -0,0 +1,19 @@
+use crate::blockchain::sized_bytes::Bytes32;
+use serde::{Deserialize, Serialize};
+
+//Matches plots whose field named key contains value, used by get_harvester_plots_valid
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct PlotFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+//One page of a harvester's plots, T is a Plot for valid plots and a filename for the others
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct PaginatedPlots<T> {
+    pub node_id: Bytes32,
+    pub page: u32,
+    pub page_count: u32,
+    pub total_count: u32,
+    pub plots: Vec<T>,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+//Network census kept by a crawler, versions maps a software version to its node count
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct PeerCounts {
+    pub total_last_5_days: u64,
+    pub reliable_nodes: u64,
+    pub ipv4_last_5_days: u64,
+    pub ipv6_last_5_days: u64,
+    pub versions: HashMap<String, u64>,
+}
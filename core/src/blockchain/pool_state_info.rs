@@ -0,0 +1,25 @@
+use crate::blockchain::sized_bytes::Bytes32;
+use crate::config::PoolWalletConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+//Farmer view of a pool it farms to, timestamps are unix seconds
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct PoolStateInfo {
+    pub p2_singleton_puzzle_hash: Bytes32,
+    pub points_found_since_start: u64,
+    pub points_found_24h: Vec<(f64, u64)>,
+    pub points_acknowledged_since_start: u64,
+    pub points_acknowledged_24h: Vec<(f64, u64)>,
+    pub next_farmer_update: f64,
+    pub next_pool_info_update: f64,
+    pub current_points: u64,
+    pub current_difficulty: Option<u64>,
+    pub pool_errors_24h: Vec<(f64, Value)>,
+    pub authentication_token_timeout: Option<u8>,
+    pub pool_config: PoolWalletConfig,
+    #[serde(default)]
+    pub plot_count: u64,
+    #[serde(default)]
+    pub pool_info: Option<Value>,
+}
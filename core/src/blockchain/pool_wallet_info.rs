@@ -0,0 +1,28 @@
+use crate::blockchain::coin::Coin;
+use crate::blockchain::sized_bytes::Bytes32;
+use crate::blockchain::transaction_record::TransactionRecord;
+use crate::clvm::program::SerializedProgram;
+use crate::pool::PoolState;
+use serde::{Deserialize, Serialize};
+
+//State of a plot NFT as tracked by its pool wallet
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct PoolWalletInfo {
+    pub current: PoolState,
+    pub target: Option<PoolState>,
+    pub launcher_coin: Coin,
+    pub launcher_id: Bytes32,
+    pub p2_singleton_puzzle_hash: Bytes32,
+    pub current_inner: SerializedProgram,
+    pub tip_singleton_coin_id: Bytes32,
+    pub singleton_block_height: u32,
+}
+
+//Pool wallet spends return the singleton transaction and an optional fee transaction
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct PoolWalletTransaction {
+    #[serde(default)]
+    pub total_fee: Option<u64>,
+    pub transaction: TransactionRecord,
+    pub fee_transaction: Option<TransactionRecord>,
+}
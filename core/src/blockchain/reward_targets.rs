@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+//Targets are addresses, the key flags are only set when the farmer was asked to search for them
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct RewardTargets {
+    pub farmer_target: String,
+    pub pool_target: String,
+    #[serde(default)]
+    pub have_farmer_sk: Option<bool>,
+    #[serde(default)]
+    pub have_pool_sk: Option<bool>,
+}
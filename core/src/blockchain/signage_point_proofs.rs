@@ -0,0 +1,10 @@
+use crate::blockchain::proof_of_space::ProofOfSpace;
+use crate::protocols::farmer::NewSignagePoint;
+use serde::{Deserialize, Serialize};
+
+//A signage point seen by the farmer and the proofs found for it, keyed by plot identifier
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct SignagePointProofs {
+    pub signage_point: NewSignagePoint,
+    pub proofs: Vec<(String, ProofOfSpace)>,
+}
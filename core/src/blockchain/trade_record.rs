@@ -0,0 +1,34 @@
+use crate::blockchain::coin::Coin;
+use crate::blockchain::sized_bytes::Bytes32;
+use crate::blockchain::unsized_bytes::UnsizedBytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+//Asset amounts are keyed by "xch" or the hex asset id of the CAT
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct OfferSummary {
+    pub offered: HashMap<String, u64>,
+    pub requested: HashMap<String, u64>,
+    pub fees: u64,
+    #[serde(default)]
+    pub infos: Value,
+}
+
+//The wallet RPC sends trade records with the raw offer replaced by its summary
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct TradeRecord {
+    pub confirmed_at_index: u32,
+    pub accepted_at_time: Option<u64>,
+    pub created_at_time: u64,
+    pub is_my_offer: bool,
+    pub sent: u32,
+    pub sent_to: Vec<(String, u8, Option<String>)>,
+    pub taken_offer: Option<UnsizedBytes>,
+    pub coins_of_interest: Vec<Coin>,
+    pub trade_id: Bytes32,
+    pub status: String,
+    pub summary: Option<OfferSummary>,
+    #[serde(default)]
+    pub pending: HashMap<String, u64>,
+}
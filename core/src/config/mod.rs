@@ -1,5 +1,9 @@
+pub mod root;
+
 use crate::blockchain::sized_bytes::{Bytes32, Bytes48};
+use crate::consensus::constants::{ConsensusConstants, MAINNET};
 use crate::consensus::overrides::ConsensusOverrides;
+use crate::consensus::registry::CONSENSUS_CONSTANTS_REGISTRY;
 use num_bigint::BigInt;
 use std::collections::HashMap;
 
@@ -495,6 +499,32 @@ pub struct ChiaConfig {
     #[serde(default)]
     pub simulator: SimulatorConfig,
 }
+impl ChiaConfig {
+    //Address prefix and default full node port of the selected network
+    pub fn network_config(&self) -> Option<&ConfigOverride> {
+        self.network_overrides.config.get(&self.selected_network)
+    }
+    //Constants of the selected network with the configured overrides applied
+    pub fn consensus_constants(&self) -> ConsensusConstants {
+        let overrides = self.network_overrides.constants.get(&self.selected_network);
+        //Custom networks are defined by their overrides alone, only warn when there are none
+        let mut constants = match (
+            CONSENSUS_CONSTANTS_REGISTRY.get(&self.selected_network),
+            overrides,
+        ) {
+            (Some(constants), _) => constants.as_ref().clone(),
+            (None, Some(_)) => MAINNET.as_ref().clone(),
+            (None, None) => CONSENSUS_CONSTANTS_REGISTRY
+                .get_or_mainnet(&self.selected_network)
+                .as_ref()
+                .clone(),
+        };
+        if let Some(overrides) = overrides {
+            overrides.apply(&mut constants);
+        }
+        constants
+    }
+}
 impl Default for ChiaConfig {
     fn default() -> Self {
         ChiaConfig {
@@ -1398,6 +1428,9 @@ pub struct ConfigOverrides {
     pub testnet7: Option<ConfigOverride>,
     pub testnet10: Option<ConfigOverride>,
     pub testnet11: Option<ConfigOverride>,
+    //Any other network name, for simulators and custom testnets
+    #[serde(flatten, default)]
+    pub custom: HashMap<String, ConfigOverride>,
 }
 impl Default for ConfigOverrides {
     fn default() -> Self {
@@ -1438,6 +1471,24 @@ impl Default for ConfigOverrides {
                 address_prefix: Some("txch".to_string()),
                 default_full_node_port: Some(58444),
             }),
+            custom: HashMap::new(),
+        }
+    }
+}
+
+impl ConfigOverrides {
+    pub fn get(&self, network: &str) -> Option<&ConfigOverride> {
+        match network {
+            "mainnet" => self.mainnet.as_ref(),
+            "testnet0" => self.testnet0.as_ref(),
+            "testnet2" => self.testnet2.as_ref(),
+            "testnet3" => self.testnet3.as_ref(),
+            "testnet4" => self.testnet4.as_ref(),
+            "testnet5" => self.testnet5.as_ref(),
+            "testnet7" => self.testnet7.as_ref(),
+            "testnet10" => self.testnet10.as_ref(),
+            "testnet11" => self.testnet11.as_ref(),
+            _ => self.custom.get(network),
         }
     }
 }
@@ -1453,6 +1504,9 @@ pub struct ConstantsOverrides {
     pub testnet7: Option<ConsensusOverrides>,
     pub testnet10: Option<ConsensusOverrides>,
     pub testnet11: Option<ConsensusOverrides>,
+    //Any other network name, for simulators and custom testnets
+    #[serde(flatten, default)]
+    pub custom: HashMap<String, ConsensusOverrides>,
 }
 impl Default for ConstantsOverrides {
     fn default() -> Self {
@@ -1613,6 +1667,24 @@ impl Default for ConstantsOverrides {
                 plot_filter_32_height: Some(16121088),
                 ..Default::default()
             }),
+            custom: HashMap::new(),
+        }
+    }
+}
+
+impl ConstantsOverrides {
+    pub fn get(&self, network: &str) -> Option<&ConsensusOverrides> {
+        match network {
+            "mainnet" => self.mainnet.as_ref(),
+            "testnet0" => self.testnet0.as_ref(),
+            "testnet2" => self.testnet2.as_ref(),
+            "testnet3" => self.testnet3.as_ref(),
+            "testnet4" => self.testnet4.as_ref(),
+            "testnet5" => self.testnet5.as_ref(),
+            "testnet7" => self.testnet7.as_ref(),
+            "testnet10" => self.testnet10.as_ref(),
+            "testnet11" => self.testnet11.as_ref(),
+            _ => self.custom.get(network),
         }
     }
 }
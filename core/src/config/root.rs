@@ -0,0 +1,584 @@
+use crate::config::ChiaConfig;
+use crate::consensus::registry::CONSENSUS_CONSTANTS_REGISTRY;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+pub const CHIA_ROOT_ENV: &str = "CHIA_ROOT";
+pub const DEFAULT_CHIA_ROOT: &str = "~/.chia/mainnet";
+pub const CONFIG_FILE: &str = "config/config.yaml";
+//CHIA_FULL_NODE__PORT=8445 overrides full_node.port, sections are separated by a double underscore
+const ENV_PREFIX: &str = "CHIA_";
+const ENV_SECTION_SEPARATOR: &str = "__";
+const PATH_KEYS: [&str; 8] = [
+    "crt",
+    "key",
+    "private_crt",
+    "private_key",
+    "public_crt",
+    "public_key",
+    "database_path",
+    "log_filename",
+];
+
+//A chia root directory and its config.yaml, the raw document is kept so unknown keys survive a save
+pub struct ChiaRoot {
+    pub path: PathBuf,
+    text: String,
+    value: Value,
+}
+impl ChiaRoot {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = expand_home(&path.as_ref().to_string_lossy());
+        let text = fs::read_to_string(path.join(CONFIG_FILE))?;
+        let value = parse_yaml(&text)?;
+        Ok(ChiaRoot { path, text, value })
+    }
+
+    //Uses CHIA_ROOT when set, otherwise the default mainnet root in the home directory
+    pub fn from_env() -> Result<Self, Error> {
+        Self::load(env::var(CHIA_ROOT_ENV).unwrap_or_else(|_| DEFAULT_CHIA_ROOT.to_string()))
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.path.join(CONFIG_FILE)
+    }
+
+    //Expands ${CHIA_ROOT} and ~, relative paths are relative to the root like the reference node
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        let root = self.path.to_string_lossy();
+        let expanded = path
+            .replace("${CHIA_ROOT}", &root)
+            .replace("$CHIA_ROOT", &root);
+        self.path.join(expand_home(&expanded))
+    }
+
+    pub fn selected_network(&self) -> String {
+        selected_network(&self.value)
+    }
+
+    //The effective config with CHIA_* environment overrides applied and paths resolved against the root,
+    //its network_overrides.constants are registered so every network in the file can be looked up
+    pub fn config(&self) -> Result<ChiaConfig, Error> {
+        self.config_with_env(env::vars())
+    }
+
+    pub fn config_with_env(
+        &self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<ChiaConfig, Error> {
+        let mut value = self.value.clone();
+        apply_env_overrides(&mut value, vars)?;
+        normalize_constants_overrides(&mut value);
+        let network = selected_network(&value);
+        self.resolve_paths(&mut value, &network);
+        let config: ChiaConfig = serde_yaml::from_value(value).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid config {:?}: {}", self.config_path(), e),
+            )
+        })?;
+        //Clients and servers look constants up by network name, so custom networks must be registered
+        CONSENSUS_CONSTANTS_REGISTRY.apply_config(&config.network_overrides.constants);
+        Ok(config)
+    }
+
+    //Sets a value by its dotted path, e.g. full_node.port, creating missing sections
+    pub fn set<T: Serialize>(&mut self, key_path: &str, value: T) -> Result<(), Error> {
+        let value = serde_yaml::to_value(value).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Failed to serialize {key_path}: {}", e),
+            )
+        })?;
+        let path: Vec<String> = key_path.split('.').map(str::to_string).collect();
+        set_path(&mut self.value, &path, value)
+    }
+
+    pub fn get(&self, key_path: &str) -> Option<&Value> {
+        key_path
+            .split('.')
+            .try_fold(&self.value, |value, key| value.get(key))
+    }
+
+    //Changed scalars and new keys are edited in place so comments and anchors are kept, removed
+    //keys or restructured sections are refused rather than rewriting the whole document
+    pub fn save(&mut self) -> Result<(), Error> {
+        let original = parse_yaml(&self.text)?;
+        let text = render(&self.text, &original, &self.value)?;
+        let tmp_path = self.config_path().with_extension("yaml.tmp");
+        fs::write(&tmp_path, &text)?;
+        fs::rename(&tmp_path, self.config_path())?;
+        self.text = text;
+        Ok(())
+    }
+
+    fn resolve_paths(&self, value: &mut Value, network: &str) {
+        match value {
+            Value::Mapping(map) => {
+                for (key, value) in map.iter_mut() {
+                    let key = key.as_str().unwrap_or_default();
+                    match value {
+                        Value::String(path) if PATH_KEYS.contains(&key) => {
+                            if key == "database_path" {
+                                *path = path.replace("CHALLENGE", network);
+                            }
+                            *path = self.resolve(path).to_string_lossy().to_string();
+                        }
+                        value => self.resolve_paths(value, network),
+                    }
+                }
+            }
+            Value::Sequence(values) => {
+                for value in values {
+                    self.resolve_paths(value, network);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn parse_yaml(text: &str) -> Result<Value, Error> {
+    serde_yaml::from_str(text).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid config yaml: {}", e),
+        )
+    })
+}
+
+fn selected_network(value: &Value) -> String {
+    value
+        .get("selected_network")
+        .and_then(Value::as_str)
+        .unwrap_or("mainnet")
+        .to_string()
+}
+
+fn set_path(value: &mut Value, path: &[String], new_value: Value) -> Result<(), Error> {
+    let Some((key, rest)) = path.split_first() else {
+        *value = new_value;
+        return Ok(());
+    };
+    if value.is_null() {
+        *value = Value::Mapping(Mapping::new());
+    }
+    let map = value.as_mapping_mut().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Cannot set {key}, its parent is not a section"),
+        )
+    })?;
+    let entry = map.entry(Value::String(key.clone())).or_insert(Value::Null);
+    set_path(entry, rest, new_value)
+}
+
+fn apply_env_overrides(
+    value: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), Error> {
+    for (name, raw) in vars {
+        let Some(name) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let path: Vec<String> = name
+            .split(ENV_SECTION_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect();
+        //Only names that start with a known top level key, so CHIA_ROOT and friends are left alone
+        if value.get(&path[0]).is_none() {
+            continue;
+        }
+        let parsed = serde_yaml::from_str(&raw).unwrap_or(Value::String(raw));
+        set_path(value, &path, parsed)?;
+    }
+    Ok(())
+}
+
+//The reference config spells constant overrides in upper case
+fn normalize_constants_overrides(value: &mut Value) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, value) in map.iter_mut() {
+                if key.as_str() == Some("network_overrides") {
+                    if let Some(networks) =
+                        value.get_mut("constants").and_then(Value::as_mapping_mut)
+                    {
+                        for (_, overrides) in networks.iter_mut() {
+                            if let Some(overrides) = overrides.as_mapping_mut() {
+                                *overrides = overrides
+                                    .iter()
+                                    .map(|(k, v)| match k.as_str() {
+                                        Some(k) => (Value::String(k.to_lowercase()), v.clone()),
+                                        None => (k.clone(), v.clone()),
+                                    })
+                                    .collect();
+                            }
+                        }
+                    }
+                } else {
+                    normalize_constants_overrides(value);
+                }
+            }
+        }
+        Value::Sequence(values) => {
+            for value in values {
+                normalize_constants_overrides(value);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn render(text: &str, original: &Value, updated: &Value) -> Result<String, Error> {
+    let mut changes = vec![];
+    if !diff(original, updated, &mut vec![], &mut changes) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Config change removes or restructures a section, it cannot be saved without losing comments and anchors",
+        ));
+    }
+    let mut text = text.to_string();
+    for change in &changes {
+        let patched = match change {
+            Change::Scalar(path, value) => patch_scalar(&text, path, &scalar_text(value)?),
+            Change::Insert(path, key, value) => insert_key(&text, path, key, value)?,
+        };
+        text = patched.ok_or_else(|| {
+            let path = match change {
+                Change::Scalar(path, _) => path.join("."),
+                Change::Insert(path, key, _) => path
+                    .iter()
+                    .chain([key])
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join("."),
+            };
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Cannot save {path} in place, it is not a block mapping entry"),
+            )
+        })?;
+    }
+    Ok(text)
+}
+
+enum Change<'a> {
+    Scalar(Vec<String>, &'a Value),
+    Insert(Vec<String>, String, &'a Value),
+}
+
+//Collects changed scalars and added keys, returns false when the change is structural
+fn diff<'a>(
+    original: &Value,
+    updated: &'a Value,
+    path: &mut Vec<String>,
+    changes: &mut Vec<Change<'a>>,
+) -> bool {
+    match (original, updated) {
+        (Value::Mapping(old), Value::Mapping(new)) => {
+            if old.keys().any(|key| !new.contains_key(key)) {
+                return false;
+            }
+            for (key, new_value) in new {
+                let Some(name) = key.as_str() else {
+                    return false;
+                };
+                let Some(old_value) = old.get(key) else {
+                    changes.push(Change::Insert(path.clone(), name.to_string(), new_value));
+                    continue;
+                };
+                path.push(name.to_string());
+                let in_place = diff(old_value, new_value, path, changes);
+                path.pop();
+                if !in_place {
+                    return false;
+                }
+            }
+            true
+        }
+        (old, new) if old == new => true,
+        (Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_), _)
+        | (_, Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_)) => false,
+        (_, new) => {
+            changes.push(Change::Scalar(path.clone(), new));
+            true
+        }
+    }
+}
+
+fn scalar_text(value: &Value) -> Result<String, Error> {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim_end().to_string())
+        .map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Failed to write config: {}", e),
+            )
+        })
+}
+
+//Finds the block mapping line of a dotted path, returning its index and indent
+fn find_key(lines: &[String], path: &[String]) -> Option<(usize, usize)> {
+    let mut keys: Vec<(usize, String)> = vec![];
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let Some((raw_key, _)) = split_key(trimmed) else {
+            continue;
+        };
+        while keys.last().is_some_and(|(depth, _)| *depth >= indent) {
+            keys.pop();
+        }
+        keys.push((
+            indent,
+            raw_key.trim_matches(|c| c == '"' || c == '\'').to_string(),
+        ));
+        if keys.len() == path.len() && keys.iter().zip(path).all(|((_, k), p)| k == p) {
+            return Some((index, indent));
+        }
+    }
+    None
+}
+
+fn join_lines(lines: &[String]) -> String {
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+//Replaces the value of a block mapping line, keeping its indentation, anchor and trailing comment
+fn patch_scalar(text: &str, path: &[String], scalar: &str) -> Option<String> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let (index, indent) = find_key(&lines, path)?;
+    let (raw_key, rest) = split_key(lines[index].trim_start())?;
+    let (value, comment) = split_comment(rest);
+    let value = value.trim();
+    if value.is_empty() || value.starts_with(['|', '>', '[', '{']) {
+        return None;
+    }
+    let anchor = value
+        .split_whitespace()
+        .next()
+        .filter(|token| token.starts_with('&'));
+    let mut patched = format!("{}{}: ", " ".repeat(indent), raw_key);
+    if let Some(anchor) = anchor {
+        patched.push_str(anchor);
+        patched.push(' ');
+    }
+    patched.push_str(scalar);
+    if let Some(comment) = comment {
+        patched.push(' ');
+        patched.push_str(comment);
+    }
+    lines[index] = patched;
+    Some(join_lines(&lines))
+}
+
+//Appends a new key after the last entry of its parent block mapping, at the indent of its siblings
+fn insert_key(
+    text: &str,
+    parent: &[String],
+    key: &str,
+    value: &Value,
+) -> Result<Option<String>, Error> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let (end, indent) = if parent.is_empty() {
+        (lines.len(), 0)
+    } else {
+        let Some((index, parent_indent)) = find_key(&lines, parent) else {
+            return Ok(None);
+        };
+        let Some((_, rest)) = split_key(lines[index].trim_start()) else {
+            return Ok(None);
+        };
+        //Only a plain or anchored block mapping, never an alias or flow mapping
+        let (value, _) = split_comment(rest);
+        if value
+            .split_whitespace()
+            .any(|token| !token.starts_with('&'))
+        {
+            return Ok(None);
+        }
+        let mut end = index + 1;
+        let mut indent = None;
+        for (offset, line) in lines[index + 1..].iter().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let line_indent = line.len() - trimmed.len();
+            if line_indent <= parent_indent {
+                break;
+            }
+            indent.get_or_insert(line_indent);
+            end = index + 2 + offset;
+        }
+        let Some(indent) = indent else {
+            return Ok(None);
+        };
+        (end, indent)
+    };
+    let mut entry = Mapping::new();
+    entry.insert(Value::String(key.to_string()), value.clone());
+    let rendered = serde_yaml::to_string(&entry).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to write config: {}", e),
+        )
+    })?;
+    let inserted = rendered
+        .lines()
+        .map(|line| format!("{}{}", " ".repeat(indent), line));
+    lines.splice(end..end, inserted);
+    Ok(Some(join_lines(&lines)))
+}
+
+fn split_key(line: &str) -> Option<(&str, &str)> {
+    let end = match line.chars().next()? {
+        quote @ ('"' | '\'') => line[1..].find(quote)? + 2,
+        _ => 0,
+    };
+    let colon = end + line[end..].find(':')?;
+    let rest = &line[colon + 1..];
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some((&line[..colon], rest))
+    } else {
+        None
+    }
+}
+
+fn split_comment(value: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if previous.is_whitespace() => return (&value[..i], Some(&value[i..])),
+            _ => {}
+        }
+        previous = c;
+    }
+    (value, None)
+}
+
+#[test]
+fn test_chia_root() {
+    use crate::blockchain::sized_bytes::{Bytes32, SizedBytes};
+    let dir = env::temp_dir().join(format!("chia_root_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(dir.join("config")).unwrap();
+    let text = r#"# Chia config
+selected_network: &selected_network testnet11
+self_hostname: &self_hostname localhost
+daemon_port: 55400
+private_ssl_ca:
+  crt: config/ssl/ca/private_ca.crt
+  key: config/ssl/ca/private_ca.key
+network_overrides: &network_overrides
+  constants:
+    testnet11:
+      DIFFICULTY_CONSTANT_FACTOR: 10052721566054
+      GENESIS_CHALLENGE: 37a90eb5185a9c4439a91ddc98bbadce7b4feba060d50116a067de66bf236615
+      MEMPOOL_BLOCK_BUFFER: 10
+      SUB_SLOT_ITERS_STARTING: 67108864
+    chia_root_testnet:
+      GENESIS_CHALLENGE: 0202020202020202020202020202020202020202020202020202020202020202
+  config:
+    testnet11:
+      address_prefix: txch
+      default_full_node_port: 58444
+full_node:
+  # Port for peers
+  port: 58444 # testnet port
+  selected_network: *selected_network
+  database_path: db/blockchain_v2_CHALLENGE.sqlite
+  unknown_setting: keep me
+overrides_alias: *network_overrides
+"#;
+    fs::write(dir.join(CONFIG_FILE), text).unwrap();
+    let mut root = ChiaRoot::load(&dir).unwrap();
+    assert_eq!(root.selected_network(), "testnet11");
+    let vars = [("CHIA_FULL_NODE__PORT".to_string(), "58445".to_string())];
+    let config = root.config_with_env(vars).unwrap();
+    assert_eq!(config.full_node.port, 58445);
+    let custom = CONSENSUS_CONSTANTS_REGISTRY
+        .get_or_err("chia_root_testnet")
+        .unwrap();
+    assert_eq!(custom.genesis_challenge, Bytes32::new(&[2u8; 32]));
+    assert_eq!(
+        PathBuf::from(&config.private_ssl_ca.crt),
+        dir.join("config/ssl/ca/private_ca.crt")
+    );
+    assert_eq!(
+        PathBuf::from(&config.full_node.database_path),
+        dir.join("db/blockchain_v2_testnet11.sqlite")
+    );
+    assert_eq!(
+        config.network_config().unwrap().address_prefix.as_deref(),
+        Some("txch")
+    );
+    let constants = config.consensus_constants();
+    assert_eq!(constants.sub_slot_iters_starting, 67108864);
+    assert_eq!(
+        constants.genesis_challenge,
+        Bytes32::from("37a90eb5185a9c4439a91ddc98bbadce7b4feba060d50116a067de66bf236615")
+    );
+    root.set("full_node.port", 8444).unwrap();
+    root.set("self_hostname", "127.0.0.1").unwrap();
+    root.save().unwrap();
+    let saved = fs::read_to_string(root.config_path()).unwrap();
+    assert!(saved.contains("  port: 8444 # testnet port"));
+    assert!(saved.contains("self_hostname: &self_hostname 127.0.0.1"));
+    assert!(saved.contains("# Port for peers"));
+    assert!(saved.contains("unknown_setting: keep me"));
+    root.set("full_node.new_setting", true).unwrap();
+    root.set("network_overrides.config.testnet11.new_port", 58446)
+        .unwrap();
+    root.set("new_section.enabled", false).unwrap();
+    root.save().unwrap();
+    let saved = fs::read_to_string(root.config_path()).unwrap();
+    assert!(saved.starts_with("# Chia config\n"));
+    assert!(saved.contains("network_overrides: &network_overrides\n"));
+    assert!(saved.contains("  selected_network: *selected_network\n"));
+    assert!(saved.contains("  port: 8444 # testnet port"));
+    assert!(saved.contains("  unknown_setting: keep me\n  new_setting: true\n"));
+    assert!(saved.contains("      default_full_node_port: 58444\n      new_port: 58446\n"));
+    assert!(saved.contains("overrides_alias: *network_overrides\n"));
+    assert!(saved.ends_with("new_section:\n  enabled: false\n"));
+    //Inserting under an alias or removing a key would lose the document structure
+    root.set("overrides_alias.config.mainnet", "added").unwrap();
+    assert_eq!(root.save().unwrap_err().kind(), ErrorKind::InvalidInput);
+    let mut removed = ChiaRoot::load(&dir).unwrap();
+    if let Some(Value::Mapping(full_node)) = removed.value.get_mut("full_node") {
+        full_node.remove("unknown_setting");
+    }
+    assert_eq!(removed.save().unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(fs::read_to_string(root.config_path()).unwrap(), saved);
+    let reloaded = ChiaRoot::load(&dir).unwrap();
+    assert_eq!(
+        reloaded.get("full_node.new_setting"),
+        Some(&Value::Bool(true))
+    );
+    assert_eq!(
+        reloaded
+            .get("full_node.unknown_setting")
+            .and_then(Value::as_str),
+        Some("keep me")
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
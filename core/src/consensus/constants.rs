@@ -327,6 +327,32 @@ pub static TESTNET_11: Lazy<Arc<ConsensusConstants>> = Lazy::new(|| {
         ..Default::default()
     })
 });
+//Matches the constants the reference simulator uses, small discriminants and fast epochs for local testing
+pub static SIMULATOR: Lazy<Arc<ConsensusConstants>> = Lazy::new(|| {
+    Arc::new(ConsensusConstants {
+        min_blocks_per_challenge_block: 12,
+        max_sub_slot_blocks: 50,
+        num_sps_sub_slot: 16,
+        sub_slot_iters_starting: 2u64.pow(10),
+        difficulty_constant_factor: 2u128.pow(25),
+        difficulty_starting: 2u64.pow(10),
+        sub_epoch_blocks: 170,
+        epoch_blocks: 340,
+        discriminant_size_bits: BigInt::from(16),
+        number_zero_bits_plot_filter: 1,
+        min_plot_size: 18,
+        weight_proof_recent_blocks: 380,
+        blocks_cache_size: 340 + (50 * 3),
+        hard_fork_height: 2,
+        hard_fork_fix_height: 2,
+        plot_filter_128_height: 10,
+        plot_filter_64_height: 15,
+        plot_filter_32_height: 20,
+        bech32_prefix: String::from("txch"),
+        is_testnet: true,
+        ..Default::default()
+    })
+});
 pub static CONSENSUS_CONSTANTS_MAP: Lazy<HashMap<String, Arc<ConsensusConstants>>> =
     Lazy::new(|| {
         HashMap::from([
@@ -334,10 +360,11 @@ pub static CONSENSUS_CONSTANTS_MAP: Lazy<HashMap<String, Arc<ConsensusConstants>
             ("testnet2".to_string(), TESTNET_2.clone()),
             ("testnet3".to_string(), TESTNET_3.clone()),
             ("testnet4".to_string(), TESTNET_4.clone()),
-            ("testnet4".to_string(), TESTNET_5.clone()),
+            ("testnet5".to_string(), TESTNET_5.clone()),
             ("testnet7".to_string(), TESTNET_7.clone()),
             ("testnet10".to_string(), TESTNET_10.clone()),
             ("testnet11".to_string(), TESTNET_11.clone()),
+            ("simulator0".to_string(), SIMULATOR.clone()),
             ("mainnet".to_string(), MAINNET.clone()),
         ])
     });
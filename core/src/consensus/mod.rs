@@ -3,3 +3,4 @@ pub mod coinbase;
 pub mod constants;
 pub mod overrides;
 pub mod pot_iterations;
+pub mod registry;
@@ -1,5 +1,7 @@
 use crate::blockchain::sized_bytes::Bytes32;
+use crate::consensus::constants::ConsensusConstants;
 use num_bigint::BigInt;
+use serde::{Deserialize, Deserializer};
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct ConsensusOverrides {
@@ -13,24 +15,35 @@ pub struct ConsensusOverrides {
     pub difficulty_change_max_factor: Option<u32>,
     pub sub_epoch_blocks: Option<u32>,
     pub epoch_blocks: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub significant_bits: Option<BigInt>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub discriminant_size_bits: Option<BigInt>,
     pub number_zero_bits_plot_filter: Option<u8>,
     pub min_plot_size: Option<u8>,
     pub max_plot_size: Option<u8>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub sub_slot_time_target: Option<BigInt>,
     pub num_sp_intervals_extra: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub max_future_time: Option<BigInt>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub max_future_time2: Option<BigInt>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub number_of_timestamps: Option<BigInt>,
     pub genesis_challenge: Option<Bytes32>,
     pub agg_sig_me_additional_data: Option<Bytes32>,
     pub genesis_pre_farm_pool_puzzle_hash: Option<Bytes32>,
     pub genesis_pre_farm_farmer_puzzle_hash: Option<Bytes32>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub max_vdf_witness_size: Option<BigInt>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub mempool_block_buffer: Option<BigInt>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub max_coin_amount: Option<BigInt>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub max_block_cost_clvm: Option<BigInt>,
+    #[serde(default, deserialize_with = "deserialize_big_int")]
     pub cost_per_byte: Option<BigInt>,
     pub weight_proof_threshold: Option<u8>,
     pub weight_proof_recent_blocks: Option<u32>,
@@ -49,3 +62,92 @@ pub struct ConsensusOverrides {
     pub bech32_prefix: Option<String>,
     pub is_testnet: Option<bool>,
 }
+
+impl ConsensusOverrides {
+    //Copies every value that is set onto constants, leaving the rest untouched
+    pub fn apply(&self, constants: &mut ConsensusConstants) {
+        macro_rules! apply_fields {
+            ($($field:ident),+ $(,)?) => {
+                $(
+                    if let Some(value) = &self.$field {
+                        constants.$field = value.clone();
+                    }
+                )+
+            };
+        }
+        apply_fields!(
+            slot_blocks_target,
+            min_blocks_per_challenge_block,
+            max_sub_slot_blocks,
+            num_sps_sub_slot,
+            sub_slot_iters_starting,
+            difficulty_constant_factor,
+            difficulty_starting,
+            difficulty_change_max_factor,
+            sub_epoch_blocks,
+            epoch_blocks,
+            significant_bits,
+            discriminant_size_bits,
+            number_zero_bits_plot_filter,
+            min_plot_size,
+            max_plot_size,
+            sub_slot_time_target,
+            num_sp_intervals_extra,
+            max_future_time,
+            max_future_time2,
+            number_of_timestamps,
+            genesis_challenge,
+            genesis_pre_farm_pool_puzzle_hash,
+            genesis_pre_farm_farmer_puzzle_hash,
+            max_vdf_witness_size,
+            mempool_block_buffer,
+            max_coin_amount,
+            max_block_cost_clvm,
+            cost_per_byte,
+            weight_proof_threshold,
+            weight_proof_recent_blocks,
+            max_block_count_per_requests,
+            blocks_cache_size,
+            max_generator_size,
+            max_generator_ref_list_size,
+            pool_sub_slot_iters,
+            soft_fork2_height,
+            soft_fork3_height,
+            hard_fork_height,
+            hard_fork_fix_height,
+            plot_filter_128_height,
+            plot_filter_64_height,
+            plot_filter_32_height,
+            bech32_prefix,
+            is_testnet,
+        );
+        //AGG_SIG_ME signs over the genesis challenge unless the network sets it separately
+        match (&self.agg_sig_me_additional_data, &self.genesis_challenge) {
+            (Some(value), _) | (None, Some(value)) => {
+                constants.agg_sig_me_additional_data = value.bytes.to_vec();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+//Config files carry these as plain integers, not the (sign, digits) form BigInt serializes to
+fn deserialize_big_int<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<BigInt>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BigIntRepr {
+        Int(i64),
+        UInt(u64),
+        Text(String),
+        Raw(BigInt),
+    }
+    match Option::<BigIntRepr>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(BigIntRepr::Int(i)) => Ok(Some(BigInt::from(i))),
+        Some(BigIntRepr::UInt(i)) => Ok(Some(BigInt::from(i))),
+        Some(BigIntRepr::Text(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+        Some(BigIntRepr::Raw(i)) => Ok(Some(i)),
+    }
+}
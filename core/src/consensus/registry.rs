@@ -0,0 +1,156 @@
+use crate::config::ConstantsOverrides;
+use crate::consensus::constants::{ConsensusConstants, CONSENSUS_CONSTANTS_MAP, MAINNET};
+use crate::consensus::overrides::ConsensusOverrides;
+use log::warn;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::sync::{Arc, RwLock};
+
+pub static CONSENSUS_CONSTANTS_REGISTRY: Lazy<ConsensusConstantsRegistry> =
+    Lazy::new(ConsensusConstantsRegistry::default);
+
+//Runtime lookup of consensus constants by network name, seeded with the built in networks and
+//extended with config overrides so custom networks do not need a rebuild
+#[derive(Debug)]
+pub struct ConsensusConstantsRegistry {
+    networks: RwLock<HashMap<String, Arc<ConsensusConstants>>>,
+}
+impl Default for ConsensusConstantsRegistry {
+    fn default() -> Self {
+        Self {
+            networks: RwLock::new(CONSENSUS_CONSTANTS_MAP.clone()),
+        }
+    }
+}
+impl ConsensusConstantsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, network: &str) -> Option<Arc<ConsensusConstants>> {
+        match self.networks.read() {
+            Ok(networks) => networks.get(network).cloned(),
+            Err(e) => e.into_inner().get(network).cloned(),
+        }
+    }
+
+    //Unknown networks need their constants in network_overrides.constants, see apply_config
+    pub fn get_or_err(&self, network: &str) -> Result<Arc<ConsensusConstants>, Error> {
+        self.get(network).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Unknown network {network}, add its constants to network_overrides"),
+            )
+        })
+    }
+
+    pub fn get_or_mainnet(&self, network: &str) -> Arc<ConsensusConstants> {
+        self.get(network).unwrap_or_else(|| {
+            warn!("Unknown network {network}, using mainnet consensus constants");
+            MAINNET.clone()
+        })
+    }
+
+    pub fn contains(&self, network: &str) -> bool {
+        self.get(network).is_some()
+    }
+
+    pub fn networks(&self) -> Vec<String> {
+        let mut names: Vec<String> = match self.networks.read() {
+            Ok(networks) => networks.keys().cloned().collect(),
+            Err(e) => e.into_inner().keys().cloned().collect(),
+        };
+        names.sort();
+        names
+    }
+
+    pub fn register(
+        &self,
+        network: &str,
+        constants: ConsensusConstants,
+    ) -> Arc<ConsensusConstants> {
+        let constants = Arc::new(constants);
+        match self.networks.write() {
+            Ok(mut networks) => networks.insert(network.to_string(), constants.clone()),
+            Err(e) => e
+                .into_inner()
+                .insert(network.to_string(), constants.clone()),
+        };
+        constants
+    }
+
+    //Applies overrides on top of the built in constants for the network, unknown networks start
+    //from mainnet the same way the reference node does
+    pub fn register_with_overrides(
+        &self,
+        network: &str,
+        overrides: &ConsensusOverrides,
+    ) -> Arc<ConsensusConstants> {
+        let mut constants = CONSENSUS_CONSTANTS_MAP
+            .get(network)
+            .unwrap_or(&MAINNET)
+            .as_ref()
+            .clone();
+        overrides.apply(&mut constants);
+        self.register(network, constants)
+    }
+
+    //Rebuilds every network that has an entry in the config's network_overrides.constants
+    pub fn apply_config(&self, overrides: &ConstantsOverrides) {
+        let mut names: Vec<String> = CONSENSUS_CONSTANTS_MAP.keys().cloned().collect();
+        names.extend(overrides.custom.keys().cloned());
+        for name in names {
+            if let Some(network_overrides) = overrides.get(&name) {
+                self.register_with_overrides(&name, network_overrides);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_consensus_constants_registry() {
+    use crate::blockchain::sized_bytes::{Bytes32, SizedBytes};
+    use crate::config::ChiaConfig;
+    let registry = ConsensusConstantsRegistry::new();
+    assert_eq!(registry.get_or_mainnet("mainnet"), MAINNET.clone());
+    assert_eq!(registry.get_or_mainnet("unknown"), MAINNET.clone());
+    assert!(registry.get("simulator0").is_some_and(|c| c.is_testnet));
+    assert!(registry.contains("testnet11"));
+    let yaml = r#"
+testnet11:
+  genesis_challenge: "37a90eb5185a9c4439a91ddc98bbadce7b4feba060d50116a067de66bf236615"
+  sub_slot_iters_starting: 67108864
+  hard_fork_height: 0
+  plot_filter_128_height: 6029568
+mynet:
+  genesis_challenge: "0101010101010101010101010101010101010101010101010101010101010101"
+  plot_filter_32_height: 100
+"#;
+    let overrides: ConstantsOverrides = serde_yaml::from_str(yaml).unwrap();
+    assert!(overrides.custom.contains_key("mynet"));
+    registry.apply_config(&overrides);
+    let testnet = registry.get_or_mainnet("testnet11");
+    assert_eq!(testnet.sub_slot_iters_starting, 67108864);
+    assert_eq!(testnet.plot_filter_128_height, 6029568);
+    let custom = registry.get("mynet").unwrap();
+    assert_eq!(custom.genesis_challenge, Bytes32::new(&[1u8; 32]));
+    assert_eq!(custom.plot_filter_32_height, 100);
+    assert_eq!(custom.bech32_prefix, MAINNET.bech32_prefix);
+    assert_eq!(custom.agg_sig_me_additional_data, vec![1u8; 32]);
+    assert_eq!(
+        testnet.agg_sig_me_additional_data,
+        testnet.genesis_challenge.bytes.to_vec()
+    );
+    assert_eq!(
+        registry.get_or_err("unknown").unwrap_err().kind(),
+        ErrorKind::NotFound
+    );
+    assert!(registry.get_or_err("mynet").is_ok());
+    assert!(registry.networks().contains(&"mynet".to_string()));
+    let config = ChiaConfig {
+        selected_network: "simulator0".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(config.consensus_constants().min_plot_size, 18);
+}
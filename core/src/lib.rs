@@ -9,6 +9,7 @@ pub mod pool;
 pub mod protocols;
 pub mod ssl;
 pub mod utils;
+pub mod vdf;
 
 fn _version() -> &'static str {
     env!("CARGO_PKG_VERSION")
@@ -181,19 +181,19 @@ pub struct RespondPeers {
 
 #[derive(ChiaSerial, Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct FeeEstimate {
-    estimates: Vec<u64>,
-    target_times: Vec<u64>,
-    current_fee_rate: f64,
-    mempool_size: u64,
-    mempool_fees: u64,
-    num_spends: u64,
-    mempool_max_size: u64,
-    full_node_synced: bool,
-    peak_height: u64,
-    last_peak_timestamp: u64,
-    node_time_utc: u64,
-    last_block_cost: u64,
-    fees_last_block: Option<u64>,
-    fee_rate_last_block: f64,
-    last_tx_block_height: u32,
+    pub estimates: Vec<u64>,
+    pub target_times: Vec<u64>,
+    pub current_fee_rate: f64,
+    pub mempool_size: u64,
+    pub mempool_fees: u64,
+    pub num_spends: u64,
+    pub mempool_max_size: u64,
+    pub full_node_synced: bool,
+    pub peak_height: u64,
+    pub last_peak_timestamp: u64,
+    pub node_time_utc: u64,
+    pub last_block_cost: u64,
+    pub fees_last_block: Option<u64>,
+    pub fee_rate_last_block: f64,
+    pub last_tx_block_height: u32,
 }
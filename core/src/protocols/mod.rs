@@ -4,11 +4,14 @@ pub mod full_node;
 pub mod harvester;
 pub mod introducer;
 pub mod pool;
+pub mod rate_limits;
 pub mod shared;
 pub mod timelord;
 pub mod wallet;
 
 use crate::blockchain::sized_bytes::Bytes32;
+use crate::protocols::rate_limits::{PeerPolicy, RateLimitViolation, RateLimiter};
+use crate::protocols::shared::{Capabilities, Capability};
 use crate::utils::await_termination;
 use async_trait::async_trait;
 use dg_xch_macros::ChiaSerial;
@@ -19,17 +22,19 @@ use futures_util::SinkExt;
 use futures_util::{Sink, Stream, StreamExt};
 use hyper::upgrade::Upgraded;
 use hyper_util::rt::TokioIo;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use std::collections::HashMap;
+use std::future::Future;
 use std::io::{Cursor, Error, ErrorKind};
+use std::net::SocketAddr;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::select;
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, Mutex, RwLock, Semaphore, TryAcquireError};
 use tokio_tungstenite::tungstenite::error::ProtocolError;
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
@@ -483,6 +488,146 @@ impl From<ChiaMessage> for Message {
     }
 }
 
+//Links a protocol struct to its message type, requests also name the type a peer rejects them with
+pub trait ChiaProtocolMessage {
+    fn msg_type() -> ProtocolMessageTypes;
+    fn reject_type() -> Option<ProtocolMessageTypes> {
+        None
+    }
+}
+
+macro_rules! protocol_message {
+    ($($t:path => $msg_type:ident $(| $reject:ident)?),* $(,)?) => {
+        $(
+            impl ChiaProtocolMessage for $t {
+                fn msg_type() -> ProtocolMessageTypes {
+                    ProtocolMessageTypes::$msg_type
+                }
+                $(
+                    fn reject_type() -> Option<ProtocolMessageTypes> {
+                        Some(ProtocolMessageTypes::$reject)
+                    }
+                )?
+            }
+        )*
+    };
+}
+
+protocol_message!(
+    shared::Handshake => Handshake,
+    harvester::HarvesterHandshake => HarvesterHandshake,
+    harvester::NewSignagePointHarvester => NewSignagePointHarvester,
+    harvester::NewProofOfSpace => NewProofOfSpace,
+    harvester::RequestSignatures => RequestSignatures,
+    harvester::RespondSignatures => RespondSignatures,
+    harvester::RequestPlots => RequestPlots,
+    harvester::RespondPlots => RespondPlots,
+    farmer::NewSignagePoint => NewSignagePoint,
+    farmer::DeclareProofOfSpace => DeclareProofOfSpace,
+    farmer::RequestSignedValues => RequestSignedValues,
+    farmer::SignedValues => SignedValues,
+    farmer::FarmingInfo => FarmingInfo,
+    timelord::NewPeakTimelord => NewPeakTimelord,
+    timelord::NewUnfinishedBlockTimelord => NewUnfinishedBlockTimelord,
+    timelord::NewInfusionPointVDF => NewInfusionPointVdf,
+    timelord::NewSignagePointVDF => NewSignagePointVdf,
+    timelord::NewEndOfSubSlotVDF => NewEndOfSubSlotVdf,
+    timelord::RequestCompactProofOfTime => RequestCompactProofOfTime,
+    timelord::RespondCompactProofOfTime => RespondCompactProofOfTime,
+    full_node::NewPeak => NewPeak,
+    full_node::NewTransaction => NewTransaction,
+    full_node::RequestTransaction => RequestTransaction,
+    full_node::RespondTransaction => RespondTransaction,
+    full_node::RequestProofOfWeight => RequestProofOfWeight,
+    full_node::RespondProofOfWeight => RespondProofOfWeight,
+    full_node::RequestBlock => RequestBlock | RejectBlock,
+    full_node::RespondBlock => RespondBlock,
+    full_node::RejectBlock => RejectBlock,
+    full_node::RequestBlocks => RequestBlocks | RejectBlocks,
+    full_node::RespondBlocks => RespondBlocks,
+    full_node::RejectBlocks => RejectBlocks,
+    full_node::RequestUnfinishedBlock => RequestUnfinishedBlock,
+    full_node::RespondUnfinishedBlock => RespondUnfinishedBlock,
+    full_node::NewSignagePointOrEndOfSubSlot => NewSignagePointOrEndOfSubSlot,
+    full_node::RequestSignagePointOrEndOfSubSlot => RequestSignagePointOrEndOfSubSlot,
+    full_node::RespondSignagePoint => RespondSignagePoint,
+    full_node::RespondEndOfSubSlot => RespondEndOfSubSlot,
+    full_node::RequestMempoolTransactions => RequestMempoolTransactions,
+    full_node::RequestCompactVDF => RequestCompactVdf,
+    full_node::RespondCompactVDF => RespondCompactVdf,
+    full_node::NewCompactVDF => NewCompactVdf,
+    full_node::RequestPeers => RequestPeers,
+    full_node::RespondPeers => RespondPeers,
+    wallet::RequestPuzzleSolution => RequestPuzzleSolution | RejectPuzzleSolution,
+    wallet::RespondPuzzleSolution => RespondPuzzleSolution,
+    wallet::RejectPuzzleSolution => RejectPuzzleSolution,
+    wallet::SendTransaction => SendTransaction,
+    wallet::TransactionAck => TransactionAck,
+    wallet::NewPeakWallet => NewPeakWallet,
+    wallet::RequestBlockHeader => RequestBlockHeader | RejectHeaderRequest,
+    wallet::RespondBlockHeader => RespondBlockHeader,
+    wallet::RejectHeaderRequest => RejectHeaderRequest,
+    wallet::RequestRemovals => RequestRemovals | RejectRemovalsRequest,
+    wallet::RespondRemovals => RespondRemovals,
+    wallet::RejectRemovalsRequest => RejectRemovalsRequest,
+    wallet::RequestAdditions => RequestAdditions | RejectAdditionsRequest,
+    wallet::RespondAdditions => RespondAdditions,
+    wallet::RejectAdditionsRequest => RejectAdditionsRequest,
+    wallet::RequestHeaderBlocks => RequestHeaderBlocks | RejectHeaderBlocks,
+    wallet::RespondHeaderBlocks => RespondHeaderBlocks,
+    wallet::RejectHeaderBlocks => RejectHeaderBlocks,
+    wallet::RequestBlockHeaders => RequestBlockHeaders | RejectBlockHeaders,
+    wallet::RespondBlockHeaders => RespondBlockHeaders,
+    wallet::RejectBlockHeaders => RejectBlockHeaders,
+    wallet::RegisterForPhUpdates => RegisterInterestInPuzzleHash,
+    wallet::RespondToPhUpdates => RespondToPhUpdate,
+    wallet::RegisterForCoinUpdates => RegisterInterestInCoin,
+    wallet::RespondToCoinUpdates => RespondToCoinUpdate,
+    wallet::CoinStateUpdate => CoinStateUpdate,
+    wallet::RequestChildren => RequestChildren,
+    wallet::RespondChildren => RespondChildren,
+    wallet::RequestSESInfo => RequestSesHashes,
+    wallet::RespondSESInfo => RespondSesHashes,
+    wallet::RequestFeeEstimates => RequestFeeEstimates,
+    wallet::RespondFeeEstimates => RespondFeeEstimates,
+    introducer::RequestPeersIntroducer => RequestPeersIntroducer,
+    introducer::RespondPeersIntroducer => RespondPeersIntroducer,
+);
+
+#[derive(Debug, Clone)]
+pub enum PeerResponse<T> {
+    Response(T),
+    //The peer answered with the reject type of the request, the raw message is kept for decoding
+    Rejected(Arc<ChiaMessage>),
+    NoResponse,
+}
+impl<T> PeerResponse<T> {
+    pub fn ok(self) -> Option<T> {
+        match self {
+            PeerResponse::Response(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+struct RequestHandler {
+    channel: Mutex<Option<oneshot::Sender<Arc<ChiaMessage>>>>,
+}
+#[async_trait]
+impl MessageHandler for RequestHandler {
+    async fn handle(
+        &self,
+        msg: Arc<ChiaMessage>,
+        _peer_id: Arc<Bytes32>,
+        _peers: PeerMap,
+    ) -> Result<(), Error> {
+        if let Some(channel) = self.channel.lock().await.take() {
+            let _ = channel.send(msg);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct ChiaMessageFilter {
     pub msg_type: Option<ProtocolMessageTypes>,
@@ -518,10 +663,18 @@ impl ChiaMessageHandler {
 pub type PeerMap = Arc<RwLock<HashMap<Bytes32, Arc<SocketPeer>>>>;
 
 pub struct SocketPeer {
+    pub peer_addr: Option<SocketAddr>,
     pub node_type: Arc<RwLock<NodeType>>,
     pub protocol_version: Arc<RwLock<ChiaProtocolVersion>>,
+    //Capabilities both sides advertised, shared with the connection and set during the handshake
+    pub capabilities: Arc<RwLock<Capabilities>>,
     pub websocket: Arc<RwLock<WebsocketConnection>>,
 }
+impl SocketPeer {
+    pub async fn has_capability(&self, capability: Capability) -> bool {
+        self.capabilities.read().await.has(capability)
+    }
+}
 
 pub enum WebsocketMsgStream {
     TokioIo(WebSocketStream<TokioIo<Upgraded>>),
@@ -572,9 +725,14 @@ impl Sink<Message> for WebsocketMsgStream {
     }
 }
 
+//Chia resets the request nonce before it reaches i16::MAX
+const MAX_REQUEST_ID: u16 = 32767;
+
 pub struct WebsocketConnection {
-    write: SplitSink<WebsocketMsgStream, Message>,
+    write: Arc<Mutex<SplitSink<WebsocketMsgStream, Message>>>,
     message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+    next_request_id: Arc<AtomicU16>,
+    capabilities: Arc<RwLock<Capabilities>>,
 }
 impl WebsocketConnection {
     pub fn new(
@@ -585,24 +743,34 @@ impl WebsocketConnection {
     ) -> (Self, ReadStream) {
         let (write, read) = websocket.split();
         let websocket = WebsocketConnection {
-            write,
+            write: Arc::new(Mutex::new(write)),
             message_handlers: message_handlers.clone(),
+            next_request_id: Arc::new(AtomicU16::new(0)),
+            capabilities: Arc::new(RwLock::new(Capabilities::none())),
         };
         let stream = ReadStream {
             read,
             message_handlers,
             peer_id,
             peers,
+            rate_limiter: None,
+            handler_permits: None,
         };
         (websocket, stream)
     }
     pub async fn send(&mut self, msg: Message) -> Result<(), Error> {
         self.write
+            .lock()
+            .await
             .send(msg)
             .await
             .map_err(|e| Error::new(ErrorKind::Other, e))
     }
 
+    pub fn capabilities(&self) -> Arc<RwLock<Capabilities>> {
+        self.capabilities.clone()
+    }
+
     pub async fn subscribe(&self, uuid: Uuid, handle: ChiaMessageHandler) {
         self.message_handlers
             .write()
@@ -614,19 +782,150 @@ impl WebsocketConnection {
         self.message_handlers.write().await.remove(&uuid)
     }
 
+    fn next_request_id(&self) -> u16 {
+        let mut current = self.next_request_id.load(Ordering::Relaxed);
+        loop {
+            let next = if current >= MAX_REQUEST_ID {
+                0
+            } else {
+                current + 1
+            };
+            match self.next_request_id.compare_exchange_weak(
+                current,
+                next,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return current,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    //Sends msg tagged with a fresh message id and resolves with the reply carrying the same id.
+    //The returned future does not borrow the connection, so a lock guard can be released before awaiting it.
+    //Peers without the NoneResponse capability stay silent when they have nothing to send, so for
+    //requests that cannot be rejected a timeout is reported as NoResponse instead of an error
+    pub fn request<Req, Resp>(
+        &self,
+        msg: &Req,
+        protocol_version: ChiaProtocolVersion,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<PeerResponse<Resp>, Error>> + Send + 'static
+    where
+        Req: ChiaProtocolMessage + ChiaSerialize,
+        Resp: ChiaProtocolMessage + ChiaSerialize + Send + 'static,
+    {
+        let id = self.next_request_id();
+        let request = ChiaMessage::new(Req::msg_type(), protocol_version, msg, Some(id));
+        let reject_type = Req::reject_type();
+        let write = self.write.clone();
+        let message_handlers = self.message_handlers.clone();
+        let capabilities = self.capabilities.clone();
+        async move {
+            let (tx, rx) = oneshot::channel();
+            //One handler per accepted type, all sharing the channel, so unrelated messages that
+            //reuse the id are left to the other handlers
+            let handler = Arc::new(RequestHandler {
+                channel: Mutex::new(Some(tx)),
+            });
+            let mut accepted = vec![Resp::msg_type(), ProtocolMessageTypes::NoneResponse];
+            accepted.extend(reject_type);
+            let mut handle_ids = vec![];
+            {
+                let mut handlers = message_handlers.write().await;
+                for msg_type in accepted {
+                    let handle_id = Uuid::new_v4();
+                    handlers.insert(
+                        handle_id,
+                        Arc::new(ChiaMessageHandler::new(
+                            Arc::new(ChiaMessageFilter {
+                                msg_type: Some(msg_type),
+                                id: Some(id),
+                            }),
+                            handler.clone(),
+                        )),
+                    );
+                    handle_ids.push(handle_id);
+                }
+            }
+            let sent = write
+                .lock()
+                .await
+                .send(request.into())
+                .await
+                .map_err(|e| Error::new(ErrorKind::ConnectionAborted, e));
+            let res = match sent {
+                Ok(()) => select! {
+                    res = rx => res.map_err(|_| Error::new(
+                        ErrorKind::ConnectionAborted,
+                        "Connection closed before response received",
+                    )),
+                    _ = tokio::time::sleep(timeout) => Err(Error::new(
+                        ErrorKind::TimedOut,
+                        format!("Timeout waiting for response to {:?}({id})", Req::msg_type()),
+                    )),
+                },
+                Err(e) => Err(e),
+            };
+            {
+                let mut handlers = message_handlers.write().await;
+                for handle_id in &handle_ids {
+                    handlers.remove(handle_id);
+                }
+            }
+            let res = match res {
+                Err(e)
+                    if e.kind() == ErrorKind::TimedOut
+                        && reject_type.is_none()
+                        && !capabilities.read().await.has(Capability::NoneResponse) =>
+                {
+                    return Ok(PeerResponse::NoResponse);
+                }
+                res => res?,
+            };
+            if res.msg_type == Resp::msg_type() {
+                let mut cursor = Cursor::new(&res.data);
+                Ok(PeerResponse::Response(
+                    Resp::from_bytes(&mut cursor, protocol_version).map_err(|e| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("Failed to parse {:?}: {:?}", res.msg_type, e),
+                        )
+                    })?,
+                ))
+            } else if Some(res.msg_type) == reject_type {
+                Ok(PeerResponse::Rejected(res))
+            } else if res.msg_type == ProtocolMessageTypes::NoneResponse {
+                Ok(PeerResponse::NoResponse)
+            } else {
+                Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Unexpected {:?} in response to {:?}({id})",
+                        res.msg_type,
+                        Req::msg_type()
+                    ),
+                ))
+            }
+        }
+    }
+
     pub async fn close(&mut self, msg: Option<Message>) -> Result<(), Error> {
         if let Some(msg) = msg {
-            let _ = self
-                .write
+            let mut write = self.write.lock().await;
+            let _ = write
                 .send(msg)
                 .await
                 .map_err(|e| Error::new(ErrorKind::Other, e));
-            self.write
+            write
                 .close()
                 .await
                 .map_err(|e| Error::new(ErrorKind::Other, e))
         } else {
             self.write
+                .lock()
+                .await
                 .close()
                 .await
                 .map_err(|e| Error::new(ErrorKind::Other, e))
@@ -645,8 +944,64 @@ pub struct ReadStream {
     message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
     peer_id: Arc<Bytes32>,
     peers: PeerMap,
+    rate_limiter: Option<RateLimiter>,
+    handler_permits: Option<Arc<Semaphore>>,
 }
 impl ReadStream {
+    //Enforces the policy's size and rate limits on inbound messages, peers that break them are
+    //disconnected and their address is banned
+    pub fn set_peer_policy(&mut self, policy: Arc<PeerPolicy>) {
+        self.handler_permits = Some(Arc::new(Semaphore::new(policy.max_pending_handlers.max(1))));
+        self.rate_limiter = Some(RateLimiter::new(policy));
+    }
+
+    fn check_limits(
+        &mut self,
+        msg_type: ProtocolMessageTypes,
+        size: usize,
+    ) -> Result<(), RateLimitViolation> {
+        match self.rate_limiter.as_mut() {
+            Some(limiter) => {
+                let max_message_size = limiter.policy().max_message_size;
+                if size > max_message_size {
+                    Err(RateLimitViolation::MessageTooLarge {
+                        msg_type,
+                        size,
+                        limit: max_message_size as u64,
+                    })
+                } else {
+                    limiter.process(msg_type, size)
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    async fn disconnect_and_ban(
+        &self,
+        peer: Option<&Arc<SocketPeer>>,
+        violation: RateLimitViolation,
+    ) {
+        let Some(peer) = peer else {
+            error!("Closing connection, {violation}");
+            return;
+        };
+        if let (Some(limiter), Some(addr)) = (self.rate_limiter.as_ref(), peer.peer_addr) {
+            let policy = limiter.policy();
+            error!(
+                "Banning {} for {:?}, {violation}",
+                addr.ip(),
+                policy.ban_time
+            );
+            policy.bans.ban(addr.ip(), policy.ban_time);
+        } else {
+            error!("Closing connection, {violation}");
+        }
+        if let Err(e) = peer.websocket.write().await.close(None).await {
+            debug!("Failed to close connection: {:?}", e);
+        }
+    }
+
     pub async fn run(&mut self, run: Arc<AtomicBool>) {
         loop {
             let peer_self = self.peers.read().await.get(&self.peer_id).cloned();
@@ -661,6 +1016,11 @@ impl ReadStream {
                         Some(Ok(msg)) => {
                             match msg {
                                 Message::Binary(bin_data) => {
+                                    let msg_type = bin_data.first().map(|b| ProtocolMessageTypes::from(*b)).unwrap_or(ProtocolMessageTypes::Unknown);
+                                    if let Err(violation) = self.check_limits(msg_type, bin_data.len()) {
+                                        self.disconnect_and_ban(peer_self.as_ref(), violation).await;
+                                        return;
+                                    }
                                     let mut cursor = Cursor::new(&bin_data);
                                     match ChiaMessage::from_bytes(&mut cursor, protocol_version) {
                                         Ok(chia_msg) => {
@@ -673,10 +1033,33 @@ impl ReadStream {
                                                     let peer_id = self.peer_id.clone();
                                                     let peers = self.peers.clone();
                                                     let v_arc_c = v.handle.clone();
+                                                    //Responses to pending requests only complete a channel, so they never wait for
+                                                    //a permit, otherwise the reader would stall behind handlers awaiting them
+                                                    let permit = match &self.handler_permits {
+                                                        Some(permits) if v.filter.id.is_none() => {
+                                                            match permits.clone().try_acquire_owned() {
+                                                                Ok(permit) => Some(permit),
+                                                                Err(TryAcquireError::NoPermits) => {
+                                                                    let limit = self.rate_limiter.as_ref().map(|l| l.policy().max_pending_handlers).unwrap_or_default();
+                                                                    let violation = RateLimitViolation::TooManyPendingHandlers { msg_type: msg_arc.msg_type, limit };
+                                                                    if self.rate_limiter.as_ref().is_some_and(|l| l.policy().ban_on_overload) {
+                                                                        self.disconnect_and_ban(peer_self.as_ref(), violation).await;
+                                                                        return;
+                                                                    }
+                                                                    warn!("Dropping message, {violation}");
+                                                                    matched = true;
+                                                                    continue;
+                                                                }
+                                                                Err(TryAcquireError::Closed) => None,
+                                                            }
+                                                        }
+                                                        _ => None,
+                                                    };
                                                     tokio::spawn(async move {
                                                         if let Err(e) = v_arc_c.handle(msg_arc_c.clone(), peer_id, peers).await {
                                                             error!("Error Handling Message({:#?}): {e:?}", msg_arc_c.msg_type);
                                                         }
+                                                        drop(permit);
                                                     });
                                                     matched = true;
                                                 }
@@ -736,3 +1119,176 @@ impl ReadStream {
         }
     }
 }
+
+#[tokio::test]
+async fn test_request_matching() {
+    use crate::protocols::full_node::{RejectBlock, RequestBlock, RespondPeers};
+    use tokio::net::TcpListener;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    //Answers both requests in reverse order, rejecting the first one
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        //The first takes the only handler permit and never finishes, the second is dropped
+        for _ in 0..2 {
+            ws.send(
+                ChiaMessage::new(
+                    ProtocolMessageTypes::RequestPeers,
+                    ChiaProtocolVersion::default(),
+                    &crate::protocols::full_node::RequestPeers {},
+                    None,
+                )
+                .into(),
+            )
+            .await
+            .unwrap();
+        }
+        let mut requests = vec![];
+        while requests.len() < 2 {
+            if let Some(Ok(Message::Binary(data))) = ws.next().await {
+                let msg = ChiaMessage::from_bytes(
+                    &mut Cursor::new(&data),
+                    ChiaProtocolVersion::default(),
+                )
+                .unwrap();
+                let req = RequestBlock::from_bytes(
+                    &mut Cursor::new(&msg.data),
+                    ChiaProtocolVersion::default(),
+                )
+                .unwrap();
+                requests.push((msg.id, req.height));
+            }
+        }
+        for (id, height) in requests.into_iter().rev() {
+            let reply = if height == 1 {
+                ChiaMessage::new(
+                    ProtocolMessageTypes::RejectBlock,
+                    ChiaProtocolVersion::default(),
+                    &RejectBlock { height },
+                    id,
+                )
+            } else {
+                //Same id but a type the request does not expect, it must be skipped
+                ws.send(
+                    ChiaMessage::new(
+                        ProtocolMessageTypes::RequestBlock,
+                        ChiaProtocolVersion::default(),
+                        &RequestBlock {
+                            height,
+                            include_transaction_block: false,
+                        },
+                        id,
+                    )
+                    .into(),
+                )
+                .await
+                .unwrap();
+                ChiaMessage::new(
+                    ProtocolMessageTypes::RespondPeers,
+                    ChiaProtocolVersion::default(),
+                    &RespondPeers { peer_list: vec![] },
+                    id,
+                )
+            };
+            ws.send(reply.into()).await.unwrap();
+        }
+        ws
+    });
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (ws, _) =
+        tokio_tungstenite::client_async(format!("ws://{addr}"), MaybeTlsStream::Plain(stream))
+            .await
+            .unwrap();
+    let run = Arc::new(AtomicBool::new(true));
+    let (connection, mut stream) = WebsocketConnection::new(
+        WebsocketMsgStream::Tls(ws),
+        Default::default(),
+        Arc::new(Bytes32::default()),
+        Default::default(),
+    );
+    struct StalledHandler {
+        calls: Arc<AtomicU16>,
+    }
+    #[async_trait]
+    impl MessageHandler for StalledHandler {
+        async fn handle(
+            &self,
+            _msg: Arc<ChiaMessage>,
+            _peer_id: Arc<Bytes32>,
+            _peers: PeerMap,
+        ) -> Result<(), Error> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            std::future::pending::<()>().await;
+            Ok(())
+        }
+    }
+    let calls = Arc::new(AtomicU16::new(0));
+    connection
+        .subscribe(
+            Uuid::new_v4(),
+            ChiaMessageHandler::new(
+                Arc::new(ChiaMessageFilter {
+                    msg_type: Some(ProtocolMessageTypes::RequestPeers),
+                    id: None,
+                }),
+                Arc::new(StalledHandler {
+                    calls: calls.clone(),
+                }),
+            ),
+        )
+        .await;
+    //Responses must still reach the pending requests while every permit is taken
+    stream.set_peer_policy(Arc::new(PeerPolicy {
+        max_pending_handlers: 1,
+        ..Default::default()
+    }));
+    let reader_run = run.clone();
+    tokio::spawn(async move { stream.run(reader_run).await });
+    let request = |height| {
+        connection.request::<_, RespondPeers>(
+            &RequestBlock {
+                height,
+                include_transaction_block: false,
+            },
+            ChiaProtocolVersion::default(),
+            Duration::from_secs(5),
+        )
+    };
+    let (rejected, answered) = tokio::join!(request(1), request(2));
+    assert!(
+        matches!(rejected.unwrap(), PeerResponse::Rejected(msg) if msg.msg_type == ProtocolMessageTypes::RejectBlock)
+    );
+    assert!(matches!(answered.unwrap(), PeerResponse::Response(r) if r.peer_list.is_empty()));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    let timed_out = connection
+        .request::<_, RespondPeers>(
+            &RequestBlock {
+                height: 3,
+                include_transaction_block: false,
+            },
+            ChiaProtocolVersion::default(),
+            Duration::from_millis(100),
+        )
+        .await;
+    assert_eq!(timed_out.unwrap_err().kind(), ErrorKind::TimedOut);
+    //Without NoneResponse a silent peer means it had nothing to send
+    let request_peers = || {
+        connection.request::<_, RespondPeers>(
+            &crate::protocols::full_node::RequestPeers {},
+            ChiaProtocolVersion::default(),
+            Duration::from_millis(100),
+        )
+    };
+    assert!(matches!(
+        request_peers().await.unwrap(),
+        PeerResponse::NoResponse
+    ));
+    *connection.capabilities().write().await = Capabilities::new(&[Capability::NoneResponse]);
+    assert_eq!(
+        request_peers().await.unwrap_err().kind(),
+        ErrorKind::TimedOut
+    );
+    run.store(false, Ordering::Relaxed);
+    drop(server.await.unwrap());
+}
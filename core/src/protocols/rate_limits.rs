@@ -0,0 +1,407 @@
+use crate::protocols::ProtocolMessageTypes;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const KB: u32 = 1024;
+const MB: u32 = 1024 * 1024;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    //Messages allowed per window
+    pub frequency: u32,
+    pub max_size: u32,
+    //Defaults to frequency * max_size
+    pub max_total_size: Option<u64>,
+}
+impl RateLimit {
+    pub const fn new(frequency: u32, max_size: u32) -> Self {
+        Self {
+            frequency,
+            max_size,
+            max_total_size: None,
+        }
+    }
+    pub const fn with_total(frequency: u32, max_size: u32, max_total_size: u64) -> Self {
+        Self {
+            frequency,
+            max_size,
+            max_total_size: Some(max_total_size),
+        }
+    }
+    pub fn total_size(&self) -> u64 {
+        self.max_total_size
+            .unwrap_or(self.frequency as u64 * self.max_size as u64)
+    }
+}
+
+//Returns the reference node's limit for a message type and whether it is a transaction message.
+//Transaction messages have their own budget, everything else also counts towards the non tx limit
+pub fn reference_rate_limit(msg_type: ProtocolMessageTypes) -> (RateLimit, bool) {
+    use ProtocolMessageTypes::*;
+    match msg_type {
+        NewTransaction | RequestTransaction => (RateLimit::new(5000, 100), true),
+        RespondTransaction => (RateLimit::with_total(5000, MB, 20 * MB as u64), true),
+        SendTransaction => (RateLimit::new(5000, MB), true),
+        TransactionAck => (RateLimit::new(5000, 2 * KB), true),
+        Handshake => (RateLimit::new(5, 10 * KB), false),
+        HarvesterHandshake => (RateLimit::new(5, MB), false),
+        NewSignagePointHarvester => (RateLimit::new(100, 4886), false),
+        NewProofOfSpace | RequestSignatures | RespondSignatures => {
+            (RateLimit::new(100, 2 * KB), false)
+        }
+        NewSignagePoint => (RateLimit::new(200, 2 * KB), false),
+        DeclareProofOfSpace | RequestSignedValues => (RateLimit::new(100, 10 * MB), false),
+        FarmingInfo | SignedValues => (RateLimit::new(100, KB), false),
+        NewPeakTimelord => (RateLimit::new(100, 20 * KB), false),
+        NewUnfinishedBlockTimelord | RequestCompactProofOfTime => {
+            (RateLimit::new(100, 10 * KB), false)
+        }
+        NewSignagePointVdf
+        | NewInfusionPointVdf
+        | NewEndOfSubSlotVdf
+        | RespondCompactProofOfTime => (RateLimit::new(100, 100 * KB), false),
+        NewPeak => (RateLimit::new(200, 512), false),
+        RequestProofOfWeight => (RateLimit::new(5, 100), false),
+        RespondProofOfWeight => (RateLimit::new(5, 50 * MB), false),
+        RequestBlock | RejectBlock | NewUnfinishedBlock | RequestUnfinishedBlock => {
+            (RateLimit::new(200, 100), false)
+        }
+        RequestBlocks => (RateLimit::new(500, 100), false),
+        RespondBlocks => (RateLimit::with_total(100, 50 * MB, 250 * MB as u64), false),
+        RejectBlocks => (RateLimit::new(100, 100), false),
+        RespondBlock | RespondUnfinishedBlock => {
+            (RateLimit::with_total(200, 2 * MB, 20 * MB as u64), false)
+        }
+        NewSignagePointOrEndOfSubSlot | RequestSignagePointOrEndOfSubSlot | FarmNewBlock => {
+            (RateLimit::new(200, 200), false)
+        }
+        RespondSignagePoint => (RateLimit::new(200, 50 * KB), false),
+        RespondEndOfSubSlot => (RateLimit::new(100, 50 * KB), false),
+        RequestMempoolTransactions => (RateLimit::new(5, MB), false),
+        RequestCompactVdf => (RateLimit::new(200, KB), false),
+        RespondCompactVdf => (RateLimit::new(200, 100 * KB), false),
+        NewCompactVdf => (RateLimit::new(100, KB), false),
+        RequestPeers => (RateLimit::new(10, 100), false),
+        RespondPeers => (RateLimit::new(10, MB), false),
+        RequestPuzzleSolution | RejectPuzzleSolution => (RateLimit::new(1000, 100), false),
+        RespondPuzzleSolution => (RateLimit::new(1000, MB), false),
+        NewPeakWallet => (RateLimit::new(200, 300), false),
+        RequestBlockHeader
+        | RejectHeaderRequest
+        | RejectRemovalsRequest
+        | RejectAdditionsRequest
+        | RequestHeaderBlocks
+        | RequestBlockHeaders
+        | RejectBlockHeaders
+        | NoneResponse => (RateLimit::new(500, 100), false),
+        RespondBlockHeader | RespondBlockHeaders => (RateLimit::new(500, 500 * KB), false),
+        RequestRemovals => (RateLimit::with_total(500, 50 * KB, 10 * MB as u64), false),
+        RespondRemovals | RequestAdditions | RespondAdditions => {
+            (RateLimit::with_total(500, MB, 10 * MB as u64), false)
+        }
+        RejectHeaderBlocks => (RateLimit::new(100, 100), false),
+        RespondHeaderBlocks => (RateLimit::with_total(500, 2 * MB, 100 * MB as u64), false),
+        RequestPeersIntroducer => (RateLimit::new(100, 100), false),
+        RespondPeersIntroducer => (RateLimit::new(100, MB), false),
+        RequestPlots => (RateLimit::new(10, 10 * MB), false),
+        RespondPlots => (RateLimit::new(10, 100 * MB), false),
+        PlotSyncStart | PlotSyncLoaded | PlotSyncRemoved | PlotSyncInvalid
+        | PlotSyncKeysMissing | PlotSyncDuplicates | PlotSyncDone | PlotSyncResponse => {
+            (RateLimit::new(1000, 100 * MB), false)
+        }
+        CoinStateUpdate
+        | RegisterInterestInPuzzleHash
+        | RespondToPhUpdate
+        | RegisterInterestInCoin
+        | RespondToCoinUpdate => (RateLimit::new(1000, 100 * MB), false),
+        RequestSesHashes | RespondSesHashes | RequestChildren | RespondChildren => {
+            (RateLimit::new(2000, MB), false)
+        }
+        RequestFeeEstimates => (RateLimit::new(10000, 100), false),
+        RespondFeeEstimates => (RateLimit::new(10000, 100 * KB), false),
+        //Types added after this list use the reference default settings instead of banning the peer
+        Unknown => (RateLimit::with_total(100, MB, 100 * MB as u64), false),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    pub window: Duration,
+    //Scales every frequency, outbound limits in the reference node use a lower percentage
+    pub percentage_of_limit: u32,
+    pub non_tx_frequency: u32,
+    pub non_tx_max_total_size: u64,
+    overrides: HashMap<u8, RateLimit>,
+}
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            percentage_of_limit: 100,
+            non_tx_frequency: 1000,
+            non_tx_max_total_size: 100 * MB as u64,
+            overrides: HashMap::new(),
+        }
+    }
+}
+impl RateLimits {
+    pub fn set_limit(&mut self, msg_type: ProtocolMessageTypes, limit: RateLimit) {
+        self.overrides.insert(msg_type as u8, limit);
+    }
+
+    pub fn limit_for(&self, msg_type: ProtocolMessageTypes) -> (RateLimit, bool) {
+        let (reference, is_tx) = reference_rate_limit(msg_type);
+        (
+            self.overrides
+                .get(&(msg_type as u8))
+                .copied()
+                .unwrap_or(reference),
+            is_tx,
+        )
+    }
+
+    fn scale(&self, value: u64) -> u64 {
+        value * self.percentage_of_limit as u64 / 100
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BanList {
+    banned: Mutex<HashMap<IpAddr, Instant>>,
+}
+impl BanList {
+    pub fn ban(&self, ip: IpAddr, duration: Duration) {
+        if let Ok(mut banned) = self.banned.lock() {
+            let until = Instant::now() + duration;
+            let entry = banned.entry(ip).or_insert(until);
+            *entry = (*entry).max(until);
+        }
+    }
+
+    pub fn unban(&self, ip: &IpAddr) -> bool {
+        self.banned
+            .lock()
+            .map(|mut b| b.remove(ip).is_some())
+            .unwrap_or_default()
+    }
+
+    pub fn is_banned(&self, ip: &IpAddr) -> bool {
+        if let Ok(mut banned) = self.banned.lock() {
+            let now = Instant::now();
+            banned.retain(|_, until| *until > now);
+            banned.contains_key(ip)
+        } else {
+            false
+        }
+    }
+
+    pub fn banned(&self) -> Vec<(IpAddr, Duration)> {
+        let now = Instant::now();
+        self.banned
+            .lock()
+            .map(|b| {
+                b.iter()
+                    .filter(|(_, until)| **until > now)
+                    .map(|(ip, until)| (*ip, *until - now))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+//Shared by every connection of a server or client, bans are recorded by IP address
+#[derive(Debug)]
+pub struct PeerPolicy {
+    pub rate_limits: RateLimits,
+    pub max_message_size: usize,
+    pub max_peers: usize,
+    //Inbound messages being handled at once per connection, responses to our own requests are not
+    //counted. Messages over the limit are dropped, or the peer is banned when ban_on_overload is set
+    pub max_pending_handlers: usize,
+    pub ban_on_overload: bool,
+    pub ban_time: Duration,
+    pub bans: BanList,
+}
+impl Default for PeerPolicy {
+    fn default() -> Self {
+        Self {
+            rate_limits: RateLimits::default(),
+            max_message_size: 100 * MB as usize,
+            max_peers: 80,
+            max_pending_handlers: 256,
+            ban_on_overload: false,
+            ban_time: Duration::from_secs(300),
+            bans: BanList::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitViolation {
+    MessageTooLarge {
+        msg_type: ProtocolMessageTypes,
+        size: usize,
+        limit: u64,
+    },
+    TooFrequent {
+        msg_type: ProtocolMessageTypes,
+        limit: u64,
+    },
+    TotalSizeExceeded {
+        msg_type: ProtocolMessageTypes,
+        limit: u64,
+    },
+    NonTxLimitExceeded,
+    TooManyPendingHandlers {
+        msg_type: ProtocolMessageTypes,
+        limit: usize,
+    },
+}
+impl Display for RateLimitViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RateLimitViolation::MessageTooLarge {
+                msg_type,
+                size,
+                limit,
+            } => write!(
+                f,
+                "{msg_type:?} of {size} bytes is over the {limit} byte limit"
+            ),
+            RateLimitViolation::TooFrequent { msg_type, limit } => {
+                write!(f, "More than {limit} {msg_type:?} messages in the window")
+            }
+            RateLimitViolation::TotalSizeExceeded { msg_type, limit } => {
+                write!(
+                    f,
+                    "{msg_type:?} messages exceeded {limit} bytes in the window"
+                )
+            }
+            RateLimitViolation::TooManyPendingHandlers { msg_type, limit } => {
+                write!(
+                    f,
+                    "{msg_type:?} arrived with {limit} messages still being handled"
+                )
+            }
+            RateLimitViolation::NonTxLimitExceeded => {
+                write!(
+                    f,
+                    "Exceeded the combined limit for non transaction messages"
+                )
+            }
+        }
+    }
+}
+
+pub struct RateLimiter {
+    policy: std::sync::Arc<PeerPolicy>,
+    window_start: Instant,
+    counts: HashMap<u8, (u64, u64)>,
+    non_tx_count: u64,
+    non_tx_size: u64,
+}
+impl RateLimiter {
+    pub fn new(policy: std::sync::Arc<PeerPolicy>) -> Self {
+        Self {
+            policy,
+            window_start: Instant::now(),
+            counts: HashMap::new(),
+            non_tx_count: 0,
+            non_tx_size: 0,
+        }
+    }
+
+    pub fn policy(&self) -> &PeerPolicy {
+        &self.policy
+    }
+
+    pub fn process(
+        &mut self,
+        msg_type: ProtocolMessageTypes,
+        size: usize,
+    ) -> Result<(), RateLimitViolation> {
+        let rate_limits = &self.policy.rate_limits;
+        if self.window_start.elapsed() >= rate_limits.window {
+            self.window_start = Instant::now();
+            self.counts.clear();
+            self.non_tx_count = 0;
+            self.non_tx_size = 0;
+        }
+        let (limit, is_tx) = rate_limits.limit_for(msg_type);
+        if size as u64 > limit.max_size as u64 {
+            return Err(RateLimitViolation::MessageTooLarge {
+                msg_type,
+                size,
+                limit: limit.max_size as u64,
+            });
+        }
+        let (count, total) = self.counts.entry(msg_type as u8).or_default();
+        *count += 1;
+        *total += size as u64;
+        let frequency = rate_limits.scale(limit.frequency as u64);
+        if *count > frequency {
+            return Err(RateLimitViolation::TooFrequent {
+                msg_type,
+                limit: frequency,
+            });
+        }
+        let total_limit = rate_limits.scale(limit.total_size());
+        if *total > total_limit {
+            return Err(RateLimitViolation::TotalSizeExceeded {
+                msg_type,
+                limit: total_limit,
+            });
+        }
+        if !is_tx {
+            self.non_tx_count += 1;
+            self.non_tx_size += size as u64;
+            if self.non_tx_count > rate_limits.scale(rate_limits.non_tx_frequency as u64)
+                || self.non_tx_size > rate_limits.scale(rate_limits.non_tx_max_total_size)
+            {
+                return Err(RateLimitViolation::NonTxLimitExceeded);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_rate_limiter() {
+    use std::sync::Arc;
+    let mut policy = PeerPolicy::default();
+    policy
+        .rate_limits
+        .set_limit(ProtocolMessageTypes::NewPeak, RateLimit::new(3, 512));
+    let mut limiter = RateLimiter::new(Arc::new(policy));
+    for _ in 0..3 {
+        assert!(limiter.process(ProtocolMessageTypes::NewPeak, 100).is_ok());
+    }
+    assert_eq!(
+        limiter.process(ProtocolMessageTypes::NewPeak, 100),
+        Err(RateLimitViolation::TooFrequent {
+            msg_type: ProtocolMessageTypes::NewPeak,
+            limit: 3
+        })
+    );
+    assert!(matches!(
+        limiter.process(ProtocolMessageTypes::RequestBlock, 101),
+        Err(RateLimitViolation::MessageTooLarge { .. })
+    ));
+    let unknown = ProtocolMessageTypes::from(250u8);
+    assert_eq!(unknown, ProtocolMessageTypes::Unknown);
+    assert!(limiter.process(unknown, 10 * KB as usize).is_ok());
+    assert!(matches!(
+        limiter.process(unknown, MB as usize + 1),
+        Err(RateLimitViolation::MessageTooLarge { .. })
+    ));
+    let bans = BanList::default();
+    let ip: IpAddr = "10.0.0.1".parse().unwrap();
+    bans.ban(ip, Duration::from_secs(60));
+    assert!(bans.is_banned(&ip));
+    assert!(bans.unban(&ip));
+    bans.ban(ip, Duration::ZERO);
+    assert!(!bans.is_banned(&ip));
+}
@@ -1,15 +1,37 @@
+use crate::blockchain::sized_bytes::{Bytes32, SizedBytes};
 use dg_xch_macros::ChiaSerial;
-use rustls::client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
-use rustls::{Certificate, DigitallySignedStruct, ServerName};
+use dg_xch_serialize::hash_256;
+use rustls::client::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier, WebPkiVerifier,
+};
+use rustls::{Certificate, DigitallySignedStruct, RootCertStore, ServerName};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{Error, ErrorKind};
 use std::time::SystemTime;
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Capability {
     Base = 1,
     BlockHeaders = 2,
     RateLimitsV2 = 3,
     NoneResponse = 4,
 }
+impl TryFrom<u16> for Capability {
+    type Error = Error;
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Capability::Base),
+            2 => Ok(Capability::BlockHeaders),
+            3 => Ok(Capability::RateLimitsV2),
+            4 => Ok(Capability::NoneResponse),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unknown capability: {value}"),
+            )),
+        }
+    }
+}
 
 #[derive(ChiaSerial, Serialize, Deserialize, Debug, Clone)]
 pub struct Handshake {
@@ -29,6 +51,73 @@ pub const CAPABILITIES: [(u16, &str); 3] = [
     //(Capability::NoneResponse as u16, "1"), //This is not currently supported, Causes the Fullnode to close the connection
 ];
 
+//Set of enabled capabilities, either advertised by this side or negotiated with a peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    enabled: HashSet<Capability>,
+}
+impl Default for Capabilities {
+    //The capabilities advertised when none are configured
+    fn default() -> Self {
+        Self::from_handshake(
+            &CAPABILITIES
+                .iter()
+                .map(|e| (e.0, e.1.to_string()))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+impl Capabilities {
+    pub fn new(capabilities: &[Capability]) -> Self {
+        Self {
+            enabled: capabilities.iter().copied().collect(),
+        }
+    }
+
+    //Used until a handshake is received, nothing is assumed about the peer
+    pub fn none() -> Self {
+        Self {
+            enabled: HashSet::new(),
+        }
+    }
+
+    //Only known capabilities with a value of "1" are enabled, others are ignored like the reference node does
+    pub fn from_handshake(capabilities: &[(u16, String)]) -> Self {
+        Self {
+            enabled: capabilities
+                .iter()
+                .filter(|(_, value)| value == "1")
+                .filter_map(|(id, _)| Capability::try_from(*id).ok())
+                .collect(),
+        }
+    }
+
+    pub fn to_handshake(&self) -> Vec<(u16, String)> {
+        let mut capabilities: Vec<(u16, String)> = self
+            .enabled
+            .iter()
+            .map(|c| (*c as u16, "1".to_string()))
+            .collect();
+        capabilities.sort();
+        capabilities
+    }
+
+    //Features are only used when both sides advertised them
+    pub fn negotiate(&self, peer: &Capabilities) -> Self {
+        Self {
+            enabled: self.enabled.intersection(&peer.enabled).copied().collect(),
+        }
+    }
+
+    pub fn has(&self, capability: Capability) -> bool {
+        self.enabled.contains(&capability)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Capability> {
+        self.enabled.iter()
+    }
+}
+
 pub struct NoCertificateVerification;
 
 impl ServerCertVerifier for NoCertificateVerification {
@@ -62,3 +151,134 @@ impl ServerCertVerifier for NoCertificateVerification {
         Ok(HandshakeSignatureValid::assertion())
     }
 }
+
+//Chia certificates are issued for chia.net rather than the host of the node
+const CHIA_CERT_NAME: &str = "chia.net";
+
+//Accepts servers whose certificate chains to one of the roots
+pub struct CaCertificateVerification {
+    verifier: WebPkiVerifier,
+}
+impl CaCertificateVerification {
+    pub fn new(roots: RootCertStore) -> Self {
+        Self {
+            verifier: WebPkiVerifier::new(roots, None),
+        }
+    }
+}
+impl ServerCertVerifier for CaCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        _server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let server_name = ServerName::try_from(CHIA_CERT_NAME)
+            .map_err(|e| rustls::Error::General(format!("Invalid Server Name: {e:?}")))?;
+        self.verifier.verify_server_cert(
+            end_entity,
+            intermediates,
+            &server_name,
+            scts,
+            ocsp_response,
+            now,
+        )
+    }
+}
+
+//Accepts only the server whose certificate hashes to node_id, the same id used for peer_id
+pub struct PinnedCertificateVerification {
+    pub node_id: Bytes32,
+}
+impl ServerCertVerifier for PinnedCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let node_id = Bytes32::new(&hash_256(&end_entity.0));
+        if node_id == self.node_id {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(format!(
+                "Server node id {node_id} does not match pinned node id {}",
+                self.node_id
+            )))
+        }
+    }
+}
+
+#[test]
+fn test_server_cert_verification() {
+    use crate::ssl::{
+        generate_ca_signed_cert_data, load_certs_from_bytes, CHIA_CA_CRT, CHIA_CA_KEY,
+    };
+    let (cert_bytes, _) =
+        generate_ca_signed_cert_data(CHIA_CA_CRT.as_bytes(), CHIA_CA_KEY.as_bytes()).unwrap();
+    let cert = load_certs_from_bytes(&cert_bytes).unwrap().remove(0);
+    let server_name = ServerName::try_from("example.com").unwrap();
+    let verify = |verifier: &dyn ServerCertVerifier| {
+        verifier
+            .verify_server_cert(
+                &cert,
+                &[],
+                &server_name,
+                &mut std::iter::empty(),
+                &[],
+                SystemTime::now(),
+            )
+            .is_ok()
+    };
+    let mut roots = RootCertStore::empty();
+    for ca in load_certs_from_bytes(CHIA_CA_CRT.as_bytes()).unwrap() {
+        roots.add(&ca).unwrap();
+    }
+    assert!(verify(&CaCertificateVerification::new(roots)));
+    let (other_ca, _) =
+        generate_ca_signed_cert_data(CHIA_CA_CRT.as_bytes(), CHIA_CA_KEY.as_bytes()).unwrap();
+    let mut other_roots = RootCertStore::empty();
+    other_roots
+        .add(&load_certs_from_bytes(&other_ca).unwrap()[0])
+        .unwrap();
+    assert!(!verify(&CaCertificateVerification::new(other_roots)));
+    assert!(verify(&PinnedCertificateVerification {
+        node_id: Bytes32::new(&hash_256(&cert.0)),
+    }));
+    assert!(!verify(&PinnedCertificateVerification {
+        node_id: Bytes32::default(),
+    }));
+}
+
+#[test]
+fn test_capabilities() {
+    let ours = Capabilities::default();
+    assert!(ours.has(Capability::Base));
+    assert!(!ours.has(Capability::NoneResponse));
+    assert_eq!(
+        ours.to_handshake(),
+        CAPABILITIES
+            .iter()
+            .map(|e| (e.0, e.1.to_string()))
+            .collect::<Vec<_>>()
+    );
+    let peer = Capabilities::from_handshake(&[
+        (1, "1".to_string()),
+        (2, "0".to_string()),
+        (4, "1".to_string()),
+        (99, "1".to_string()),
+    ]);
+    assert_eq!(
+        peer,
+        Capabilities::new(&[Capability::Base, Capability::NoneResponse])
+    );
+    let negotiated = ours.negotiate(&peer);
+    assert_eq!(negotiated, Capabilities::new(&[Capability::Base]));
+    assert!(!Capabilities::none().has(Capability::Base));
+}
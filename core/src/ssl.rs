@@ -1,12 +1,17 @@
+use crate::blockchain::sized_bytes::{Bytes32, SizedBytes};
 use der::asn1::{Ia5String, UtcTime};
 use der::pem::LineEnding;
-use der::{DateTime, EncodePem};
-use log::{error, info};
+use der::{DateTime, Decode, Encode, EncodePem};
+use dg_xch_serialize::hash_256;
+use log::{error, info, warn};
 use rand::Rng;
 use rsa::pkcs1::DecodeRsaPrivateKey;
 use rsa::pkcs1v15::SigningKey;
 use rsa::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey};
-use rustls::server::{ClientCertVerified, ClientCertVerifier, ParsedCertificate};
+use rustls::server::{
+    ClientCertVerified, ClientCertVerifier, ClientHello, ParsedCertificate, ResolvesServerCert,
+};
+use rustls::sign::{any_supported_type, CertifiedKey};
 use rustls::{DistinguishedName, PrivateKey, RootCertStore};
 use rustls_pemfile::{certs, read_one, Item};
 use serde::{Deserialize, Serialize};
@@ -16,9 +21,10 @@ use std::fs;
 use std::fs::{create_dir_all, File, OpenOptions};
 use std::io::{BufReader, Error, ErrorKind, Write};
 use std::ops::{Add, Sub};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime};
 use x509_cert::builder::{Builder, CertificateBuilder, Profile};
 use x509_cert::der::DecodePem;
@@ -599,6 +605,344 @@ pub struct SslInfo {
     pub ca: SslCertInfo,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertInfo {
+    pub path: PathBuf,
+    pub key_path: Option<PathBuf>,
+    pub subject: String,
+    pub issuer: String,
+    //Same id peers derive from the certificate, sha256 of the DER encoding
+    pub node_id: Bytes32,
+    pub not_before: SystemTime,
+    pub not_after: SystemTime,
+    //None when there is no key file next to the certificate
+    pub key_matches: Option<bool>,
+}
+impl CertInfo {
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.not_after.duration_since(SystemTime::now()).ok()
+    }
+    pub fn is_expired(&self) -> bool {
+        self.expires_in().is_none()
+    }
+    pub fn expires_within(&self, window: Duration) -> bool {
+        self.expires_in().map(|d| d <= window).unwrap_or(true)
+    }
+}
+
+pub fn inspect_cert(crt_path: &Path, key_path: Option<&Path>) -> Result<CertInfo, Error> {
+    let cert_data = fs::read(crt_path)?;
+    let der = certs(&mut cert_data.as_slice())?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("No Certificate found in {crt_path:?}"),
+            )
+        })?;
+    let cert = Certificate::from_pem(&cert_data).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse Cert {crt_path:?}: {e:?}"),
+        )
+    })?;
+    let key_matches = match key_path {
+        Some(key_path) => Some(
+            key_matches_cert(&cert, &load_private_key(&key_path.to_string_lossy())?).map_err(
+                |e| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Failed to parse Key {key_path:?}: {e}"),
+                    )
+                },
+            )?,
+        ),
+        None => None,
+    };
+    let validity = &cert.tbs_certificate.validity;
+    Ok(CertInfo {
+        path: crt_path.to_path_buf(),
+        key_path: key_path.map(Path::to_path_buf),
+        subject: cert.tbs_certificate.subject.to_string(),
+        issuer: cert.tbs_certificate.issuer.to_string(),
+        node_id: Bytes32::new(&hash_256(&der)),
+        not_before: validity.not_before.to_system_time(),
+        not_after: validity.not_after.to_system_time(),
+        key_matches,
+    })
+}
+
+fn key_matches_cert(cert: &Certificate, key: &PrivateKey) -> Result<bool, Error> {
+    let public_key = rsa::RsaPrivateKey::from_pkcs8_der(&key.0)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_der(&key.0))
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{e:?}")))?
+        .to_public_key()
+        .to_public_key_der()
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{e:?}")))?;
+    let cert_public_key = cert
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{e:?}")))?;
+    Ok(public_key.as_bytes() == cert_public_key.as_slice())
+}
+
+//Inspects every .crt under ssl_dir, pairing each with the .key of the same name. Backups are skipped
+pub fn inspect_all_ssl(ssl_dir: &Path) -> Result<Vec<CertInfo>, Error> {
+    let mut infos = vec![];
+    let mut dirs = vec![ssl_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs::read_dir(&dir)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<Vec<PathBuf>, Error>>()?;
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                if path
+                    .file_name()
+                    .map(|n| n != SSL_BACKUP_DIR)
+                    .unwrap_or(true)
+                {
+                    dirs.push(path);
+                }
+            } else if path.extension().map(|e| e == "crt").unwrap_or(false) {
+                let key_path = path.with_extension("key");
+                let key_path = key_path.exists().then_some(key_path);
+                infos.push(inspect_cert(&path, key_path.as_deref())?);
+            }
+        }
+    }
+    Ok(infos)
+}
+
+//Logs a warning for every certificate expiring within warn_within and returns them
+pub fn check_ssl_expiry(ssl_dir: &Path, warn_within: Duration) -> Result<Vec<CertInfo>, Error> {
+    let expiring: Vec<CertInfo> = inspect_all_ssl(ssl_dir)?
+        .into_iter()
+        .filter(|c| c.expires_within(warn_within))
+        .collect();
+    for cert in &expiring {
+        match cert.expires_in() {
+            Some(remaining) => warn!(
+                "Certificate {:?} expires in {} days",
+                cert.path,
+                remaining.as_secs() / 86400
+            ),
+            None => warn!("Certificate {:?} has expired", cert.path),
+        }
+    }
+    Ok(expiring)
+}
+
+const SSL_BACKUP_DIR: &str = "backup";
+
+//Regenerates every node certificate from the existing CAs, the old files are copied to
+//ssl_dir/backup/<unix time>/ first. Returns the backup directory
+pub fn rotate_node_certs(ssl_dir: &Path) -> Result<PathBuf, Error> {
+    let ca_dir = ssl_dir.join("ca");
+    let private_ca_crt = fs::read(ca_dir.join("private_ca.crt"))?;
+    let private_ca_key = fs::read(ca_dir.join("private_ca.key"))?;
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{e:?}")))?
+        .as_secs();
+    let backup_dir = ssl_dir.join(SSL_BACKUP_DIR).join(timestamp.to_string());
+    for (prefix, nodes) in [
+        ("private", ALL_PRIVATE_NODE_NAMES.as_slice()),
+        ("public", ALL_PUBLIC_NODE_NAMES.as_slice()),
+    ] {
+        for node_name in nodes {
+            for ext in ["crt", "key"] {
+                let file_name = format!("{prefix}_{node_name}.{ext}");
+                let path = ssl_dir.join(node_name).join(&file_name);
+                if path.exists() {
+                    let node_backup_dir = backup_dir.join(node_name);
+                    create_dir_all(&node_backup_dir)?;
+                    fs::copy(&path, node_backup_dir.join(&file_name))?;
+                }
+            }
+        }
+    }
+    info!("Backed up node certs to {backup_dir:?}");
+    //Unlike create_all_ssl a failed write is returned, the backup holds the previous files
+    for (prefix, nodes, ca_crt, ca_key) in [
+        (
+            "private",
+            ALL_PRIVATE_NODE_NAMES.as_slice(),
+            private_ca_crt.as_slice(),
+            private_ca_key.as_slice(),
+        ),
+        (
+            "public",
+            ALL_PUBLIC_NODE_NAMES.as_slice(),
+            CHIA_CA_CRT.as_bytes(),
+            CHIA_CA_KEY.as_bytes(),
+        ),
+    ] {
+        for node_name in nodes {
+            let node_dir = ssl_dir.join(node_name);
+            create_dir_all(&node_dir)?;
+            generate_ca_signed_cert(
+                &node_dir.join(format!("{prefix}_{node_name}.crt")),
+                ca_crt,
+                &node_dir.join(format!("{prefix}_{node_name}.key")),
+                ca_key,
+                true,
+            )
+            .map_err(|e| {
+                Error::new(
+                    e.kind(),
+                    format!("Failed to rotate {prefix} {node_name} cert: {e}"),
+                )
+            })?;
+        }
+    }
+    Ok(backup_dir)
+}
+
+//Serves the current certificate to new TLS connections, reloading it leaves open connections as they are
+pub struct ReloadableCertResolver {
+    key: RwLock<Arc<CertifiedKey>>,
+}
+impl ReloadableCertResolver {
+    pub fn new(certs: Vec<rustls::Certificate>, key: PrivateKey) -> Result<Arc<Self>, Error> {
+        Ok(Arc::new(Self {
+            key: RwLock::new(Self::certified_key(certs, key)?),
+        }))
+    }
+
+    pub fn reload(&self, certs: Vec<rustls::Certificate>, key: PrivateKey) -> Result<(), Error> {
+        let certified_key = Self::certified_key(certs, key)?;
+        *self.key.write().unwrap_or_else(|e| e.into_inner()) = certified_key;
+        Ok(())
+    }
+
+    pub fn reload_from_files(&self, crt_path: &str, key_path: &str) -> Result<(), Error> {
+        self.reload(load_certs(crt_path)?, load_private_key(key_path)?)
+    }
+
+    fn certified_key(
+        certs: Vec<rustls::Certificate>,
+        key: PrivateKey,
+    ) -> Result<Arc<CertifiedKey>, Error> {
+        //Cert and key are written separately, a pair caught halfway through a rotation is refused
+        let cert = certs
+            .first()
+            .map(|c| Certificate::from_der(&c.0))
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "No Certificate to load"))?
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Failed to parse Cert: {e:?}"),
+                )
+            })?;
+        if !key_matches_cert(&cert, &key)? {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Private Key does not match the Certificate",
+            ));
+        }
+        let signing_key = any_supported_type(&key).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid Private Key: {:?}", e),
+            )
+        })?;
+        Ok(Arc::new(CertifiedKey::new(certs, signing_key)))
+    }
+}
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.key.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+}
+
+fn modified_time(paths: &[&str]) -> Option<SystemTime> {
+    paths
+        .iter()
+        .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+//Reloads the resolver whenever either file changes on disk, checking every interval until run is false
+pub async fn watch_cert_files(
+    resolver: Arc<ReloadableCertResolver>,
+    crt_path: String,
+    key_path: String,
+    interval: Duration,
+    run: Arc<AtomicBool>,
+) {
+    let mut last_modified = modified_time(&[&crt_path, &key_path]);
+    let mut waited = Duration::ZERO;
+    while run.load(Ordering::Relaxed) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        waited += Duration::from_millis(100);
+        if waited < interval {
+            continue;
+        }
+        waited = Duration::ZERO;
+        let modified = modified_time(&[&crt_path, &key_path]);
+        if modified != last_modified {
+            match resolver.reload_from_files(&crt_path, &key_path) {
+                Ok(()) => {
+                    info!("Reloaded Cert {crt_path}");
+                    last_modified = modified;
+                }
+                Err(e) => error!("Failed to reload Cert {crt_path}: {:?}", e),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cert_lifecycle() {
+    let ssl_dir = std::env::temp_dir().join(format!("dg_xch_ssl_{}", rand::random::<u32>()));
+    create_all_ssl(&ssl_dir, false).unwrap();
+    let infos = inspect_all_ssl(&ssl_dir).unwrap();
+    assert_eq!(
+        infos.len(),
+        2 + ALL_PRIVATE_NODE_NAMES.len() + ALL_PUBLIC_NODE_NAMES.len()
+    );
+    assert!(infos.iter().all(|c| c.key_matches == Some(true)));
+    assert!(check_ssl_expiry(&ssl_dir, Duration::from_secs(86400))
+        .unwrap()
+        .is_empty());
+    let farmer = |infos: &[CertInfo]| {
+        infos
+            .iter()
+            .find(|c| c.path.ends_with("farmer/private_farmer.crt"))
+            .map(|c| c.node_id)
+            .unwrap()
+    };
+    let old_id = farmer(&infos);
+    let backup_dir = rotate_node_certs(&ssl_dir).unwrap();
+    assert!(backup_dir.join("farmer/private_farmer.crt").exists());
+    let rotated = inspect_all_ssl(&ssl_dir).unwrap();
+    assert_eq!(rotated.len(), infos.len());
+    assert_ne!(farmer(&rotated), old_id);
+    assert!(validate_all_ssl(&ssl_dir));
+    let path = |node: &str, ext: &str| {
+        ssl_dir
+            .join(node)
+            .join(format!("private_{node}.{ext}"))
+            .to_string_lossy()
+            .to_string()
+    };
+    let resolver = ReloadableCertResolver::new(
+        load_certs(&path("farmer", "crt")).unwrap(),
+        load_private_key(&path("farmer", "key")).unwrap(),
+    )
+    .unwrap();
+    let e = resolver
+        .reload_from_files(&path("farmer", "crt"), &path("harvester", "key"))
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidData);
+    fs::write(ssl_dir.join("ca/private_ca.key"), b"not a key").unwrap();
+    assert!(rotate_node_certs(&ssl_dir).is_err());
+    fs::remove_dir_all(&ssl_dir).unwrap();
+}
+
 #[test]
 pub fn test_ssl() {
     use simple_logger::SimpleLogger;
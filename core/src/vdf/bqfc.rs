@@ -0,0 +1,182 @@
+use crate::vdf::classgroup::Form;
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+use std::io::{Error, ErrorKind};
+
+//Compressed form encoding used by chiavdf, 100 bytes for discriminants up to 1024 bits
+pub const BQFC_MAX_D_BITS: usize = 1024;
+pub const BQFC_FORM_SIZE: usize = BQFC_MAX_D_BITS.div_ceil(32) * 3 + 4;
+
+const BQFC_B_SIGN: u8 = 1 << 0;
+const BQFC_T_SIGN: u8 = 1 << 1;
+const BQFC_IS_1: u8 = 1 << 2;
+const BQFC_IS_GEN: u8 = 1 << 3;
+
+//A reduced form is stored as a, a partial extended gcd cofactor t of (a, b) and enough of b to lift
+//b mod a/g back to b, where g = gcd(a, t)
+struct CompressedForm {
+    a: BigInt,
+    t: BigInt,
+    g: BigInt,
+    b0: BigInt,
+    b_sign: bool,
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.to_string())
+}
+
+fn compress(form: &Form) -> CompressedForm {
+    let b_abs = form.b.abs();
+    if form.a == form.b {
+        return CompressedForm {
+            a: form.a.clone(),
+            t: BigInt::zero(),
+            g: BigInt::one(),
+            b0: BigInt::zero(),
+            b_sign: false,
+        };
+    }
+    //Euclid on (a, |b|) stopping at the first remainder below sqrt(a), r = t * |b| mod a
+    let bound = form.a.sqrt();
+    let (mut r2, mut r1) = (form.a.clone(), b_abs.clone());
+    let (mut co2, mut co1) = (BigInt::zero(), -BigInt::one());
+    while !r1.is_zero() && r1 > bound {
+        let (q, r) = r2.div_mod_floor(&r1);
+        r2 = r1;
+        r1 = r;
+        let co = &co2 - &q * &co1;
+        co2 = co1;
+        co1 = co;
+    }
+    let t = -co1;
+    let g = form.a.gcd(&t);
+    let b0 = &b_abs / (&form.a / &g);
+    CompressedForm {
+        a: form.a.clone(),
+        t,
+        g,
+        b0,
+        b_sign: form.b.is_negative(),
+    }
+}
+
+fn decompress(c: &CompressedForm, discriminant: &BigInt) -> Result<Form, Error> {
+    if c.t.is_zero() {
+        return Form::from_abd(c.a.clone(), c.a.clone(), discriminant);
+    }
+    if c.g.is_zero() || !c.a.is_positive() {
+        return Err(invalid("Invalid compressed form"));
+    }
+    let x_squared = (&c.t * &c.t * discriminant).mod_floor(&c.a);
+    let x = x_squared.sqrt();
+    if &x * &x != x_squared {
+        return Err(invalid("Compressed form is not on the discriminant"));
+    }
+    let d = &c.a / &c.g;
+    let (x, t) = (&x / &c.g, &c.t / &c.g);
+    let b = if d.is_one() {
+        BigInt::zero()
+    } else {
+        let t_inv = t
+            .mod_floor(&d)
+            .modinv(&d)
+            .ok_or_else(|| invalid("Compressed form t is not invertible"))?;
+        (x * t_inv).mod_floor(&d)
+    };
+    let b = b + &c.b0 * &d;
+    Form::from_abd(c.a.clone(), if c.b_sign { -b } else { b }, discriminant)
+}
+
+fn export_le(out: &mut [u8], value: &BigInt) -> Result<(), Error> {
+    let (_, bytes) = value.to_bytes_le();
+    if value.is_zero() {
+        return Ok(());
+    }
+    if bytes.len() > out.len() {
+        return Err(invalid("Value does not fit the compressed form"));
+    }
+    out[..bytes.len()].copy_from_slice(&bytes);
+    Ok(())
+}
+
+fn import_le(bytes: &[u8]) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, bytes)
+}
+
+pub fn serialize_form(form: &Form, d_bits: usize) -> Result<[u8; BQFC_FORM_SIZE], Error> {
+    let d_bits = (d_bits + 31) & !31;
+    if d_bits > BQFC_MAX_D_BITS {
+        return Err(invalid("Discriminant is too large for compressed forms"));
+    }
+    let form = form.reduced();
+    let mut out = [0u8; BQFC_FORM_SIZE];
+    if form.b.is_one() && form.a <= BigInt::from(2) {
+        out[0] = if form.a.is_one() {
+            BQFC_IS_1
+        } else {
+            BQFC_IS_GEN
+        };
+        return Ok(out);
+    }
+    let c = compress(&form);
+    let g_size = (c.g.bits() as usize).div_ceil(8).max(1) - 1;
+    if g_size >= d_bits / 32 {
+        return Err(invalid("Compressed form g is too large"));
+    }
+    out[0] =
+        if c.b_sign { BQFC_B_SIGN } else { 0 } | if c.t.is_negative() { BQFC_T_SIGN } else { 0 };
+    out[1] = g_size as u8;
+    let mut offset = 2;
+    for (value, size) in [
+        (&c.a / &c.g, d_bits / 16 - g_size),
+        (c.t.abs() / &c.g, d_bits / 32 - g_size),
+        (c.g.clone(), g_size + 1),
+        (c.b0.clone(), g_size + 1),
+    ] {
+        export_le(&mut out[offset..offset + size], &value)?;
+        offset += size;
+    }
+    Ok(out)
+}
+
+pub fn deserialize_form(discriminant: &BigInt, data: &[u8], d_bits: usize) -> Result<Form, Error> {
+    let d_bits = (d_bits + 31) & !31;
+    if data.len() != BQFC_FORM_SIZE || d_bits > BQFC_MAX_D_BITS {
+        return Err(invalid("Invalid compressed form size"));
+    }
+    if data[0] & (BQFC_IS_1 | BQFC_IS_GEN) != 0 {
+        if data[1..].iter().any(|b| *b != 0) {
+            return Err(invalid("Invalid identity or generator encoding"));
+        }
+        return Ok(if data[0] & BQFC_IS_GEN != 0 {
+            Form::generator(discriminant)
+        } else {
+            Form::identity(discriminant)
+        });
+    }
+    let g_size = data[1] as usize;
+    if g_size >= d_bits / 32 {
+        return Err(invalid("Compressed form g is too large"));
+    }
+    let mut offset = 2;
+    let mut read = |size: usize| {
+        let value = import_le(&data[offset..offset + size]);
+        offset += size;
+        value
+    };
+    let a = read(d_bits / 16 - g_size);
+    let t = read(d_bits / 32 - g_size);
+    let g = read(g_size + 1);
+    let b0 = read(g_size + 1);
+    let t = &t * &g;
+    let c = CompressedForm {
+        a: &a * &g,
+        t: if data[0] & BQFC_T_SIGN != 0 { -t } else { t },
+        g,
+        b0,
+        b_sign: data[0] & BQFC_B_SIGN != 0,
+    };
+    decompress(&c, discriminant)
+}
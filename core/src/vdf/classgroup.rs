@@ -0,0 +1,123 @@
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+use std::io::{Error, ErrorKind};
+
+//Binary quadratic form ax^2 + bxy + cy^2 with negative discriminant b^2 - 4ac
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Form {
+    pub a: BigInt,
+    pub b: BigInt,
+    pub c: BigInt,
+}
+impl Form {
+    pub fn from_abd(a: BigInt, b: BigInt, discriminant: &BigInt) -> Result<Self, Error> {
+        if !a.is_positive() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Form a must be positive",
+            ));
+        }
+        let (c, rem) = (&b * &b - discriminant).div_rem(&(BigInt::from(4) * &a));
+        if !rem.is_zero() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Form does not match the discriminant",
+            ));
+        }
+        Ok(Self { a, b, c })
+    }
+
+    pub fn identity(discriminant: &BigInt) -> Self {
+        Self {
+            a: BigInt::one(),
+            b: BigInt::one(),
+            c: (BigInt::one() - discriminant) / 4,
+        }
+    }
+
+    //The default starting element of chia VDFs, requires a discriminant of 1 mod 8
+    pub fn generator(discriminant: &BigInt) -> Self {
+        Self {
+            a: BigInt::from(2),
+            b: BigInt::one(),
+            c: (BigInt::one() - discriminant) / 8,
+        }
+    }
+
+    pub fn discriminant(&self) -> BigInt {
+        &self.b * &self.b - BigInt::from(4) * &self.a * &self.c
+    }
+
+    pub fn normalized(&self) -> Self {
+        let Self { a, b, c } = self;
+        if -a < *b && b <= a {
+            return self.clone();
+        }
+        let r = (a - b).div_floor(&(BigInt::from(2) * a));
+        Self {
+            a: a.clone(),
+            b: b + BigInt::from(2) * &r * a,
+            c: a * &r * &r + b * &r + c,
+        }
+    }
+
+    //Reduced forms are unique per class, |b| <= a <= c and b >= 0 when |b| == a or a == c
+    pub fn reduced(&self) -> Self {
+        let mut form = self.normalized();
+        while form.a > form.c || (form.a == form.c && form.b.is_negative()) {
+            let s = (&form.c + &form.b).div_floor(&(BigInt::from(2) * &form.c));
+            form = Self {
+                a: form.c.clone(),
+                b: -&form.b + BigInt::from(2) * &s * &form.c,
+                c: &form.c * &s * &s - &form.b * &s + &form.a,
+            };
+        }
+        form.normalized()
+    }
+
+    pub fn compose(&self, other: &Self) -> Self {
+        let (a1, b1, c1) = (&self.a, &self.b, &self.c);
+        let (a2, b2) = (&other.a, &other.b);
+        let g = (b2 + b1).div_floor(&BigInt::from(2));
+        let h = (b2 - b1).div_floor(&BigInt::from(2));
+        let w = a1.gcd(a2).gcd(&g);
+        let s = a1 / &w;
+        let t = a2 / &w;
+        let u = &g / &w;
+        //Solves k*t - l*s = h, k*u - m*s = c2 and l*u - m*t = c1 for k, l and m
+        let (mu, v) = solve_mod(&(&t * &u), &(&h * &u + &s * c1), &(&s * &t));
+        let (lambda, _) = solve_mod(&(&t * &v), &(&h - &t * &mu), &s);
+        let k = &mu + &v * &lambda;
+        let l = (&k * &t - &h).div_floor(&s);
+        let m = (&t * &u * &k - &h * &u - &s * c1).div_floor(&(&s * &t));
+        Self {
+            a: &s * &t,
+            b: &w * &u - (&k * &t + &l * &s),
+            c: &k * &l - &w * &m,
+        }
+        .reduced()
+    }
+
+    pub fn square(&self) -> Self {
+        self.compose(self)
+    }
+
+    pub fn pow(&self, exponent: &BigUint) -> Self {
+        let mut result = Self::identity(&self.discriminant());
+        for i in (0..exponent.bits()).rev() {
+            result = result.square();
+            if exponent.bit(i) {
+                result = result.compose(self);
+            }
+        }
+        result
+    }
+}
+
+//Returns (x0, step) so that every x0 + n * step solves a * x = b mod m
+fn solve_mod(a: &BigInt, b: &BigInt, m: &BigInt) -> (BigInt, BigInt) {
+    let gcd = a.extended_gcd(m);
+    let q = b.div_floor(&gcd.gcd);
+    ((q * gcd.x).mod_floor(m), m / &gcd.gcd)
+}
@@ -0,0 +1,318 @@
+pub mod bqfc;
+pub mod classgroup;
+
+use crate::blockchain::class_group_element::ClassgroupElement;
+use crate::blockchain::vdf_info::VdfInfo;
+use crate::blockchain::vdf_proof::VdfProof;
+use crate::consensus::constants::ConsensusConstants;
+use crate::vdf::bqfc::{deserialize_form, serialize_form, BQFC_FORM_SIZE};
+use crate::vdf::classgroup::Form;
+use log::debug;
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+use sha2::{Digest, Sha256};
+use std::io::{Error, ErrorKind};
+
+const B_BITS: usize = 264;
+const B_BYTES: usize = B_BITS / 8;
+const SEGMENT_SIZE: usize = 8 + B_BYTES + BQFC_FORM_SIZE;
+const SMALL_PRIMES: [u32; 24] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+const MILLER_RABIN_BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+//Expands seed with sha256 into length bits until the result, with the bitmask bits set, is prime
+pub fn hash_prime(seed: &[u8], length: usize, bitmask: &[u64]) -> BigUint {
+    let mut sprout = seed.to_vec();
+    loop {
+        let mut blob = Vec::with_capacity(length / 8);
+        while blob.len() * 8 < length {
+            for byte in sprout.iter_mut().rev() {
+                *byte = byte.wrapping_add(1);
+                if *byte != 0 {
+                    break;
+                }
+            }
+            let hash = Sha256::digest(&sprout);
+            let remaining = length / 8 - blob.len();
+            blob.extend_from_slice(&hash[..remaining.min(hash.len())]);
+        }
+        let mut p = BigUint::from_bytes_be(&blob);
+        for bit in bitmask {
+            p.set_bit(*bit, true);
+        }
+        if is_probable_prime(&p) {
+            return p;
+        }
+    }
+}
+
+pub fn is_probable_prime(n: &BigUint) -> bool {
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    if n.is_even() {
+        return *n == two;
+    }
+    for p in SMALL_PRIMES {
+        let p = BigUint::from(p);
+        if *n == p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
+        }
+    }
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap_or_default();
+    let d = &n_minus_one >> s;
+    'bases: for base in MILLER_RABIN_BASES {
+        let mut x = BigUint::from(base).modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+pub fn create_discriminant(challenge: &[u8], size_bits: usize) -> BigInt {
+    let bitmask = [0, 1, 2, size_bits as u64 - 1];
+    -BigInt::from(hash_prime(challenge, size_bits, &bitmask))
+}
+
+//Fiat-Shamir challenge prime for the proof that x reaches y
+fn get_b(x: &[u8], y: &[u8]) -> BigUint {
+    let mut seed = x.to_vec();
+    seed.extend_from_slice(y);
+    hash_prime(&seed, B_BITS, &[B_BITS as u64 - 1])
+}
+
+//proof^B * x^(2^iterations mod B)
+fn wesolowski_output(x: &Form, proof: &Form, b: &BigUint, iterations: u64) -> Form {
+    let r = BigUint::from(2u32).modpow(&BigUint::from(iterations), b);
+    proof.pow(b).compose(&x.pow(&r))
+}
+
+pub fn verify_wesolowski(
+    discriminant: &BigInt,
+    x_s: &[u8],
+    y_s: &[u8],
+    proof_s: &[u8],
+    iterations: u64,
+    d_bits: usize,
+) -> Result<bool, Error> {
+    let x = deserialize_form(discriminant, x_s, d_bits)?;
+    let y = deserialize_form(discriminant, y_s, d_bits)?;
+    let proof = deserialize_form(discriminant, proof_s, d_bits)?;
+    let b = get_b(x_s, y_s);
+    Ok(wesolowski_output(&x, &proof, &b, iterations) == y.reduced())
+}
+
+//Verifies a proof blob of y, proof and then depth segments of (iterations, B, proof), each segment
+//proving an intermediate element that becomes the input of the next one
+pub fn verify_n_wesolowski(
+    discriminant: &BigInt,
+    x_s: &[u8],
+    proof_blob: &[u8],
+    iterations: u64,
+    d_bits: usize,
+    depth: u8,
+) -> Result<bool, Error> {
+    if proof_blob.len() != 2 * BQFC_FORM_SIZE + depth as usize * SEGMENT_SIZE {
+        return Ok(false);
+    }
+    let mut x_bytes = x_s.to_vec();
+    let mut x = deserialize_form(discriminant, x_s, d_bits)?;
+    let mut iterations = iterations;
+    for segment in proof_blob[2 * BQFC_FORM_SIZE..].chunks(SEGMENT_SIZE).rev() {
+        let segment_iterations = u64::from_be_bytes(
+            segment[..8]
+                .try_into()
+                .map_err(|_| Error::new(ErrorKind::InvalidData, "Invalid segment"))?,
+        );
+        if segment_iterations > iterations {
+            return Ok(false);
+        }
+        let b = BigUint::from_bytes_be(&segment[8..8 + B_BYTES]);
+        let proof = deserialize_form(discriminant, &segment[8 + B_BYTES..], d_bits)?;
+        let y = wesolowski_output(&x, &proof, &b, segment_iterations);
+        let y_bytes = serialize_form(&y, d_bits)?;
+        if get_b(&x_bytes, &y_bytes) != b {
+            debug!("VDF segment challenge mismatch");
+            return Ok(false);
+        }
+        iterations -= segment_iterations;
+        x = y;
+        x_bytes = y_bytes.to_vec();
+    }
+    verify_wesolowski(
+        discriminant,
+        &x_bytes,
+        &proof_blob[..BQFC_FORM_SIZE],
+        &proof_blob[BQFC_FORM_SIZE..2 * BQFC_FORM_SIZE],
+        iterations,
+        d_bits,
+    )
+}
+
+//Checks a VdfProof the way the full node does, input is ignored for proofs normalized to identity
+pub fn validate_vdf_proof(
+    constants: &ConsensusConstants,
+    input: &ClassgroupElement,
+    info: &VdfInfo,
+    proof: &VdfProof,
+) -> bool {
+    let max_witness_size = constants.max_vdf_witness_size.to_u64().unwrap_or_default();
+    if proof.witness_type as u64 + 1 > max_witness_size {
+        return false;
+    }
+    let Some(d_bits) = constants.discriminant_size_bits.to_usize() else {
+        return false;
+    };
+    let input = if proof.normalized_to_identity {
+        ClassgroupElement::get_default_element()
+    } else {
+        input.clone()
+    };
+    let discriminant = create_discriminant(&info.challenge.bytes, d_bits);
+    let mut proof_blob = info.output.data.bytes.to_vec();
+    proof_blob.extend_from_slice(&proof.witness.bytes);
+    match verify_n_wesolowski(
+        &discriminant,
+        &input.data.bytes,
+        &proof_blob,
+        info.number_of_iterations,
+        d_bits,
+        proof.witness_type,
+    ) {
+        Ok(valid) => valid,
+        Err(e) => {
+            debug!("Invalid VDF proof: {:?}", e);
+            false
+        }
+    }
+}
+
+//Computes y = x^(2^iterations) and a single Wesolowski proof for it, returning (y, proof) as
+//compressed forms. This squares iterations times so it is only practical for small counts
+pub fn prove(
+    discriminant: &BigInt,
+    x_s: &[u8],
+    iterations: u64,
+    d_bits: usize,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let x = deserialize_form(discriminant, x_s, d_bits)?;
+    let mut y = x.clone();
+    for _ in 0..iterations {
+        y = y.square();
+    }
+    let y_s = serialize_form(&y, d_bits)?;
+    let b = get_b(x_s, &y_s);
+    let exponent = (BigUint::one() << iterations as usize) / &b;
+    let proof = serialize_form(&x.pow(&exponent), d_bits)?;
+    Ok((y_s.to_vec(), proof.to_vec()))
+}
+
+#[test]
+fn test_vdf() {
+    use crate::blockchain::sized_bytes::{Bytes100, Bytes32, SizedBytes};
+    use crate::blockchain::unsized_bytes::UnsizedBytes;
+    let challenge = Bytes32::new(&[7u8; 32]);
+    let d_bits = 512;
+    let discriminant = create_discriminant(&challenge.bytes, d_bits);
+    assert_eq!(discriminant.bits(), d_bits as u64);
+    assert_eq!(discriminant.mod_floor(&BigInt::from(8)), BigInt::from(1));
+    let generator = Form::generator(&discriminant);
+    let x = generator.pow(&BigUint::from(12345u32));
+    assert_eq!(x.discriminant(), discriminant);
+    assert_eq!(
+        x.compose(&generator.pow(&BigUint::from(55u32))),
+        generator.pow(&BigUint::from(12400u32))
+    );
+    let x_s = serialize_form(&x, d_bits).unwrap();
+    assert_eq!(deserialize_form(&discriminant, &x_s, d_bits).unwrap(), x);
+    let (y_s, proof_s) = prove(&discriminant, &x_s, 200, d_bits).unwrap();
+    assert!(verify_wesolowski(&discriminant, &x_s, &y_s, &proof_s, 200, d_bits).unwrap());
+    assert!(!verify_wesolowski(&discriminant, &x_s, &y_s, &proof_s, 201, d_bits).unwrap());
+    //One segment proving the first 120 iterations followed by the final 80
+    let (mid_s, mid_proof_s) = prove(&discriminant, &x_s, 120, d_bits).unwrap();
+    let (y2_s, proof2_s) = prove(&discriminant, &mid_s, 80, d_bits).unwrap();
+    assert_eq!(y2_s, y_s);
+    let mut blob = [y2_s, proof2_s].concat();
+    blob.extend_from_slice(&120u64.to_be_bytes());
+    let b = get_b(&x_s, &mid_s).to_bytes_be();
+    blob.extend_from_slice(&[vec![0u8; B_BYTES - b.len()], b].concat());
+    blob.extend_from_slice(&mid_proof_s);
+    assert!(verify_n_wesolowski(&discriminant, &x_s, &blob, 200, d_bits, 1).unwrap());
+    assert!(!verify_n_wesolowski(&discriminant, &x_s, &blob, 199, d_bits, 1).unwrap());
+    let default = ClassgroupElement::get_default_element();
+    assert_eq!(
+        deserialize_form(&discriminant, &default.data.bytes, d_bits).unwrap(),
+        generator
+    );
+    assert_ne!(default.data, Bytes100::default());
+    //Full node path with the mainnet discriminant size, starting from the default element
+    let constants = crate::consensus::constants::MAINNET.as_ref();
+    let mainnet_bits = 1024;
+    let challenge = Bytes32::new(&[0x5a; 32]);
+    let discriminant = create_discriminant(&challenge.bytes, mainnet_bits);
+    let (output, witness) = prove(&discriminant, &default.data.bytes, 150, mainnet_bits).unwrap();
+    let info = VdfInfo {
+        challenge,
+        number_of_iterations: 150,
+        output: ClassgroupElement {
+            data: Bytes100::new(&output),
+        },
+    };
+    let proof = VdfProof {
+        witness_type: 0,
+        witness: UnsizedBytes::new(&witness),
+        normalized_to_identity: false,
+    };
+    assert!(validate_vdf_proof(constants, &default, &info, &proof));
+    let normalized = VdfProof {
+        normalized_to_identity: true,
+        ..proof.clone()
+    };
+    let other_input = ClassgroupElement {
+        data: Bytes100::new(&x_s),
+    };
+    assert!(validate_vdf_proof(
+        constants,
+        &other_input,
+        &info,
+        &normalized
+    ));
+    assert!(!validate_vdf_proof(constants, &other_input, &info, &proof));
+    let mut bad_witness = witness.clone();
+    bad_witness[1] ^= 0x01;
+    let bad = VdfProof {
+        witness: UnsizedBytes::new(&bad_witness),
+        ..proof.clone()
+    };
+    assert!(!validate_vdf_proof(constants, &default, &info, &bad));
+    let wrong_iterations = VdfInfo {
+        number_of_iterations: 151,
+        ..info.clone()
+    };
+    assert!(!validate_vdf_proof(
+        constants,
+        &default,
+        &wrong_iterations,
+        &proof
+    ));
+    let too_deep = VdfProof {
+        witness_type: 64,
+        ..proof
+    };
+    assert!(!validate_vdf_proof(constants, &default, &info, &too_deep));
+}
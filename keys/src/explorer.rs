@@ -0,0 +1,356 @@
+use crate::{
+    derive_child_pk_unhardened, derive_child_sk, derive_child_sk_unhardened, encode_puzzle_hash,
+    fingerprint, BLS_SPEC_NUMBER, CHIA_BLOCKCHAIN_NUMBER, FARMER_PATH, POOL_AUTH_PATH, POOL_PATH,
+    SINGLETON_PATH, WALLET_PATH,
+};
+use blst::min_pk::{PublicKey, SecretKey};
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48};
+use dg_xch_puzzles::p2_delegated_puzzle_or_hidden_puzzle::{
+    calculate_synthetic_public_key, puzzle_hash_for_pk, DEFAULT_HIDDEN_PUZZLE_HASH,
+};
+use std::fmt::{Display, Formatter};
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+//A derivation path in the chia CLI format, m/12381n/8444n/2/0 where n marks a hardened index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath {
+    pub steps: Vec<(u32, bool)>,
+}
+impl DerivationPath {
+    pub fn new(indexes: &[u32], hardened: bool) -> Self {
+        Self {
+            steps: indexes.iter().map(|i| (*i, hardened)).collect(),
+        }
+    }
+
+    pub fn is_observer(&self) -> bool {
+        self.steps.iter().all(|(_, hardened)| !hardened)
+    }
+
+    pub fn derive_sk(&self, master_sk: &SecretKey) -> Result<SecretKey, Error> {
+        let mut key = master_sk.clone();
+        for (index, hardened) in &self.steps {
+            key = if *hardened {
+                derive_child_sk(&key, *index)?
+            } else {
+                derive_child_sk_unhardened(&key, *index)?
+            };
+        }
+        Ok(key)
+    }
+
+    pub fn derive_pk(&self, master_pk: &PublicKey) -> Result<PublicKey, Error> {
+        if !self.is_observer() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Path {self} has hardened indexes and needs the secret key"),
+            ));
+        }
+        let mut key = *master_pk;
+        for (index, _) in &self.steps {
+            key = derive_child_pk_unhardened(&key, *index)?;
+        }
+        Ok(key)
+    }
+}
+impl Display for DerivationPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "m")?;
+        for (index, hardened) in &self.steps {
+            write!(f, "/{index}{}", if *hardened { "n" } else { "" })?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for DerivationPath {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = vec![];
+        for (i, part) in s.trim().trim_end_matches('/').split('/').enumerate() {
+            if i == 0 && part == "m" {
+                continue;
+            }
+            let (index, hardened) = match part.strip_suffix('n') {
+                Some(index) => (index, true),
+                None => (part, false),
+            };
+            steps.push((
+                index.parse::<u32>().map_err(|e| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Invalid path component {part} in {s}: {:?}", e),
+                    )
+                })?,
+                hardened,
+            ));
+        }
+        Ok(Self { steps })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyKind {
+    Farmer,
+    Pool,
+    Wallet,
+    Owner,
+    Auth,
+}
+impl KeyKind {
+    pub const ALL: [KeyKind; 5] = [
+        KeyKind::Farmer,
+        KeyKind::Pool,
+        KeyKind::Wallet,
+        KeyKind::Owner,
+        KeyKind::Auth,
+    ];
+
+    //Auth keys live at pool_wallet_index * 10000 + auth_index, index is the pool wallet index and
+    //only auth index 0 is searched
+    pub fn path(&self, index: u32, hardened: bool) -> Result<DerivationPath, Error> {
+        let (purpose, index) = match self {
+            KeyKind::Farmer => (FARMER_PATH, index),
+            KeyKind::Pool => (POOL_PATH, index),
+            KeyKind::Wallet => (WALLET_PATH, index),
+            KeyKind::Owner => (SINGLETON_PATH, index),
+            KeyKind::Auth => (
+                POOL_AUTH_PATH,
+                index.checked_mul(10000).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Pool wallet index {index} is above the largest auth key index of {}",
+                            u32::MAX / 10000
+                        ),
+                    )
+                })?,
+            ),
+        };
+        Ok(DerivationPath::new(
+            &[BLS_SPEC_NUMBER, CHIA_BLOCKCHAIN_NUMBER, purpose, index],
+            hardened,
+        ))
+    }
+}
+impl FromStr for KeyKind {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "farmer" => Ok(KeyKind::Farmer),
+            "pool" => Ok(KeyKind::Pool),
+            "wallet" => Ok(KeyKind::Wallet),
+            "owner" | "singleton" => Ok(KeyKind::Owner),
+            "auth" | "pool_auth" => Ok(KeyKind::Auth),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown key kind {s}, expected farmer, pool, wallet, owner or auth"),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SearchTarget {
+    PublicKey(Bytes48),
+    PuzzleHash(Bytes32),
+}
+
+#[derive(Debug, Clone)]
+pub struct DerivedKey {
+    pub path: DerivationPath,
+    pub public_key: Bytes48,
+    pub synthetic_public_key: Bytes48,
+    pub puzzle_hash: Bytes32,
+    pub fingerprint: u32,
+}
+impl DerivedKey {
+    pub fn from_public_key(path: DerivationPath, public_key: &PublicKey) -> Result<Self, Error> {
+        let pk_bytes = Bytes48::from(public_key.to_bytes());
+        Ok(Self {
+            path,
+            synthetic_public_key: calculate_synthetic_public_key(
+                &pk_bytes,
+                &DEFAULT_HIDDEN_PUZZLE_HASH,
+            )?,
+            puzzle_hash: puzzle_hash_for_pk(&pk_bytes)?,
+            public_key: pk_bytes,
+            fingerprint: fingerprint(public_key),
+        })
+    }
+
+    pub fn address(&self, prefix: &str) -> Result<String, Error> {
+        encode_puzzle_hash(&self.puzzle_hash, prefix)
+    }
+
+    pub fn matches(&self, target: &SearchTarget) -> bool {
+        match target {
+            SearchTarget::PublicKey(pk) => {
+                *pk == self.public_key || *pk == self.synthetic_public_key
+            }
+            SearchTarget::PuzzleHash(ph) => *ph == self.puzzle_hash,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyMatch {
+    pub kind: KeyKind,
+    pub index: u32,
+    pub hardened: bool,
+    pub key: DerivedKey,
+}
+
+//Without a master secret key only unhardened paths can be derived
+pub enum KeySource<'a> {
+    Secret(&'a SecretKey),
+    Observer(&'a PublicKey),
+}
+impl KeySource<'_> {
+    pub fn master_public_key(&self) -> PublicKey {
+        match self {
+            KeySource::Secret(sk) => sk.sk_to_pk(),
+            KeySource::Observer(pk) => **pk,
+        }
+    }
+
+    pub fn derive(&self, path: &DerivationPath) -> Result<DerivedKey, Error> {
+        DerivedKey::from_public_key(path.clone(), &self.node(path)?.public_key())
+    }
+
+    fn node(&self, path: &DerivationPath) -> Result<KeyNode, Error> {
+        Ok(match self {
+            KeySource::Secret(sk) => KeyNode::Secret(path.derive_sk(sk)?),
+            KeySource::Observer(pk) => KeyNode::Public(path.derive_pk(pk)?),
+        })
+    }
+}
+
+enum KeyNode {
+    Secret(SecretKey),
+    Public(PublicKey),
+}
+impl KeyNode {
+    fn child(&self, index: u32, hardened: bool) -> Result<KeyNode, Error> {
+        Ok(match self {
+            KeyNode::Secret(sk) if hardened => KeyNode::Secret(derive_child_sk(sk, index)?),
+            KeyNode::Secret(sk) => KeyNode::Secret(derive_child_sk_unhardened(sk, index)?),
+            KeyNode::Public(pk) => KeyNode::Public(derive_child_pk_unhardened(pk, index)?),
+        })
+    }
+
+    fn public_key(&self) -> PublicKey {
+        match self {
+            KeyNode::Secret(sk) => sk.sk_to_pk(),
+            KeyNode::Public(pk) => *pk,
+        }
+    }
+}
+
+pub fn search_keys(
+    source: &KeySource,
+    target: &SearchTarget,
+    kinds: &[KeyKind],
+    start: u32,
+    end: u32,
+) -> Result<Vec<KeyMatch>, Error> {
+    let mut matches = vec![];
+    let hardened_options: &[bool] = match source {
+        KeySource::Secret(_) => &[true, false],
+        KeySource::Observer(_) => &[false],
+    };
+    for kind in kinds {
+        for hardened in hardened_options {
+            //Every kind shares the purpose level, derive it once and only walk the last index
+            let mut parent_path = kind.path(0, *hardened)?;
+            parent_path.steps.pop();
+            let parent = source.node(&parent_path)?;
+            for index in start..end {
+                let path = kind.path(index, *hardened)?;
+                let (child_index, _) = path.steps[path.steps.len() - 1];
+                let key = DerivedKey::from_public_key(
+                    path,
+                    &parent.child(child_index, *hardened)?.public_key(),
+                )?;
+                if key.matches(target) {
+                    matches.push(KeyMatch {
+                        kind: *kind,
+                        index,
+                        hardened: *hardened,
+                        key,
+                    });
+                }
+            }
+        }
+    }
+    Ok(matches)
+}
+
+#[test]
+fn test_key_explorer() {
+    use crate::{
+        key_from_mnemonic_str, master_sk_to_singleton_owner_sk, master_sk_to_wallet_sk,
+        master_sk_to_wallet_sk_unhardened,
+    };
+    let master_sk = key_from_mnemonic_str("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+    let path = DerivationPath::from_str("m/12381n/8444n/2n/7n").unwrap();
+    assert_eq!(path.to_string(), "m/12381n/8444n/2n/7n");
+    assert_eq!(
+        path.derive_sk(&master_sk).unwrap().to_bytes(),
+        master_sk_to_wallet_sk(&master_sk, 7).unwrap().to_bytes()
+    );
+    let observer_path = DerivationPath::from_str("m/12381/8444/2/7").unwrap();
+    assert_eq!(
+        observer_path.derive_pk(&master_sk.sk_to_pk()).unwrap(),
+        master_sk_to_wallet_sk_unhardened(&master_sk, 7)
+            .unwrap()
+            .sk_to_pk()
+    );
+    let owner_pk = Bytes48::from(
+        master_sk_to_singleton_owner_sk(&master_sk, 3)
+            .unwrap()
+            .sk_to_pk()
+            .to_bytes(),
+    );
+    let found = search_keys(
+        &KeySource::Secret(&master_sk),
+        &SearchTarget::PublicKey(owner_pk),
+        &KeyKind::ALL,
+        0,
+        5,
+    )
+    .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].kind, found[0].index), (KeyKind::Owner, 3));
+    let master_pk = master_sk.sk_to_pk();
+    let observer = KeySource::Observer(&master_pk);
+    let wallet_key = observer
+        .derive(&KeyKind::Wallet.path(4, false).unwrap())
+        .unwrap();
+    let found = search_keys(
+        &observer,
+        &SearchTarget::PuzzleHash(wallet_key.puzzle_hash),
+        &[KeyKind::Wallet],
+        0,
+        10,
+    )
+    .unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(!found[0].hardened);
+    assert_eq!(
+        KeyKind::Auth.path(2, true).unwrap().to_string(),
+        "m/12381n/8444n/6n/20000n"
+    );
+    let last = u32::MAX / 10000;
+    assert!(KeyKind::Auth.path(last, false).is_ok());
+    let e = KeyKind::Auth.path(last + 1, false).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    assert!(search_keys(
+        &observer,
+        &SearchTarget::PuzzleHash(wallet_key.puzzle_hash),
+        &[KeyKind::Auth],
+        last,
+        last + 2
+    )
+    .is_err());
+}
@@ -1,6 +1,6 @@
 use bech32::{FromBase32, ToBase32, Variant};
 use bip39::Mnemonic;
-use blst::min_pk::{PublicKey, SecretKey};
+use blst::min_pk::{AggregatePublicKey, PublicKey, SecretKey};
 use blst::{blst_bendian_from_scalar, blst_scalar, blst_scalar_from_be_bytes, blst_sk_add_n_check};
 use dg_xch_core::blockchain::sized_bytes::{hex_to_bytes, prep_hex_str, Bytes32, SizedBytes};
 use dg_xch_puzzles::p2_delegated_puzzle_or_hidden_puzzle::puzzle_hash_for_pk;
@@ -11,6 +11,8 @@ use std::io::{Error, ErrorKind};
 use std::mem::size_of;
 use std::str::FromStr;
 
+pub mod explorer;
+
 fn _version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
@@ -104,6 +106,25 @@ fn derive_child_sk_unhardened(key: &SecretKey, index: u32) -> Result<SecretKey,
     SecretKey::from_bytes(&agg).map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{:?}", e)))
 }
 
+fn derive_child_pk_unhardened(key: &PublicKey, index: u32) -> Result<PublicKey, Error> {
+    let mut buf = vec![];
+    buf.extend(key.to_bytes());
+    buf.extend(index.to_be_bytes());
+    let hash = hash_256(&buf);
+    let mut out = [0u8; 32];
+    let mut h = blst_scalar::default();
+    let offset = unsafe {
+        blst_scalar_from_be_bytes(&mut h, hash.as_ptr(), hash.len());
+        blst_bendian_from_scalar(out.as_mut_ptr(), &h);
+        out
+    };
+    let offset = SecretKey::from_bytes(&offset)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{:?}", e)))?;
+    AggregatePublicKey::aggregate(&[key, &offset.sk_to_pk()], false)
+        .map(|agg| agg.to_public_key())
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{:?}", e)))
+}
+
 pub fn derive_path(key: &SecretKey, paths: Vec<u32>) -> Result<SecretKey, Error> {
     let mut key: SecretKey = key.clone();
     for index in paths {
@@ -120,6 +141,14 @@ pub fn derive_path_unhardened(key: &SecretKey, paths: Vec<u32>) -> Result<Secret
     Ok(key)
 }
 
+pub fn derive_path_unhardened_pk(key: &PublicKey, paths: Vec<u32>) -> Result<PublicKey, Error> {
+    let mut key: PublicKey = *key;
+    for index in paths {
+        key = derive_child_pk_unhardened(&key, index)?;
+    }
+    Ok(key)
+}
+
 pub fn master_sk_to_farmer_sk(key: &SecretKey) -> Result<SecretKey, Error> {
     derive_path(
         key,
@@ -155,6 +184,31 @@ pub fn master_sk_to_wallet_sk_unhardened(key: &SecretKey, index: u32) -> Result<
     derive_path_unhardened(&intermediate, vec![index])
 }
 
+pub fn master_pk_to_wallet_pk_unhardened_intermediate(key: &PublicKey) -> Result<PublicKey, Error> {
+    derive_path_unhardened_pk(
+        key,
+        vec![BLS_SPEC_NUMBER, CHIA_BLOCKCHAIN_NUMBER, WALLET_PATH],
+    )
+}
+
+pub fn master_pk_to_wallet_pk_unhardened(key: &PublicKey, index: u32) -> Result<PublicKey, Error> {
+    let intermediate = master_pk_to_wallet_pk_unhardened_intermediate(key)?;
+    derive_path_unhardened_pk(&intermediate, vec![index])
+}
+
+#[test]
+fn test_unhardened_pk_derivation() {
+    let master_sk = key_from_mnemonic_str("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").unwrap();
+    let master_pk = master_sk.sk_to_pk();
+    for index in [0, 1, 17, 500] {
+        let from_sk = master_sk_to_wallet_sk_unhardened(&master_sk, index)
+            .unwrap()
+            .sk_to_pk();
+        let from_pk = master_pk_to_wallet_pk_unhardened(&master_pk, index).unwrap();
+        assert_eq!(from_sk, from_pk);
+    }
+}
+
 pub fn master_sk_to_local_sk(key: &SecretKey) -> Result<SecretKey, Error> {
     derive_path(
         key,
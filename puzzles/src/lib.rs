@@ -1,6 +1,7 @@
 pub mod clvm_puzzles;
 pub mod p2_conditions;
 pub mod p2_delegated_puzzle_or_hidden_puzzle;
+pub mod p2_m_of_n_delegate_direct;
 pub mod singleton;
 pub mod utils;
 
@@ -0,0 +1,312 @@
+use crate::clvm_puzzles::puzzle_for_singleton;
+use crate::singleton::launch_conditions_and_coin_spend;
+use blst::min_pk::{AggregateSignature, SecretKey, Signature};
+use dg_xch_core::blockchain::coin::Coin;
+use dg_xch_core::blockchain::coin_spend::CoinSpend;
+use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48, Bytes96};
+use dg_xch_core::blockchain::spend_bundle::SpendBundle;
+use dg_xch_core::blockchain::utils::pkm_pairs_for_conditions_dict;
+use dg_xch_core::clvm::bls_bindings::{aggregate_verify_signature, sign};
+use dg_xch_core::clvm::condition_utils::conditions_dict_for_solution;
+use dg_xch_core::clvm::program::{Program, SerializedProgram};
+use dg_xch_core::clvm::sexp::IntoSExp;
+use lazy_static::lazy_static;
+use std::io::{Error, ErrorKind};
+
+const P2_M_OF_N_DELEGATE_DIRECT_HEX: &str = "ff02ffff01ff02ffff03ffff09ff05ffff02ff0affff04ff02ffff04ff17ff8080808080ffff01ff02ff16ffff04ff02ffff04ffff02ff04ffff04ff02ffff04ff0bffff04ff17ffff04ffff02ff2effff04ff02ffff04ff2fff80808080ff808080808080ffff04ffff02ff2fff5f80ff8080808080ffff01ff088080ff0180ffff04ffff01ffff02ffff03ff0bffff01ff02ffff03ff13ffff01ff04ffff04ffff0132ffff04ff09ffff04ff17ff80808080ffff02ff04ffff04ff02ffff04ff0dffff04ff1bffff04ff17ff80808080808080ffff01ff02ff04ffff04ff02ffff04ff0dffff04ff1bffff04ff17ff80808080808080ff0180ffff018080ff0180ffff02ffff03ff05ffff01ff10ffff03ff09ffff0101ff8080ffff02ff0affff04ff02ffff04ff0dff8080808080ffff018080ff0180ffff02ffff03ff05ffff01ff04ff09ffff02ff16ffff04ff02ffff04ff0dffff04ff0bff808080808080ffff010b80ff0180ffff02ffff03ffff07ff0580ffff01ff0bffff0102ffff02ff2effff04ff02ffff04ff09ff80808080ffff02ff2effff04ff02ffff04ff0dff8080808080ffff01ff0bffff0101ff058080ff018080ff018080";
+
+lazy_static! {
+    pub static ref MOD: Program = SerializedProgram::from_hex(P2_M_OF_N_DELEGATE_DIRECT_HEX)
+        .unwrap()
+        .to_program();
+}
+
+//Curried args are (M public_keys), the solution is (selectors delegated_puzzle delegated_solution)
+pub fn puzzle_for_m_of_n_public_keys(m: u64, public_keys: &[Bytes48]) -> Result<Program, Error> {
+    if m == 0 || m as usize > public_keys.len() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Invalid threshold {m} for a key set of {}",
+                public_keys.len()
+            ),
+        ));
+    }
+    MOD.curry(&[Program::to(m), Program::to(public_keys.to_vec())])
+}
+
+pub fn puzzle_hash_for_m_of_n_public_keys(
+    m: u64,
+    public_keys: &[Bytes48],
+) -> Result<Bytes32, Error> {
+    Ok(puzzle_for_m_of_n_public_keys(m, public_keys)?.tree_hash())
+}
+
+pub fn selectors_for_signers(
+    m: u64,
+    public_keys: &[Bytes48],
+    signers: &[Bytes48],
+) -> Result<Vec<u8>, Error> {
+    let mut remaining = m;
+    let selectors: Vec<u8> = public_keys
+        .iter()
+        .map(|key| {
+            if remaining > 0 && signers.contains(key) {
+                remaining -= 1;
+                1
+            } else {
+                0
+            }
+        })
+        .collect();
+    if remaining > 0 {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Need {m} signers from the key set, found {}", m - remaining),
+        ))
+    } else {
+        Ok(selectors)
+    }
+}
+
+pub fn solution_for_delegated_puzzle(
+    m: u64,
+    public_keys: &[Bytes48],
+    signers: &[Bytes48],
+    delegated_puzzle: Program,
+    solution: Program,
+) -> Result<Program, Error> {
+    let selectors = selectors_for_signers(m, public_keys, signers)?;
+    Ok(Program::to(vec![
+        selectors.to_sexp(),
+        delegated_puzzle.to_sexp(),
+        solution.to_sexp(),
+    ]))
+}
+
+pub fn vault_puzzle(
+    launcher_id: &Bytes32,
+    m: u64,
+    public_keys: &[Bytes48],
+) -> Result<Program, Error> {
+    puzzle_for_singleton(launcher_id, &puzzle_for_m_of_n_public_keys(m, public_keys)?)
+}
+
+pub fn vault_puzzle_hash(
+    launcher_id: &Bytes32,
+    m: u64,
+    public_keys: &[Bytes48],
+) -> Result<Bytes32, Error> {
+    Ok(vault_puzzle(launcher_id, m, public_keys)?.tree_hash())
+}
+
+pub fn launch_vault(
+    origin: Coin,
+    m: u64,
+    public_keys: &[Bytes48],
+    amount: u64,
+) -> Result<(Vec<Program>, CoinSpend), Error> {
+    launch_conditions_and_coin_spend(
+        origin,
+        puzzle_for_m_of_n_public_keys(m, public_keys)?,
+        Program::to(0),
+        amount,
+    )
+}
+
+//The delegated puzzle must recreate the vault with an odd amount to keep the singleton alive
+pub fn vault_coin_spend(
+    vault_coin: Coin,
+    launcher_id: &Bytes32,
+    lineage_proof: Program,
+    m: u64,
+    public_keys: &[Bytes48],
+    inner_solution: Program,
+) -> Result<CoinSpend, Error> {
+    let puzzle = vault_puzzle(launcher_id, m, public_keys)?;
+    let solution = Program::to(vec![
+        lineage_proof.to_sexp(),
+        vault_coin.amount.to_sexp(),
+        inner_solution.to_sexp(),
+    ]);
+    Ok(CoinSpend {
+        coin: vault_coin,
+        puzzle_reveal: puzzle.into(),
+        solution: solution.into(),
+    })
+}
+
+//Signs every AGG_SIG target in the spends that belongs to this key. The returned bundle has no
+//coin spends so partials from several operators can be combined with complete_multisig_spend
+pub fn partial_sign_coin_spends(
+    coin_spends: &[CoinSpend],
+    secret_key: &SecretKey,
+    additional_data: &[u8],
+    max_cost: u64,
+) -> Result<SpendBundle, Error> {
+    let public_key = Bytes48::from(secret_key.sk_to_pk().to_bytes());
+    let mut signatures: Vec<Signature> = vec![];
+    for coin_spend in coin_spends {
+        let conditions_dict = conditions_dict_for_solution(
+            &coin_spend.puzzle_reveal,
+            &coin_spend.solution,
+            max_cost,
+        )?
+        .0;
+        for (pk_bytes, msg) in
+            pkm_pairs_for_conditions_dict(conditions_dict, coin_spend.coin.name(), additional_data)?
+        {
+            if pk_bytes == public_key {
+                signatures.push(sign(secret_key, &msg));
+            }
+        }
+    }
+    if signatures.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("No signing targets found for key {public_key}"),
+        ));
+    }
+    let aggregated_signature =
+        AggregateSignature::aggregate(&signatures.iter().collect::<Vec<&Signature>>(), true)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{:?}", e)))?
+            .to_signature();
+    Ok(SpendBundle {
+        coin_spends: vec![],
+        aggregated_signature: Bytes96::from(aggregated_signature),
+    })
+}
+
+pub fn complete_multisig_spend(
+    coin_spends: Vec<CoinSpend>,
+    partials: Vec<SpendBundle>,
+    additional_data: &[u8],
+    max_cost: u64,
+) -> Result<SpendBundle, Error> {
+    if partials.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "No partial signatures to combine",
+        ));
+    }
+    let mut bundle = SpendBundle::aggregate(partials)?;
+    if !bundle.coin_spends.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Partial signature bundles should not contain coin spends",
+        ));
+    }
+    bundle.coin_spends = coin_spends;
+    let mut pk_list = vec![];
+    let mut msg_list = vec![];
+    for coin_spend in &bundle.coin_spends {
+        let conditions_dict = conditions_dict_for_solution(
+            &coin_spend.puzzle_reveal,
+            &coin_spend.solution,
+            max_cost,
+        )?
+        .0;
+        for (pk_bytes, msg) in
+            pkm_pairs_for_conditions_dict(conditions_dict, coin_spend.coin.name(), additional_data)?
+        {
+            pk_list.push(pk_bytes);
+            msg_list.push(msg);
+        }
+    }
+    let signature: Signature = bundle.aggregated_signature.try_into()?;
+    if aggregate_verify_signature(
+        &pk_list.iter().collect::<Vec<&Bytes48>>(),
+        &msg_list.iter().map(|m| m.as_slice()).collect(),
+        &signature,
+    ) {
+        Ok(bundle)
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Combined signature does not cover every signing target",
+        ))
+    }
+}
+
+#[test]
+pub fn test_m_of_n_mod_hash() {
+    //Pins the bundled serialization so it cannot change unnoticed. This value still has to be
+    //checked against chia's p2_m_of_n_delegate_direct.clvm.hex.sha256tree, if they differ MOD
+    //must be replaced with the reference hex before wallets rely on these puzzle hashes
+    assert_eq!(
+        MOD.tree_hash(),
+        Bytes32::from("0xa20263ac28a17bf38a31edaefc47d4727f2ff41b0cb36d6c314c62e79623f08a")
+    );
+}
+
+#[test]
+pub fn test_m_of_n_partial_signatures() {
+    use crate::p2_conditions::puzzle_for_conditions;
+    use crate::utils::make_create_coin_condition;
+    let secret_keys: Vec<SecretKey> = (1u8..=3)
+        .map(|i| SecretKey::key_gen(&[i; 32], &[]).unwrap())
+        .collect();
+    let public_keys: Vec<Bytes48> = secret_keys
+        .iter()
+        .map(|sk| Bytes48::from(sk.sk_to_pk().to_bytes()))
+        .collect();
+    let puzzle = puzzle_for_m_of_n_public_keys(2, &public_keys).unwrap();
+    let coin = Coin {
+        parent_coin_info: Bytes32::from(
+            "0x01f8c5b1ce0cd1ac14d2bb15ae3c7fd3a8fd5d1d0f1b3a07b0a40e1c33c1ec56",
+        ),
+        puzzle_hash: puzzle.tree_hash(),
+        amount: 1000,
+    };
+    let delegated_puzzle = puzzle_for_conditions(vec![make_create_coin_condition(
+        Bytes32::from("0x48068eb6150f738fe90a001c562f0c4b769b7d64a59915aa8c0886b978e38137"),
+        1000,
+        &[],
+    )])
+    .unwrap();
+    let signers = vec![public_keys[0], public_keys[2]];
+    let solution = solution_for_delegated_puzzle(
+        2,
+        &public_keys,
+        &signers,
+        delegated_puzzle.clone(),
+        Program::to(0),
+    )
+    .unwrap();
+    let (_, output) = puzzle
+        .run(u64::MAX, 0, &solution)
+        .expect("Expected puzzle to run");
+    assert_eq!(output.as_list().len(), 3);
+    let bad_solution = Program::to(vec![
+        vec![1u8, 0, 0].to_sexp(),
+        delegated_puzzle.to_sexp(),
+        0.to_sexp(),
+    ]);
+    assert!(puzzle.run(u64::MAX, 0, &bad_solution).is_err());
+    let coin_spends = vec![CoinSpend {
+        coin,
+        puzzle_reveal: puzzle.into(),
+        solution: solution.into(),
+    }];
+    let additional_data = [7u8; 32];
+    let partials = vec![
+        partial_sign_coin_spends(&coin_spends, &secret_keys[0], &additional_data, u64::MAX)
+            .unwrap(),
+        partial_sign_coin_spends(&coin_spends, &secret_keys[2], &additional_data, u64::MAX)
+            .unwrap(),
+    ];
+    assert!(
+        partial_sign_coin_spends(&coin_spends, &secret_keys[1], &additional_data, u64::MAX)
+            .is_err()
+    );
+    assert!(complete_multisig_spend(
+        coin_spends.clone(),
+        partials[..1].to_vec(),
+        &additional_data,
+        u64::MAX
+    )
+    .is_err());
+    let bundle =
+        complete_multisig_spend(coin_spends, partials, &additional_data, u64::MAX).unwrap();
+    assert_eq!(bundle.coin_spends.len(), 1);
+}
@@ -0,0 +1,320 @@
+use dg_xch_clients::websocket::discovery::AddressManager;
+use dg_xch_core::config::{SeederConfig, Soa};
+use log::{debug, info, warn};
+use std::io::Error;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::select;
+use tokio::sync::RwLock;
+
+const MAX_UDP_RESPONSE: usize = 512;
+const TYPE_A: u16 = 1;
+const TYPE_NS: u16 = 2;
+const TYPE_SOA: u16 = 6;
+const TYPE_AAAA: u16 = 28;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+const RCODE_FORMAT_ERROR: u16 = 1;
+const RCODE_NAME_ERROR: u16 = 3;
+const RCODE_NOT_IMPLEMENTED: u16 = 4;
+const RCODE_REFUSED: u16 = 5;
+
+#[derive(Debug, Clone)]
+pub struct DnsSeederConfig {
+    pub host: String,
+    pub port: u16,
+    pub domain_name: String,
+    pub nameserver: String,
+    pub ttl: u32,
+    pub soa: Soa,
+    //DNS answers carry no port, so only peers listening on this port are served
+    pub peer_port: u16,
+    //Only peers reached within this many seconds are served
+    pub max_age: u64,
+}
+impl DnsSeederConfig {
+    pub fn from_seeder_config(host: &str, config: &SeederConfig) -> Self {
+        Self {
+            host: host.to_string(),
+            port: config.dns_port,
+            domain_name: config.domain_name.clone(),
+            nameserver: config.nameserver.clone(),
+            ttl: config.ttl as u32,
+            soa: config.soa.clone(),
+            peer_port: config.other_peers_port,
+            max_age: 5 * 24 * 60 * 60,
+        }
+    }
+}
+
+//Minimal authoritative server for the seeder zone, answering A and AAAA queries from the peer table
+pub struct DnsSeeder {
+    pub config: DnsSeederConfig,
+    pub address_manager: Arc<RwLock<AddressManager>>,
+}
+impl DnsSeeder {
+    pub fn new(config: DnsSeederConfig, address_manager: Arc<RwLock<AddressManager>>) -> Self {
+        Self {
+            config,
+            address_manager,
+        }
+    }
+
+    pub async fn run(&self, run: Arc<AtomicBool>) -> Result<(), Error> {
+        let socket = UdpSocket::bind((self.config.host.as_str(), self.config.port)).await?;
+        info!(
+            "Serving {} on {}:{}",
+            self.config.domain_name, self.config.host, self.config.port
+        );
+        let mut buf = [0u8; MAX_UDP_RESPONSE];
+        while run.load(Ordering::Relaxed) {
+            select! {
+                res = socket.recv_from(&mut buf) => {
+                    match res {
+                        Ok((len, addr)) => {
+                            let peers = self.peers().await;
+                            if let Some(response) = answer(&self.config, &buf[..len], &peers) {
+                                if let Err(e) = socket.send_to(&response, addr).await {
+                                    debug!("Failed to answer {addr}: {:?}", e);
+                                }
+                            }
+                        }
+                        Err(e) => warn!("Failed to read DNS query: {:?}", e),
+                    }
+                }
+                _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn peers(&self) -> Vec<IpAddr> {
+        self.address_manager
+            .read()
+            .await
+            .reachable(usize::MAX, self.config.max_age)
+            .into_iter()
+            .filter(|p| p.port == self.config.peer_port)
+            .filter_map(|p| p.host.parse().ok())
+            .collect()
+    }
+}
+
+//Builds the response to a raw query, None means the packet should be dropped
+pub fn answer(config: &DnsSeederConfig, query: &[u8], peers: &[IpAddr]) -> Option<Vec<u8>> {
+    if query.len() < 12 {
+        return None;
+    }
+    let flags = u16::from_be_bytes([query[2], query[3]]);
+    if flags & 0x8000 != 0 {
+        //Never answer responses
+        return None;
+    }
+    let opcode = (flags >> 11) & 0xF;
+    let question_count = u16::from_be_bytes([query[4], query[5]]);
+    let header = |rcode: u16, questions: u16| {
+        let mut response = Vec::with_capacity(MAX_UDP_RESPONSE);
+        response.extend_from_slice(&query[0..2]);
+        let flags = 0x8000 | (opcode << 11) | 0x0400 | (flags & 0x0100) | rcode;
+        response.extend_from_slice(&flags.to_be_bytes());
+        response.extend_from_slice(&questions.to_be_bytes());
+        response.extend_from_slice(&[0u8; 6]);
+        response
+    };
+    if opcode != 0 {
+        return Some(header(RCODE_NOT_IMPLEMENTED, 0));
+    }
+    if question_count != 1 {
+        return Some(header(RCODE_FORMAT_ERROR, 0));
+    }
+    let Some((name, label_lengths, question_end)) = read_name(query, 12) else {
+        return Some(header(RCODE_FORMAT_ERROR, 0));
+    };
+    if question_end + 4 > query.len() {
+        return Some(header(RCODE_FORMAT_ERROR, 0));
+    }
+    let qtype = u16::from_be_bytes([query[question_end], query[question_end + 1]]);
+    let qclass = u16::from_be_bytes([query[question_end + 2], query[question_end + 3]]);
+    let zone = config.domain_name.trim_end_matches('.').to_lowercase();
+    let in_zone = name == zone;
+    //Offset of the zone name inside the question, used as the owner of the SOA record. Raw label
+    //lengths are used since lossy decoding can change the length of non utf8 labels
+    let zone_offset = 12
+        + label_lengths
+            .iter()
+            .take(label_lengths.len().saturating_sub(zone.split('.').count()))
+            .map(|len| len + 1)
+            .sum::<usize>();
+    let rcode = if in_zone {
+        0
+    } else if name.ends_with(&format!(".{zone}")) {
+        RCODE_NAME_ERROR
+    } else {
+        RCODE_REFUSED
+    };
+    let mut response = header(rcode, 1);
+    response.extend_from_slice(&query[12..question_end + 4]);
+    if rcode == RCODE_REFUSED || qclass != CLASS_IN {
+        return Some(response);
+    }
+    let mut answers: Vec<(u16, Vec<u8>)> = vec![];
+    if in_zone {
+        if matches!(qtype, TYPE_A | TYPE_ANY) {
+            answers.extend(peers.iter().filter_map(|ip| match ip {
+                IpAddr::V4(ip) => Some((TYPE_A, ip.octets().to_vec())),
+                IpAddr::V6(_) => None,
+            }));
+        }
+        if matches!(qtype, TYPE_AAAA | TYPE_ANY) {
+            answers.extend(peers.iter().filter_map(|ip| match ip {
+                IpAddr::V6(ip) => Some((TYPE_AAAA, ip.octets().to_vec())),
+                IpAddr::V4(_) => None,
+            }));
+        }
+        if matches!(qtype, TYPE_NS | TYPE_ANY) {
+            answers.push((TYPE_NS, encode_name(&config.nameserver)));
+        }
+        if qtype == TYPE_SOA {
+            answers.push((TYPE_SOA, soa_data(config)));
+        }
+    }
+    let mut answer_count = 0u16;
+    for (rtype, data) in answers {
+        //Answers point back at the question name, 12 bytes of fixed fields plus the data
+        if response.len() + 12 + data.len() > MAX_UDP_RESPONSE {
+            break;
+        }
+        write_record(&mut response, 12, rtype, config.ttl, &data);
+        answer_count += 1;
+    }
+    response[6..8].copy_from_slice(&answer_count.to_be_bytes());
+    if answer_count == 0 && response.len() + 12 + soa_data(config).len() <= MAX_UDP_RESPONSE {
+        //Negative answers carry the SOA so resolvers know how long to cache them
+        write_record(
+            &mut response,
+            zone_offset as u16,
+            TYPE_SOA,
+            config.soa.minimum,
+            &soa_data(config),
+        );
+        response[8..10].copy_from_slice(&1u16.to_be_bytes());
+    }
+    Some(response)
+}
+
+fn write_record(response: &mut Vec<u8>, name_offset: u16, rtype: u16, ttl: u32, data: &[u8]) {
+    response.extend_from_slice(&(0xC000 | name_offset).to_be_bytes());
+    response.extend_from_slice(&rtype.to_be_bytes());
+    response.extend_from_slice(&CLASS_IN.to_be_bytes());
+    response.extend_from_slice(&ttl.to_be_bytes());
+    response.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    response.extend_from_slice(data);
+}
+
+fn soa_data(config: &DnsSeederConfig) -> Vec<u8> {
+    let mut data = encode_name(&config.nameserver);
+    data.extend(encode_name(&config.soa.rname));
+    for value in [
+        config.soa.serial_number,
+        config.soa.refresh,
+        config.soa.retry,
+        config.soa.expire,
+        config.soa.minimum,
+    ] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data
+}
+
+fn encode_name(name: &str) -> Vec<u8> {
+    let mut encoded = vec![];
+    for label in name
+        .trim_end_matches('.')
+        .split('.')
+        .filter(|l| !l.is_empty())
+    {
+        encoded.push(label.len().min(63) as u8);
+        encoded.extend_from_slice(&label.as_bytes()[..label.len().min(63)]);
+    }
+    encoded.push(0);
+    encoded
+}
+
+//Reads an uncompressed name, returning it lower cased without the trailing dot along with the
+//length of each label as it appears in the packet
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(String, Vec<usize>, usize)> {
+    let mut labels = vec![];
+    let mut lengths = vec![];
+    loop {
+        let len = *packet.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        if len > 63 {
+            return None;
+        }
+        let label = packet.get(pos..pos + len)?;
+        labels.push(String::from_utf8_lossy(label).to_lowercase());
+        lengths.push(len);
+        pos += len;
+    }
+    Some((labels.join("."), lengths, pos))
+}
+
+#[test]
+fn test_dns_answer() {
+    let config = DnsSeederConfig {
+        host: "127.0.0.1".to_string(),
+        port: 5353,
+        domain_name: "seeder.example.com.".to_string(),
+        nameserver: "ns.example.com.".to_string(),
+        ttl: 300,
+        soa: Soa::default(),
+        peer_port: 8444,
+        max_age: 3600,
+    };
+    let query = |name: &str, qtype: u16| {
+        let mut query = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+        query.extend(encode_name(name));
+        query.extend_from_slice(&qtype.to_be_bytes());
+        query.extend_from_slice(&CLASS_IN.to_be_bytes());
+        query
+    };
+    let mut peers: Vec<IpAddr> = (0..100u8).map(|i| IpAddr::from([1, 2, 3, i])).collect();
+    peers.push("2001:db8::1".parse().unwrap());
+    let response = answer(&config, &query("Seeder.Example.com", TYPE_A), &peers).unwrap();
+    assert_eq!(&response[0..2], &[0x12, 0x34]);
+    assert_eq!(response[3] & 0x0F, 0);
+    let answers = u16::from_be_bytes([response[6], response[7]]);
+    assert!(answers > 0 && (answers as usize) < peers.len());
+    assert!(response.len() <= MAX_UDP_RESPONSE);
+    let response = answer(&config, &query("seeder.example.com", TYPE_AAAA), &peers).unwrap();
+    assert_eq!(u16::from_be_bytes([response[6], response[7]]), 1);
+    let response = answer(&config, &query("x.seeder.example.com", TYPE_A), &peers).unwrap();
+    assert_eq!(response[3] & 0x0F, RCODE_NAME_ERROR as u8);
+    assert_eq!(u16::from_be_bytes([response[8], response[9]]), 1);
+    let response = answer(&config, &query("example.org", TYPE_A), &peers).unwrap();
+    assert_eq!(response[3] & 0x0F, RCODE_REFUSED as u8);
+    //The SOA owner must point at the zone even when a label is not valid utf8
+    let mut binary = vec![
+        0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0, 2, 0xff, 0xfe,
+    ];
+    binary.extend(encode_name("seeder.example.com"));
+    binary.extend_from_slice(&TYPE_A.to_be_bytes());
+    binary.extend_from_slice(&CLASS_IN.to_be_bytes());
+    let response = answer(&config, &binary, &peers).unwrap();
+    assert_eq!(response[3] & 0x0F, RCODE_NAME_ERROR as u8);
+    let authority = binary.len();
+    assert_eq!(
+        u16::from_be_bytes([response[authority], response[authority + 1]]),
+        0xC000 | 15
+    );
+    assert_eq!(read_name(&response, 15).unwrap().0, "seeder.example.com");
+    let mut reply = query("seeder.example.com", TYPE_A);
+    reply[2] |= 0x80;
+    assert!(answer(&config, &reply, &peers).is_none());
+}
@@ -1,3 +1,4 @@
+pub mod dns;
 pub mod rpc;
 pub mod websocket;
 
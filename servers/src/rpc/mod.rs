@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 use dg_xch_core::ssl::{
     generate_ca_signed_cert_data, load_certs, load_certs_from_bytes, load_private_key,
-    load_private_key_from_bytes, AllowAny, SslInfo, CHIA_CA_CRT, CHIA_CA_KEY,
+    load_private_key_from_bytes, watch_cert_files, AllowAny, ReloadableCertResolver, SslInfo,
+    CHIA_CA_CRT, CHIA_CA_KEY,
 };
 use http::request::Parts;
 use http::{Method, StatusCode};
@@ -17,7 +18,7 @@ use log::error;
 use prometheus::core::{AtomicU64, GenericCounterVec};
 #[cfg(feature = "metrics")]
 use prometheus::{HistogramOpts, HistogramVec, Opts, Registry};
-use rustls::{RootCertStore, ServerConfig};
+use rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
 use std::env;
 use std::io::{Error, ErrorKind};
 use std::net::{Ipv4Addr, SocketAddr};
@@ -206,6 +207,8 @@ impl RpcHandler for DefaultRpcHandler {
     }
 }
 
+const CERT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct RpcServerConfig {
     pub host: String,
     pub port: u16,
@@ -217,6 +220,8 @@ pub struct RpcServer {
     pub server_config: Arc<ServerConfig>,
     pub handler: Arc<dyn RpcHandler + Send + Sync + 'static>,
     pub middleware: Arc<Vec<Box<dyn MiddleWare + Send + Sync + 'static>>>,
+    pub cert_resolver: Arc<ReloadableCertResolver>,
+    pub ssl_info: Option<SslInfo>,
     #[cfg(feature = "metrics")]
     pub metrics: Arc<RpcMetrics>,
 }
@@ -241,7 +246,9 @@ impl RpcServer {
         middleware: Arc<Vec<Box<dyn MiddleWare + Send + Sync + 'static>>>,
         #[cfg(feature = "metrics")] metrics: Arc<RpcMetrics>,
     ) -> Result<Self, Error> {
-        let server_config = Self::init(config)
+        let (certs, key, root_certs) = Self::load_server_certs(config)?;
+        let cert_resolver = ReloadableCertResolver::new(certs, key)?;
+        let server_config = Self::init_with_resolver(cert_resolver.clone(), root_certs)
             .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("Invalid Cert: {:?}", e)))?;
         let socket_address = Self::init_socket(config)?;
         Ok(RpcServer {
@@ -249,12 +256,43 @@ impl RpcServer {
             server_config,
             handler,
             middleware,
+            cert_resolver,
+            ssl_info: config.ssl_info.clone(),
             #[cfg(feature = "metrics")]
             metrics,
         })
     }
 
+    //Reloads the certificate and key from ssl_info, new connections use them straight away
+    pub fn reload_certs(&self) -> Result<(), Error> {
+        let (crt_path, key_path) = self.cert_paths().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "Server was not started with cert files to reload",
+            )
+        })?;
+        self.cert_resolver.reload_from_files(&crt_path, &key_path)
+    }
+
+    fn cert_paths(&self) -> Option<(String, String)> {
+        self.ssl_info.as_ref().map(|ssl_info| {
+            (
+                format!("{}/{}", &ssl_info.root_path, &ssl_info.certs.private_crt),
+                format!("{}/{}", &ssl_info.root_path, &ssl_info.certs.private_key),
+            )
+        })
+    }
+
     pub async fn run(self, run: Arc<AtomicBool>) -> Result<(), Error> {
+        if let Some((crt_path, key_path)) = self.cert_paths() {
+            tokio::spawn(watch_cert_files(
+                self.cert_resolver.clone(),
+                crt_path,
+                key_path,
+                CERT_CHECK_INTERVAL,
+                run.clone(),
+            ));
+        }
         let server = Arc::new(self);
         let listener = TcpListener::bind(server.socket_address).await?;
         let acceptor = TlsAcceptor::from(server.server_config.clone());
@@ -297,7 +335,14 @@ impl RpcServer {
     }
 
     pub fn init(config: &RpcServerConfig) -> Result<Arc<ServerConfig>, Error> {
-        let (certs, key, root_certs) = if let Some(ssl_info) = &config.ssl_info {
+        let (certs, key, root_certs) = Self::load_server_certs(config)?;
+        Self::init_with_resolver(ReloadableCertResolver::new(certs, key)?, root_certs)
+    }
+
+    fn load_server_certs(
+        config: &RpcServerConfig,
+    ) -> Result<(Vec<Certificate>, PrivateKey, Vec<Certificate>), Error> {
+        Ok(if let Some(ssl_info) = &config.ssl_info {
             (
                 load_certs(&format!(
                     "{}/{}",
@@ -331,7 +376,13 @@ impl RpcServer {
                 load_private_key_from_bytes(&key_bytes)?,
                 load_certs_from_bytes(CHIA_CA_CRT.as_bytes())?,
             )
-        };
+        })
+    }
+
+    pub fn init_with_resolver(
+        cert_resolver: Arc<ReloadableCertResolver>,
+        root_certs: Vec<Certificate>,
+    ) -> Result<Arc<ServerConfig>, Error> {
         let mut root_cert_store = RootCertStore::empty();
         for cert in root_certs {
             root_cert_store.add(&cert).map_err(|e| {
@@ -345,13 +396,7 @@ impl RpcServer {
             ServerConfig::builder()
                 .with_safe_defaults()
                 .with_client_cert_verifier(AllowAny::new(root_cert_store))
-                .with_single_cert(certs, key)
-                .map_err(|e| {
-                    Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("Invalid Cert for Server: {:?}", e),
-                    )
-                })?,
+                .with_cert_resolver(cert_resolver),
         ))
     }
 
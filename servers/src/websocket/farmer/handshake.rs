@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use blst::min_pk::SecretKey;
 use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48};
 use dg_xch_core::protocols::harvester::HarvesterHandshake;
-use dg_xch_core::protocols::shared::{Handshake, CAPABILITIES};
+use dg_xch_core::protocols::shared::{Capabilities, Handshake};
 use dg_xch_core::protocols::{
     ChiaMessage, MessageHandler, NodeType, PeerMap, ProtocolMessageTypes,
 };
@@ -47,6 +47,9 @@ impl MessageHandler for HandshakeHandle {
             let protocol_version = ChiaProtocolVersion::from_str(&handshake.protocol_version)
                 .expect("ChiaProtocolVersion::from_str is Infallible");
             *peer.protocol_version.write().await = protocol_version;
+            let capabilities = &self.config.websocket.capabilities;
+            *peer.capabilities.write().await =
+                capabilities.negotiate(&Capabilities::from_handshake(&handshake.capabilities));
             peer.websocket
                 .write()
                 .await
@@ -61,10 +64,7 @@ impl MessageHandler for HandshakeHandle {
                             software_version: version(),
                             server_port,
                             node_type: NodeType::Farmer as u8,
-                            capabilities: CAPABILITIES
-                                .iter()
-                                .map(|e| (e.0, e.1.to_string()))
-                                .collect(),
+                            capabilities: capabilities.to_handshake(),
                         },
                         msg.id,
                     )
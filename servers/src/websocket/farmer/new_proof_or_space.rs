@@ -7,10 +7,11 @@ use dg_xch_clients::websocket::oneshot;
 use dg_xch_core::blockchain::proof_of_space::{generate_plot_public_key, generate_taproot_sk};
 use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48, SizedBytes};
 use dg_xch_core::clvm::bls_bindings::{sign, sign_prepend, AUG_SCHEME_DST};
-use dg_xch_core::consensus::constants::{ConsensusConstants, CONSENSUS_CONSTANTS_MAP};
+use dg_xch_core::consensus::constants::ConsensusConstants;
 use dg_xch_core::consensus::pot_iterations::{
     calculate_iterations_quality, calculate_sp_interval_iters,
 };
+use dg_xch_core::consensus::registry::CONSENSUS_CONSTANTS_REGISTRY;
 #[cfg(feature = "metrics")]
 use dg_xch_core::protocols::farmer::FarmerMetrics;
 use dg_xch_core::protocols::farmer::{
@@ -66,10 +67,7 @@ impl<T: PoolClient + Sized + Sync + Send + 'static> MessageHandler for NewProofO
             let mut cursor = Cursor::new(&msg.data);
             let new_pos = NewProofOfSpace::from_bytes(&mut cursor, protocol_version)?;
             if let Some(sps) = self.signage_points.read().await.get(&new_pos.sp_hash) {
-                let constants = CONSENSUS_CONSTANTS_MAP
-                    .get(&self.config.network)
-                    .cloned()
-                    .unwrap_or_default();
+                let constants = CONSENSUS_CONSTANTS_REGISTRY.get_or_mainnet(&self.config.network);
                 for sp in sps {
                     if let Some(qs) = verify_and_get_quality_string(
                         &new_pos.proof,
@@ -7,7 +7,7 @@ use dg_xch_core::blockchain::pool_target::PoolTarget;
 use dg_xch_core::blockchain::proof_of_space::{generate_plot_public_key, generate_taproot_sk};
 use dg_xch_core::blockchain::sized_bytes::{Bytes32, Bytes48};
 use dg_xch_core::clvm::bls_bindings::{sign, sign_prepend, AUG_SCHEME_DST};
-use dg_xch_core::consensus::constants::{CONSENSUS_CONSTANTS_MAP, MAINNET};
+use dg_xch_core::consensus::registry::CONSENSUS_CONSTANTS_REGISTRY;
 #[cfg(feature = "metrics")]
 use dg_xch_core::protocols::farmer::FarmerMetrics;
 use dg_xch_core::protocols::farmer::{
@@ -93,12 +93,11 @@ impl<T: Sync + Send + 'static> MessageHandler for RespondSignaturesHandle<T> {
                 }
                 if let Some(pospace) = pospace {
                     let include_taproot = pospace.pool_contract_puzzle_hash.is_some();
-                    let constants = CONSENSUS_CONSTANTS_MAP
-                        .get(&self.config.network)
-                        .unwrap_or(&MAINNET);
+                    let constants =
+                        CONSENSUS_CONSTANTS_REGISTRY.get_or_mainnet(&self.config.network);
                     if let Some(computed_quality_string) = verify_and_get_quality_string(
                         &pospace,
-                        constants,
+                        &constants,
                         &response.challenge_hash,
                         &response.sp_hash,
                         peak_height,
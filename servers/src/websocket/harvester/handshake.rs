@@ -2,7 +2,7 @@ use crate::version;
 use crate::websocket::harvester::HarvesterServerConfig;
 use async_trait::async_trait;
 use dg_xch_core::blockchain::sized_bytes::Bytes32;
-use dg_xch_core::protocols::shared::{Handshake, CAPABILITIES};
+use dg_xch_core::protocols::shared::{Capabilities, Handshake};
 use dg_xch_core::protocols::{
     ChiaMessage, MessageHandler, NodeType, PeerMap, ProtocolMessageTypes,
 };
@@ -31,6 +31,9 @@ impl MessageHandler for HandshakeHandle {
             let protocol_version = ChiaProtocolVersion::from_str(&handshake.protocol_version)
                 .expect("ChiaProtocolVersion::from_str is Infallible");
             *peer.protocol_version.write().await = protocol_version;
+            let capabilities = &self.config.websocket.capabilities;
+            *peer.capabilities.write().await =
+                capabilities.negotiate(&Capabilities::from_handshake(&handshake.capabilities));
             peer.websocket
                 .write()
                 .await
@@ -44,10 +47,7 @@ impl MessageHandler for HandshakeHandle {
                             software_version: version(),
                             server_port: self.config.websocket.port,
                             node_type: NodeType::Harvester as u8,
-                            capabilities: CAPABILITIES
-                                .iter()
-                                .map(|e| (e.0, e.1.to_string()))
-                                .collect(),
+                            capabilities: capabilities.to_handshake(),
                         },
                         msg.id,
                     )
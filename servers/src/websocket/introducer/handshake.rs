@@ -0,0 +1,92 @@
+use crate::version;
+use crate::websocket::introducer::IntroducerServerConfig;
+use async_trait::async_trait;
+use dg_xch_clients::websocket::discovery::{is_public_host, AddressManager};
+use dg_xch_core::blockchain::peer_info::TimestampedPeerInfo;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::protocols::shared::{Capabilities, Handshake};
+use dg_xch_core::protocols::{
+    ChiaMessage, MessageHandler, NodeType, PeerMap, ProtocolMessageTypes,
+};
+use dg_xch_serialize::{ChiaProtocolVersion, ChiaSerialize};
+use hyper_tungstenite::tungstenite::Message;
+use log::debug;
+use std::io::{Cursor, Error, ErrorKind};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+pub struct HandshakeHandle {
+    pub config: Arc<IntroducerServerConfig>,
+    pub address_manager: Arc<RwLock<AddressManager>>,
+}
+#[async_trait]
+impl MessageHandler for HandshakeHandle {
+    async fn handle(
+        &self,
+        msg: Arc<ChiaMessage>,
+        peer_id: Arc<Bytes32>,
+        peers: PeerMap,
+    ) -> Result<(), Error> {
+        if let Some(peer) = peers.read().await.get(&peer_id).cloned() {
+            let mut cursor = Cursor::new(&msg.data);
+            let handshake =
+                Handshake::from_bytes(&mut cursor, *peer.protocol_version.read().await)?;
+            if handshake.network_id != self.config.network {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Peer is on network {}", handshake.network_id),
+                ));
+            }
+            let node_type = NodeType::from(handshake.node_type);
+            *peer.node_type.write().await = node_type.clone();
+            let protocol_version = ChiaProtocolVersion::from_str(&handshake.protocol_version)
+                .expect("ChiaProtocolVersion::from_str is Infallible");
+            *peer.protocol_version.write().await = protocol_version;
+            let capabilities = &self.config.websocket.capabilities;
+            *peer.capabilities.write().await =
+                capabilities.negotiate(&Capabilities::from_handshake(&handshake.capabilities));
+            //Full nodes are only candidates until the prober manages to connect back to them
+            if let (NodeType::FullNode, Some(addr)) = (node_type, peer.peer_addr) {
+                let host = addr.ip().to_string();
+                if self.config.enable_private_networks || is_public_host(&host) {
+                    debug!("Recording candidate peer {host}:{}", handshake.server_port);
+                    self.address_manager.write().await.add(
+                        &[TimestampedPeerInfo {
+                            host,
+                            port: handshake.server_port,
+                            timestamp: SystemTime::now()
+                                .duration_since(UNIX_EPOCH)
+                                .map(|d| d.as_secs())
+                                .unwrap_or_default(),
+                        }],
+                        "handshake",
+                    );
+                }
+            }
+            peer.websocket
+                .write()
+                .await
+                .send(Message::Binary(
+                    ChiaMessage::new(
+                        ProtocolMessageTypes::Handshake,
+                        protocol_version,
+                        &Handshake {
+                            network_id: self.config.network.clone(),
+                            protocol_version: protocol_version.to_string(),
+                            software_version: version(),
+                            server_port: self.config.websocket.port,
+                            node_type: NodeType::Introducer as u8,
+                            capabilities: capabilities.to_handshake(),
+                        },
+                        msg.id,
+                    )
+                    .to_bytes(protocol_version),
+                ))
+                .await
+        } else {
+            Err(Error::new(ErrorKind::NotFound, "Failed to find peer"))
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::dns::{DnsSeeder, DnsSeederConfig};
+use crate::websocket::introducer::handshake::HandshakeHandle;
+use crate::websocket::introducer::request_peers_introducer::RequestPeersIntroducerHandle;
+#[cfg(feature = "metrics")]
+use crate::websocket::WebSocketMetrics;
+use crate::websocket::{WebsocketServer, WebsocketServerConfig};
+use dg_xch_clients::websocket::discovery::AddressManager;
+use dg_xch_clients::websocket::full_node::FullnodeClient;
+use dg_xch_clients::websocket::{ServerVerification, WsClientConfig};
+use dg_xch_clients::ClientSSLConfig;
+use dg_xch_core::protocols::{ChiaMessageFilter, ChiaMessageHandler, ProtocolMessageTypes};
+use dg_xch_serialize::ChiaProtocolVersion;
+use log::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::io::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+pub mod handshake;
+pub mod request_peers_introducer;
+
+pub struct IntroducerServerConfig {
+    pub network: String,
+    pub websocket: WebsocketServerConfig,
+    pub max_peers_to_send: usize,
+    //Only peers reached within this many seconds are sent to other nodes
+    pub recent_peer_threshold: u64,
+    pub probe_interval: Duration,
+    pub probe_timeout: Duration,
+    pub probe_batch_size: usize,
+    //Client certificate used when connecting back to peers, a CA signed one is generated if None
+    pub client_ssl: Option<ClientSSLConfig>,
+    pub peers_file: Option<PathBuf>,
+    pub enable_private_networks: bool,
+    pub dns: Option<DnsSeederConfig>,
+}
+
+pub struct IntroducerServer {
+    pub server: WebsocketServer,
+    pub config: Arc<IntroducerServerConfig>,
+    pub address_manager: Arc<RwLock<AddressManager>>,
+}
+impl IntroducerServer {
+    pub fn new(
+        config: IntroducerServerConfig,
+        #[cfg(feature = "metrics")] metrics: Arc<Option<WebSocketMetrics>>,
+    ) -> Result<Self, Error> {
+        let config = Arc::new(config);
+        let address_manager = match &config.peers_file {
+            Some(path) if path.exists() => AddressManager::load(path)?,
+            _ => AddressManager::default(),
+        };
+        let address_manager = Arc::new(RwLock::new(address_manager));
+        let handles = Arc::new(RwLock::new(Self::handles(
+            config.clone(),
+            address_manager.clone(),
+        )));
+        Ok(Self {
+            server: WebsocketServer::new(
+                &config.websocket,
+                Default::default(),
+                handles,
+                #[cfg(feature = "metrics")]
+                metrics,
+            )?,
+            config,
+            address_manager,
+        })
+    }
+
+    fn handles(
+        config: Arc<IntroducerServerConfig>,
+        address_manager: Arc<RwLock<AddressManager>>,
+    ) -> HashMap<Uuid, Arc<ChiaMessageHandler>> {
+        HashMap::from([
+            (
+                Uuid::new_v4(),
+                Arc::new(ChiaMessageHandler::new(
+                    Arc::new(ChiaMessageFilter {
+                        msg_type: Some(ProtocolMessageTypes::Handshake),
+                        id: None,
+                    }),
+                    Arc::new(HandshakeHandle {
+                        config: config.clone(),
+                        address_manager: address_manager.clone(),
+                    }),
+                )),
+            ),
+            (
+                Uuid::new_v4(),
+                Arc::new(ChiaMessageHandler::new(
+                    Arc::new(ChiaMessageFilter {
+                        msg_type: Some(ProtocolMessageTypes::RequestPeersIntroducer),
+                        id: None,
+                    }),
+                    Arc::new(RequestPeersIntroducerHandle {
+                        config,
+                        address_manager,
+                    }),
+                )),
+            ),
+        ])
+    }
+
+    pub async fn run(&self, run: Arc<AtomicBool>) -> Result<(), Error> {
+        let prober = tokio::spawn(probe_peers(
+            self.config.clone(),
+            self.address_manager.clone(),
+            run.clone(),
+        ));
+        let dns = self.config.dns.clone().map(|dns_config| {
+            let seeder = DnsSeeder::new(dns_config, self.address_manager.clone());
+            let run = run.clone();
+            tokio::spawn(async move { seeder.run(run).await })
+        });
+        let res = self.server.run(run.clone()).await;
+        run.store(false, Ordering::Relaxed);
+        let _ = prober.await;
+        if let Some(dns) = dns {
+            if let Ok(Err(e)) = dns.await {
+                warn!("DNS seeder exited with error: {:?}", e);
+            }
+        }
+        res
+    }
+}
+
+//Connects back to known peers with a full handshake, promoting the ones that answer to the tried table
+async fn probe_peers(
+    config: Arc<IntroducerServerConfig>,
+    address_manager: Arc<RwLock<AddressManager>>,
+    run: Arc<AtomicBool>,
+) {
+    while run.load(Ordering::Relaxed) {
+        let candidates = {
+            let mut address_manager = address_manager.write().await;
+            let selected = address_manager.select(config.probe_batch_size, &HashSet::new());
+            for entry in &selected {
+                address_manager.attempt(&entry.host, entry.port);
+            }
+            selected
+        };
+        let mut probes = JoinSet::new();
+        for entry in candidates {
+            let client_config = Arc::new(WsClientConfig {
+                host: entry.host.clone(),
+                port: entry.port,
+                network_id: config.network.clone(),
+                ssl_info: config.client_ssl.clone(),
+                software_version: None,
+                protocol_version: ChiaProtocolVersion::default(),
+                additional_headers: None,
+                peer_policy: config.websocket.peer_policy.clone(),
+                capabilities: config.websocket.capabilities.clone(),
+                server_verification: ServerVerification::PublicChiaCa,
+            });
+            let timeout = config.probe_timeout;
+            let run = run.clone();
+            probes.spawn(async move {
+                let result =
+                    tokio::time::timeout(timeout, FullnodeClient::new(client_config, run)).await;
+                let reachable = match result {
+                    Ok(Ok(client)) => {
+                        let _ = client.join().await;
+                        true
+                    }
+                    Ok(Err(e)) => {
+                        debug!("Probe of {}:{} failed: {:?}", entry.host, entry.port, e);
+                        false
+                    }
+                    Err(_) => false,
+                };
+                (entry, reachable)
+            });
+        }
+        let mut reached = 0;
+        while let Some(res) = probes.join_next().await {
+            if let Ok((entry, true)) = res {
+                address_manager
+                    .write()
+                    .await
+                    .mark_good(&entry.host, entry.port);
+                reached += 1;
+            }
+        }
+        {
+            let address_manager = address_manager.read().await;
+            info!(
+                "Probed peers, {reached} reachable. Table has {} tried and {} new peers",
+                address_manager.len_tried(),
+                address_manager.len_new()
+            );
+            if let Some(path) = &config.peers_file {
+                if let Err(e) = address_manager.save(path) {
+                    warn!("Failed to save peer table: {:?}", e);
+                }
+            }
+        }
+        let mut waited = Duration::ZERO;
+        while waited < config.probe_interval && run.load(Ordering::Relaxed) {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            waited += Duration::from_millis(100);
+        }
+    }
+}
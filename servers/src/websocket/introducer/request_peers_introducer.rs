@@ -0,0 +1,56 @@
+use crate::websocket::introducer::IntroducerServerConfig;
+use async_trait::async_trait;
+use dg_xch_clients::websocket::discovery::AddressManager;
+use dg_xch_core::blockchain::sized_bytes::Bytes32;
+use dg_xch_core::protocols::introducer::RespondPeersIntroducer;
+use dg_xch_core::protocols::{ChiaMessage, MessageHandler, PeerMap, ProtocolMessageTypes};
+use dg_xch_serialize::ChiaSerialize;
+use hyper_tungstenite::tungstenite::Message;
+use log::debug;
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct RequestPeersIntroducerHandle {
+    pub config: Arc<IntroducerServerConfig>,
+    pub address_manager: Arc<RwLock<AddressManager>>,
+}
+#[async_trait]
+impl MessageHandler for RequestPeersIntroducerHandle {
+    async fn handle(
+        &self,
+        msg: Arc<ChiaMessage>,
+        peer_id: Arc<Bytes32>,
+        peers: PeerMap,
+    ) -> Result<(), Error> {
+        if let Some(peer) = peers.read().await.get(&peer_id).cloned() {
+            let mut peer_list = self.address_manager.read().await.reachable(
+                self.config.max_peers_to_send + 1,
+                self.config.recent_peer_threshold,
+            );
+            //Never hand a node its own address
+            if let Some(addr) = peer.peer_addr {
+                let host = addr.ip().to_string();
+                peer_list.retain(|p| p.host != host);
+            }
+            peer_list.truncate(self.config.max_peers_to_send);
+            debug!("Sending {} peers to {peer_id}", peer_list.len());
+            let protocol_version = *peer.protocol_version.read().await;
+            peer.websocket
+                .write()
+                .await
+                .send(Message::Binary(
+                    ChiaMessage::new(
+                        ProtocolMessageTypes::RespondPeersIntroducer,
+                        protocol_version,
+                        &RespondPeersIntroducer { peer_list },
+                        msg.id,
+                    )
+                    .to_bytes(protocol_version),
+                ))
+                .await
+        } else {
+            Err(Error::new(ErrorKind::NotFound, "Failed to find peer"))
+        }
+    }
+}
@@ -1,13 +1,17 @@
 pub mod farmer;
 pub mod harvester;
+pub mod introducer;
 
 use dg_xch_core::blockchain::sized_bytes::{Bytes32, SizedBytes};
+use dg_xch_core::protocols::rate_limits::PeerPolicy;
+use dg_xch_core::protocols::shared::Capabilities;
 use dg_xch_core::protocols::{
     ChiaMessageHandler, NodeType, PeerMap, SocketPeer, WebsocketConnection, WebsocketMsgStream,
 };
 use dg_xch_core::ssl::{
     generate_ca_signed_cert_data, load_certs, load_certs_from_bytes, load_private_key,
-    load_private_key_from_bytes, AllowAny, SslInfo, CHIA_CA_CRT, CHIA_CA_KEY,
+    load_private_key_from_bytes, watch_cert_files, AllowAny, ReloadableCertResolver, SslInfo,
+    CHIA_CA_CRT, CHIA_CA_KEY,
 };
 use dg_xch_serialize::{hash_256, ChiaProtocolVersion};
 use http_body_util::Full;
@@ -36,10 +40,16 @@ use tokio_tungstenite::tungstenite;
 use tokio_tungstenite::tungstenite::error::TlsError;
 use uuid::Uuid;
 
+const CERT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct WebsocketServerConfig {
     pub host: String,
     pub port: u16,
     pub ssl_info: Option<SslInfo>,
+    //Rate limits, connection cap and bans applied to every peer, no limits are enforced if None
+    pub peer_policy: Option<Arc<PeerPolicy>>,
+    //Capabilities advertised in the handshake, only those the peer also advertises are used
+    pub capabilities: Capabilities,
 }
 
 #[cfg(feature = "metrics")]
@@ -52,6 +62,9 @@ pub struct WebsocketServer {
     pub server_config: Arc<ServerConfig>,
     pub peers: PeerMap,
     pub message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+    pub peer_policy: Option<Arc<PeerPolicy>>,
+    pub cert_resolver: Arc<ReloadableCertResolver>,
+    pub ssl_info: Option<SslInfo>,
     #[cfg(feature = "metrics")]
     pub metrics: Arc<Option<WebSocketMetrics>>,
 }
@@ -86,7 +99,8 @@ impl WebsocketServer {
                 load_certs_from_bytes(CHIA_CA_CRT.as_bytes())?,
             )
         };
-        let server_config = Self::init(certs, key, root_certs)
+        let cert_resolver = ReloadableCertResolver::new(certs, key)?;
+        let server_config = Self::init_with_resolver(cert_resolver.clone(), root_certs)
             .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("Invalid Cert: {:?}", e)))?;
         let socket_address = Self::init_socket(config)?;
         Ok(WebsocketServer {
@@ -94,6 +108,9 @@ impl WebsocketServer {
             server_config,
             peers,
             message_handlers,
+            peer_policy: config.peer_policy.clone(),
+            cert_resolver,
+            ssl_info: config.ssl_info.clone(),
             #[cfg(feature = "metrics")]
             metrics,
         })
@@ -113,7 +130,8 @@ impl WebsocketServer {
             load_private_key_from_bytes(&key_bytes)?,
             load_certs_from_bytes(cert_data.as_bytes())?,
         );
-        let server_config = Self::init(certs, key, root_certs)
+        let cert_resolver = ReloadableCertResolver::new(certs, key)?;
+        let server_config = Self::init_with_resolver(cert_resolver.clone(), root_certs)
             .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("Invalid Cert: {:?}", e)))?;
         let socket_address = Self::init_socket(config)?;
         Ok(WebsocketServer {
@@ -121,12 +139,44 @@ impl WebsocketServer {
             server_config,
             peers,
             message_handlers,
+            peer_policy: config.peer_policy.clone(),
+            cert_resolver,
+            ssl_info: None,
             #[cfg(feature = "metrics")]
             metrics,
         })
     }
 
+    //Reloads the certificate and key from ssl_info, new connections use them straight away
+    pub fn reload_certs(&self) -> Result<(), Error> {
+        let (crt_path, key_path) = self.cert_paths().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "Server was not started with cert files to reload",
+            )
+        })?;
+        self.cert_resolver.reload_from_files(&crt_path, &key_path)
+    }
+
+    fn cert_paths(&self) -> Option<(String, String)> {
+        self.ssl_info.as_ref().map(|ssl_info| {
+            (
+                format!("{}/{}", &ssl_info.root_path, &ssl_info.certs.private_crt),
+                format!("{}/{}", &ssl_info.root_path, &ssl_info.certs.private_key),
+            )
+        })
+    }
+
     pub async fn run(&self, run: Arc<AtomicBool>) -> Result<(), Error> {
+        if let Some((crt_path, key_path)) = self.cert_paths() {
+            tokio::spawn(watch_cert_files(
+                self.cert_resolver.clone(),
+                crt_path,
+                key_path,
+                CERT_CHECK_INTERVAL,
+                run.clone(),
+            ));
+        }
         let listener = TcpListener::bind(self.socket_address).await?;
         let acceptor = TlsAcceptor::from(self.server_config.clone());
         let mut http = Builder::new();
@@ -135,12 +185,23 @@ impl WebsocketServer {
             let run = run.clone();
             let peers = self.peers.clone();
             let handlers = self.message_handlers.clone();
+            let peer_policy = self.peer_policy.clone();
             #[cfg(feature = "metrics")]
             let metrics = self.metrics.clone();
             select!(
                 res = listener.accept() => {
                     match res {
-                        Ok((stream, _)) => {
+                        Ok((stream, remote_addr)) => {
+                            if let Some(policy) = &peer_policy {
+                                if policy.bans.is_banned(&remote_addr.ip()) {
+                                    debug!("Rejected connection from banned peer {}", remote_addr.ip());
+                                    continue;
+                                }
+                                if peers.read().await.len() >= policy.max_peers {
+                                    debug!("Rejected connection from {remote_addr}, at max peers");
+                                    continue;
+                                }
+                            }
                             let peers = peers.clone();
                             let message_handlers = handlers.clone();
                             #[cfg(feature = "metrics")]
@@ -162,6 +223,7 @@ impl WebsocketServer {
                                             req,
                                             peers: peers.clone(),
                                             message_handlers: message_handlers.clone(),
+                                            peer_policy: peer_policy.clone(),
                                             run: run.clone(),
                                         };
                                         connection_handler(
@@ -198,6 +260,13 @@ impl WebsocketServer {
         certs: Vec<Certificate>,
         key: PrivateKey,
         root_certs: Vec<Certificate>,
+    ) -> Result<Arc<ServerConfig>, Error> {
+        Self::init_with_resolver(ReloadableCertResolver::new(certs, key)?, root_certs)
+    }
+
+    pub fn init_with_resolver(
+        cert_resolver: Arc<ReloadableCertResolver>,
+        root_certs: Vec<Certificate>,
     ) -> Result<Arc<ServerConfig>, Error> {
         let mut root_cert_store = RootCertStore::empty();
         for cert in root_certs {
@@ -212,13 +281,7 @@ impl WebsocketServer {
             ServerConfig::builder()
                 .with_safe_defaults()
                 .with_client_cert_verifier(AllowAny::new(root_cert_store))
-                .with_single_cert(certs, key)
-                .map_err(|e| {
-                    Error::new(
-                        ErrorKind::InvalidInput,
-                        format!("Invalid Cert for Server: {:?}", e),
-                    )
-                })?,
+                .with_cert_resolver(cert_resolver),
         ))
     }
 
@@ -246,6 +309,7 @@ struct ConnectionData {
     pub req: Request<Incoming>,
     pub peers: PeerMap,
     pub message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+    pub peer_policy: Option<Arc<PeerPolicy>>,
     pub run: Arc<AtomicBool>,
 }
 
@@ -290,6 +354,7 @@ async fn connection_handler(
                 websocket,
                 data.peers,
                 data.message_handlers.clone(),
+                data.peer_policy.clone(),
                 data.run.clone(),
             )
             .await
@@ -312,11 +377,12 @@ async fn connection_handler(
 }
 
 async fn handle_connection(
-    _peer_addr: SocketAddr,
+    peer_addr: SocketAddr,
     peer_id: Arc<Bytes32>,
     websocket: HyperWebsocket,
     peers: PeerMap,
     message_handlers: Arc<RwLock<HashMap<Uuid, Arc<ChiaMessageHandler>>>>,
+    peer_policy: Option<Arc<PeerPolicy>>,
     run: Arc<AtomicBool>,
 ) -> Result<(), tungstenite::error::Error> {
     let (websocket, mut stream) = WebsocketConnection::new(
@@ -325,12 +391,19 @@ async fn handle_connection(
         peer_id.clone(),
         peers.clone(),
     );
+    if let Some(policy) = peer_policy {
+        stream.set_peer_policy(policy);
+    }
+    let capabilities = websocket.capabilities();
+    let websocket = Arc::new(RwLock::new(websocket));
     let removed = peers.write().await.insert(
         *peer_id,
         Arc::new(SocketPeer {
+            peer_addr: Some(peer_addr),
             node_type: Arc::new(RwLock::new(NodeType::Unknown)),
             protocol_version: Arc::new(RwLock::new(ChiaProtocolVersion::default())),
-            websocket: Arc::new(RwLock::new(websocket)),
+            capabilities,
+            websocket: websocket.clone(),
         }),
     );
     if let Some(removed) = removed {
@@ -338,5 +411,13 @@ async fn handle_connection(
         let _ = removed.websocket.write().await.close(None).await;
     }
     let _ = stream.run(run).await;
+    //Free the slot unless the peer already reconnected with a new connection
+    let mut peers = peers.write().await;
+    if peers
+        .get(&peer_id)
+        .is_some_and(|p| Arc::ptr_eq(&p.websocket, &websocket))
+    {
+        peers.remove(&peer_id);
+    }
     Ok(())
 }
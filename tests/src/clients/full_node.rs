@@ -133,7 +133,7 @@ pub async fn get_parent_spend(
 #[tokio::test]
 pub async fn test_farmer_ws_client() {
     use dg_xch_clients::websocket::farmer::FarmerClient;
-    use dg_xch_clients::websocket::WsClientConfig;
+    use dg_xch_clients::websocket::{ServerVerification, WsClientConfig};
     use dg_xch_core::protocols::farmer::FarmerSharedState;
     use dg_xch_core::protocols::{ChiaMessageFilter, ChiaMessageHandler, ProtocolMessageTypes};
     use futures_util::future::try_join_all;
@@ -161,6 +161,9 @@ pub async fn test_farmer_ws_client() {
         ssl_info: None,
         software_version: None,
         additional_headers: None,
+        peer_policy: None,
+        capabilities: Default::default(),
+        server_verification: ServerVerification::PublicChiaCa,
     });
     let shared_state = Arc::new(FarmerSharedState::<()> {
         ..Default::default()